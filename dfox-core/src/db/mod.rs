@@ -1,4 +1,7 @@
-use crate::{errors::DbError, models::schema::TableSchema};
+use crate::{
+    errors::DbError,
+    models::schema::{DbObject, RoutineSchema, TableSchema},
+};
 use async_trait::async_trait;
 
 pub mod mysql;
@@ -7,17 +10,43 @@ pub mod sqlite;
 
 #[async_trait]
 pub trait DbClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError>;
+    /// Runs a statement that returns no rows and reports how many rows it affected.
+    async fn execute(&self, query: &str) -> Result<u64, DbError>;
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
-    async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
+    /// Like `query`, but streams the rows and stops after `max_rows`. The flag
+    /// is `true` when more rows were left unread.
+    async fn query_limited(
+        &self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError>;
+    /// Opens a transaction on a connection of its own, which it keeps until it
+    /// is committed or rolled back.
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError>;
     async fn list_databases(&self) -> Result<Vec<String>, DbError>;
     async fn list_tables(&self) -> Result<Vec<String>, DbError>;
+    /// Views, materialized views, sequences, functions and procedures,
+    /// ordered by kind and then name.
+    async fn list_objects(&self) -> Result<Vec<DbObject>, DbError>;
     async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
+    /// The arguments and source of a function or procedure, named as
+    /// `list_objects` lists it.
+    async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError>;
+    /// Switches the database used by this client without opening a new pool.
+    async fn use_database(&self, database: &str) -> Result<(), DbError>;
 }
 
 #[async_trait]
 pub trait Transaction {
-    async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError>;
+    /// Runs a statement inside the transaction and reports how many rows it affected.
+    async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError>;
+    /// Like `DbClient::query_limited`, but reads inside the transaction, so
+    /// uncommitted changes are visible.
+    async fn query_transaction(
+        &mut self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError>;
     async fn commit_transaction(self: Box<Self>) -> Result<(), DbError>;
     async fn rollback_transaction(self: Box<Self>) -> Result<(), DbError>;
 }
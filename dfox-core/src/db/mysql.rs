@@ -1,11 +1,27 @@
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
+use futures_util::TryStreamExt;
 use serde_json::Value;
-use sqlx::{mysql::MySqlPoolOptions, Column, MySqlPool, Row, TypeInfo};
+use sqlx::{
+    mysql::{MySqlPoolOptions, MySqlRow},
+    Column, Executor, MySqlPool, Row, TypeInfo,
+};
+use std::{
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::{
+        connections::DbType,
+        schema::{
+            ColumnSchema, DbObject, ForeignKeySchema, IndexSchema, ObjectKind, RoutineArgument,
+            RoutineSchema, TableSchema,
+        },
+        value::{binary_value, number_value},
+    },
+    sql,
 };
 
 use super::{DbClient, Transaction};
@@ -14,7 +30,10 @@ use super::{DbClient, Transaction};
 enum ColumnType {
     Timestamp,
     Int,
+    UnsignedInt,
+    Decimal,
     Text,
+    Bytes,
     Unknown,
 }
 
@@ -22,37 +41,143 @@ impl ColumnType {
     fn from_type_name(type_name: &str) -> Self {
         match type_name {
             "TIMESTAMP" | "DATETIME" => ColumnType::Timestamp,
-            "INT" | "BIGINT" => ColumnType::Int,
+            "TINYINT" | "SMALLINT" | "MEDIUMINT" | "INT" | "BIGINT" => ColumnType::Int,
+            "TINYINT UNSIGNED" | "SMALLINT UNSIGNED" | "MEDIUMINT UNSIGNED" | "INT UNSIGNED"
+            | "BIGINT UNSIGNED" => ColumnType::UnsignedInt,
+            "DECIMAL" => ColumnType::Decimal,
             "TEXT" | "VARCHAR" => ColumnType::Text,
+            "BLOB" | "TINYBLOB" | "MEDIUMBLOB" | "LONGBLOB" | "BINARY" | "VARBINARY" => {
+                ColumnType::Bytes
+            }
             _ => ColumnType::Unknown,
         }
     }
 }
 
+/// A database picked with `use_database`, and when.
+struct SelectedDatabase {
+    name: String,
+    selected_at: Instant,
+}
+
+impl SelectedDatabase {
+    /// Whether a connection of this `age` was opened before the switch, so
+    /// may still be on the previous database.
+    fn predates(&self, age: Duration) -> bool {
+        age > self.selected_at.elapsed()
+    }
+}
+
 pub struct MySqlClient {
     pub pool: MySqlPool,
+    /// Database selected with `use_database`, applied to every new pooled connection.
+    database: Arc<RwLock<Option<SelectedDatabase>>>,
 }
 
 impl MySqlClient {
-    pub async fn connect(database_url: &str) -> Result<Self, DbError> {
+    /// Opens a pool on `database_url`. With `read_only`, every connection in
+    /// it is set read-only, so the server refuses writes.
+    pub async fn connect(database_url: &str, read_only: bool) -> Result<Self, DbError> {
+        let database: Arc<RwLock<Option<SelectedDatabase>>> = Arc::new(RwLock::new(None));
+        let selected_database = database.clone();
+        let switched_database = database.clone();
+
         let pool = MySqlPoolOptions::new()
             .max_connections(5)
+            .after_connect(move |conn, _meta| {
+                let selected = selected_database
+                    .read()
+                    .ok()
+                    .and_then(|db| db.as_ref().map(|db| db.name.clone()));
+                Box::pin(async move {
+                    if read_only {
+                        conn.execute("SET SESSION TRANSACTION READ ONLY").await?;
+                    }
+                    if let Some(db_name) = selected {
+                        conn.execute(use_statement(&db_name).as_str()).await?;
+                    }
+                    Ok(())
+                })
+            })
+            // `USE` is per connection, so one opened before the last switch,
+            // even if it was checked out then, is closed rather than handed
+            // out again; its replacement picks the database up above.
+            .before_acquire(move |_conn, meta| {
+                let opened_before_switch = switched_database
+                    .read()
+                    .ok()
+                    .is_some_and(|db| db.as_ref().is_some_and(|db| db.predates(meta.age)));
+                Box::pin(async move { Ok(!opened_before_switch) })
+            })
             .connect(database_url)
             .await
             .map_err(|e| DbError::Connection(e.to_string()))?;
 
-        Ok(Self { pool })
+        Ok(Self { pool, database })
     }
 }
 
+fn use_statement(database: &str) -> String {
+    format!("USE {}", sql::quote_identifier(&DbType::MySql, database))
+}
+
+fn row_to_json(row: &MySqlRow) -> Value {
+    let json_map = row
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let column_name = column.name();
+            let column_type = ColumnType::from_type_name(column.type_info().name());
+
+            let value: Value = match column_type {
+                ColumnType::Timestamp => match row.try_get::<NaiveDateTime, _>(i) {
+                    Ok(timestamp) => Value::String(timestamp.to_string()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Int => match row.try_get::<i64, _>(i) {
+                    Ok(int_val) => Value::Number(int_val.into()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::UnsignedInt => match row.try_get::<u64, _>(i) {
+                    Ok(int_val) => Value::Number(int_val.into()),
+                    Err(_) => Value::Null,
+                },
+                // MySQL sends DECIMAL values as text, which is decoded as is
+                // rather than through a decimal type.
+                ColumnType::Decimal => match row.try_get_unchecked::<String, _>(i) {
+                    Ok(text) => number_value(&text),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Text => match row.try_get::<String, _>(i) {
+                    Ok(text) => Value::String(text),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Bytes => match row.try_get::<Vec<u8>, _>(i) {
+                    Ok(bytes) => binary_value(&bytes),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Unknown => match row.try_get::<String, _>(i) {
+                    Ok(val) => Value::String(val),
+                    Err(_) => Value::Null,
+                },
+            };
+
+            (column_name.to_string(), value)
+        })
+        .collect();
+
+    Value::Object(json_map)
+}
+
 #[async_trait]
 impl DbClient for MySqlClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&self.pool)
             .await
             .map_err(DbError::Sqlx)?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
@@ -61,48 +186,28 @@ impl DbClient for MySqlClient {
             .await
             .map_err(DbError::Sqlx)?;
 
-        let results = rows
-            .iter()
-            .map(|row| {
-                let json_map = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, column)| {
-                        let column_name = column.name();
-                        let column_type = ColumnType::from_type_name(column.type_info().name());
-
-                        let value: Value = match column_type {
-                            ColumnType::Timestamp => match row.try_get::<NaiveDateTime, _>(i) {
-                                Ok(timestamp) => Value::String(timestamp.to_string()),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Int => match row.try_get::<i64, _>(i) {
-                                Ok(int_val) => Value::Number(int_val.into()),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Text => match row.try_get::<String, _>(i) {
-                                Ok(text) => Value::String(text),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Unknown => match row.try_get::<String, _>(i) {
-                                Ok(val) => Value::String(val),
-                                Err(_) => Value::Null,
-                            },
-                        };
-
-                        (column_name.to_string(), value)
-                    })
-                    .collect();
-
-                Value::Object(json_map)
-            })
-            .collect();
+        Ok(rows.iter().map(row_to_json).collect())
+    }
 
-        Ok(results)
+    async fn query_limited(
+        &self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&self.pool);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows.try_next().await.map_err(DbError::Sqlx)? {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
+
+        Ok((results, false))
     }
 
-    async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError> {
         let tx = self
             .pool
             .begin()
@@ -131,7 +236,8 @@ impl DbClient for MySqlClient {
     }
 
     async fn list_tables(&self) -> Result<Vec<String>, DbError> {
-        let query = "SHOW TABLES";
+        // Views are listed by `list_objects`.
+        let query = "SHOW FULL TABLES WHERE Table_type = 'BASE TABLE'";
 
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
@@ -149,8 +255,50 @@ impl DbClient for MySqlClient {
         Ok(tables)
     }
 
+    async fn list_objects(&self) -> Result<Vec<DbObject>, DbError> {
+        // MySQL has no materialized views; only MariaDB has sequences.
+        let rows = sqlx::query(
+            r#"
+            SELECT object_name, object_type
+            FROM (
+                SELECT CAST(TABLE_NAME AS CHAR) AS object_name,
+                       CAST(TABLE_TYPE AS CHAR) AS object_type
+                FROM information_schema.TABLES
+                WHERE TABLE_SCHEMA = DATABASE() AND TABLE_TYPE IN ('VIEW', 'SEQUENCE')
+                UNION ALL
+                SELECT CAST(ROUTINE_NAME AS CHAR), CAST(ROUTINE_TYPE AS CHAR)
+                FROM information_schema.ROUTINES
+                WHERE ROUTINE_SCHEMA = DATABASE()
+            ) AS objects
+            ORDER BY FIELD(object_type, 'VIEW', 'SEQUENCE', 'FUNCTION', 'PROCEDURE'), object_name
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let objects = rows
+            .iter()
+            .map(|row| DbObject {
+                name: row.try_get("object_name").unwrap_or_default(),
+                kind: match row.try_get::<String, _>("object_type").as_deref() {
+                    Ok("SEQUENCE") => ObjectKind::Sequence,
+                    Ok("FUNCTION") => ObjectKind::Function,
+                    Ok("PROCEDURE") => ObjectKind::Procedure,
+                    _ => ObjectKind::View,
+                },
+            })
+            .collect();
+
+        Ok(objects)
+    }
+
     async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!("DESCRIBE {}", table_name);
+        // Like DESCRIBE, plus the Comment column.
+        let query = format!(
+            "SHOW FULL COLUMNS FROM {}",
+            sql::quote_identifier(&DbType::MySql, table_name)
+        );
         let rows = sqlx::query(&query)
             .fetch_all(&self.pool)
             .await
@@ -173,29 +321,210 @@ impl DbClient for MySqlClient {
                     .try_get::<Option<String>, _>("Default")
                     .ok()
                     .unwrap_or(None),
+                is_primary_key: row
+                    .try_get::<String, _>("Key")
+                    .map(|key| key == "PRI")
+                    .unwrap_or(false),
+                comment: row
+                    .try_get::<String, _>("Comment")
+                    .ok()
+                    .filter(|comment| !comment.is_empty()),
             })
             .collect();
 
+        // information_schema returns binary strings on some servers, so cast to CHAR.
+        let index_rows = sqlx::query(
+            r#"
+            SELECT CAST(INDEX_NAME AS CHAR) AS index_name,
+                   CAST(COLUMN_NAME AS CHAR) AS column_name,
+                   CAST(NON_UNIQUE AS SIGNED) AS non_unique
+            FROM information_schema.STATISTICS
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+            ORDER BY INDEX_NAME, SEQ_IN_INDEX
+            "#,
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let indexes = IndexSchema::from_rows(index_rows.iter().filter_map(|row| {
+            Some((
+                row.try_get("index_name").ok()?,
+                row.try_get("column_name").ok()?,
+                row.try_get::<i64, _>("non_unique").ok()? == 0,
+            ))
+        }));
+
+        let foreign_key_rows = sqlx::query(
+            r#"
+            SELECT CAST(CONSTRAINT_NAME AS CHAR) AS constraint_name,
+                   CAST(COLUMN_NAME AS CHAR) AS column_name,
+                   CAST(REFERENCED_TABLE_NAME AS CHAR) AS referenced_table,
+                   CAST(REFERENCED_COLUMN_NAME AS CHAR) AS referenced_column
+            FROM information_schema.KEY_COLUMN_USAGE
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+              AND REFERENCED_TABLE_NAME IS NOT NULL
+            ORDER BY CONSTRAINT_NAME, ORDINAL_POSITION
+            "#,
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let foreign_keys = ForeignKeySchema::from_rows(foreign_key_rows.iter().filter_map(|row| {
+            Some((
+                row.try_get("constraint_name").ok()?,
+                row.try_get("column_name").ok()?,
+                row.try_get("referenced_table").ok()?,
+                row.try_get("referenced_column").ok()?,
+            ))
+        }));
+
+        let definition = sqlx::query(
+            r#"
+            SELECT CAST(VIEW_DEFINITION AS CHAR) AS definition
+            FROM information_schema.VIEWS
+            WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME = ?
+            "#,
+        )
+        .bind(table_name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .and_then(|row| row.try_get("definition").ok());
+
         Ok(TableSchema {
             table_name: table_name.to_string(),
             columns,
-            indexes: Vec::new(),
+            indexes,
+            foreign_keys,
+            definition,
+        })
+    }
+
+    async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError> {
+        let routine = sqlx::query(
+            r#"
+            SELECT CAST(ROUTINE_TYPE AS CHAR) AS routine_type,
+                   CAST(DTD_IDENTIFIER AS CHAR) AS return_type,
+                   CAST(ROUTINE_DEFINITION AS CHAR) AS source
+            FROM information_schema.ROUTINES
+            WHERE ROUTINE_SCHEMA = DATABASE() AND ROUTINE_NAME = ?
+            ORDER BY ROUTINE_TYPE
+            LIMIT 1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .ok_or_else(|| DbError::General(format!("Routine {} not found", name)))?;
+        let routine_type: String = routine.try_get("routine_type").unwrap_or_default();
+
+        // Position 0 is a function's return value.
+        let argument_rows = sqlx::query(
+            r#"
+            SELECT CAST(PARAMETER_NAME AS CHAR) AS argument_name,
+                   CAST(DTD_IDENTIFIER AS CHAR) AS data_type,
+                   CAST(PARAMETER_MODE AS CHAR) AS mode
+            FROM information_schema.PARAMETERS
+            WHERE SPECIFIC_SCHEMA = DATABASE() AND SPECIFIC_NAME = ? AND ROUTINE_TYPE = ?
+              AND ORDINAL_POSITION > 0
+            ORDER BY ORDINAL_POSITION
+            "#,
+        )
+        .bind(name)
+        .bind(&routine_type)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let arguments = argument_rows
+            .iter()
+            .map(|row| RoutineArgument {
+                name: row.try_get("argument_name").unwrap_or_default(),
+                data_type: row.try_get("data_type").unwrap_or_default(),
+                // Function arguments have no mode and are always IN.
+                is_input: row
+                    .try_get::<Option<String>, _>("mode")
+                    .ok()
+                    .flatten()
+                    .is_none_or(|mode| mode != "OUT"),
+            })
+            .collect();
+
+        let is_procedure = routine_type == "PROCEDURE";
+        Ok(RoutineSchema {
+            name: name.to_string(),
+            kind: if is_procedure {
+                ObjectKind::Procedure
+            } else {
+                ObjectKind::Function
+            },
+            arguments,
+            return_type: routine
+                .try_get::<Option<String>, _>("return_type")
+                .ok()
+                .flatten()
+                .filter(|_| !is_procedure),
+            source: routine.try_get("source").ok().flatten(),
         })
     }
+
+    async fn use_database(&self, database: &str) -> Result<(), DbError> {
+        // Fails, leaving the pool as it was, if there is no such database.
+        self.pool
+            .execute(use_statement(database).as_str())
+            .await
+            .map_err(DbError::Sqlx)?;
+
+        // Every pooled connection is now recycled before its next use.
+        if let Ok(mut selected) = self.database.write() {
+            *selected = Some(SelectedDatabase {
+                name: database.to_string(),
+                selected_at: Instant::now(),
+            });
+        }
+        Ok(())
+    }
 }
 
-pub struct MySqlTransaction<'a> {
-    tx: sqlx::Transaction<'a, sqlx::MySql>,
+pub struct MySqlTransaction {
+    tx: sqlx::Transaction<'static, sqlx::MySql>,
 }
 
 #[async_trait]
-impl<'a> Transaction for MySqlTransaction<'a> {
-    async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+impl Transaction for MySqlTransaction {
+    async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&mut *self.tx)
             .await
             .map_err(|e| DbError::Transaction(e.to_string()))?;
-        Ok(())
+        Ok(result.rows_affected())
+    }
+
+    async fn query_transaction(
+        &mut self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&mut *self.tx);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .map_err(|e| DbError::Transaction(e.to_string()))?
+        {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
+
+        Ok((results, false))
     }
 
     async fn commit_transaction(self: Box<Self>) -> Result<(), DbError> {
@@ -227,15 +556,29 @@ mod tests {
 
         #[async_trait]
         impl DbClient for DbClientMock {
-            async fn execute(&self, query: &str) -> Result<(), DbError>;
+            async fn execute(&self, query: &str) -> Result<u64, DbError>;
             async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn query_limited(&self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn list_databases(&self) -> Result<Vec<String>, DbError>;
             async fn list_tables(&self) -> Result<Vec<String>, DbError>;
+            async fn list_objects(&self) -> Result<Vec<DbObject>, DbError>;
             async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
-            async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
+            async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError>;
+            async fn use_database(&self, database: &str) -> Result<(), DbError>;
+            async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError>;
         }
     }
 
+    #[test]
+    fn test_connections_opened_before_use_are_recycled() {
+        let selected = SelectedDatabase {
+            name: "shop".to_string(),
+            selected_at: Instant::now() - Duration::from_secs(10),
+        };
+        assert!(selected.predates(Duration::from_secs(60)));
+        assert!(!selected.predates(Duration::from_secs(5)));
+    }
+
     #[tokio::test]
     async fn test_list_databases() {
         let mut mock_db = MockDbClientMock::new();
@@ -269,12 +612,12 @@ mod tests {
             .with(predicate::eq(
                 "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_db
             .execute("INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')")
             .await;
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
     }
 
     #[tokio::test]
@@ -310,15 +653,21 @@ mod tests {
                     data_type: "INT".to_string(),
                     is_nullable: false,
                     default: None,
+                    is_primary_key: true,
+                    comment: None,
                 },
                 ColumnSchema {
                     name: "name".to_string(),
                     data_type: "VARCHAR".to_string(),
                     is_nullable: true,
                     default: None,
+                    is_primary_key: false,
+                    comment: None,
                 },
             ],
             indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            definition: None,
         };
 
         mock_db
@@ -338,7 +687,8 @@ mod tests {
 
         #[async_trait::async_trait]
         impl Transaction for Transaction {
-            async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError>;
+            async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError>;
+            async fn query_transaction(&mut self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn commit_transaction(self: Box<Self>) -> Result<(), DbError>;
             async fn rollback_transaction(self: Box<Self>) -> Result<(), DbError>;
         }
@@ -354,7 +704,7 @@ mod tests {
             .with(mockall::predicate::eq(
                 "INSERT INTO users (name) VALUES ('Bob')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let mock_tx = std::cell::RefCell::new(Some(mock_tx));
 
@@ -396,7 +746,7 @@ mod tests {
         mock_tx
             .expect_execute_transaction()
             .with(predicate::eq("INSERT INTO users (name) VALUES ('Alice')"))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_tx
             .execute_transaction("INSERT INTO users (name) VALUES ('Alice')")
@@ -1,12 +1,24 @@
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use futures_util::TryStreamExt;
 use serde_json::Value;
-use sqlx::{postgres::PgPoolOptions, Column, PgPool, Row, TypeInfo};
+use sqlx::{
+    postgres::{PgPoolOptions, PgRow, PgValueFormat, PgValueRef},
+    Column, PgPool, Row, TypeInfo,
+};
 use uuid::Uuid;
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::{
+        connections::DbType,
+        schema::{
+            ColumnSchema, DbObject, ForeignKeySchema, IndexSchema, ObjectKind, RoutineArgument,
+            RoutineSchema, TableSchema,
+        },
+        value::{binary_value, number_value},
+    },
+    sql,
 };
 
 use super::{DbClient, Transaction};
@@ -15,8 +27,13 @@ use super::{DbClient, Transaction};
 enum ColumnType {
     Uuid,
     Timestamp,
+    TimestampTz,
+    SmallInt,
     Int,
+    BigInt,
+    Numeric,
     Text,
+    Bytes,
     Unknown,
 }
 
@@ -24,9 +41,14 @@ impl ColumnType {
     fn from_type_name(type_name: &str) -> Self {
         match type_name {
             "UUID" => ColumnType::Uuid,
-            "TIMESTAMP" | "TIMESTAMPTZ" => ColumnType::Timestamp,
+            "TIMESTAMP" => ColumnType::Timestamp,
+            "TIMESTAMPTZ" => ColumnType::TimestampTz,
+            "INT2" => ColumnType::SmallInt,
             "INT4" => ColumnType::Int,
+            "INT8" => ColumnType::BigInt,
+            "NUMERIC" => ColumnType::Numeric,
             "TEXT" | "VARCHAR" => ColumnType::Text,
+            "BYTEA" => ColumnType::Bytes,
             _ => ColumnType::Unknown,
         }
     }
@@ -48,14 +70,138 @@ impl PostgresClient {
     }
 }
 
+/// The exact text of a `NUMERIC` value, or `None` if it is NULL or malformed.
+///
+/// sqlx can only decode `NUMERIC` through the `bigdecimal` or `rust_decimal`
+/// crates, so it is read from the raw value instead.
+fn numeric_text(value: PgValueRef<'_>) -> Option<String> {
+    let bytes = value.as_bytes().ok()?;
+    match value.format() {
+        PgValueFormat::Text => std::str::from_utf8(bytes).ok().map(str::to_string),
+        PgValueFormat::Binary => numeric_from_binary(bytes),
+    }
+}
+
+/// Reads the binary `NUMERIC` format: digit count, weight, sign and display
+/// scale, followed by base-10000 digits.
+fn numeric_from_binary(bytes: &[u8]) -> Option<String> {
+    let word = |i: usize| -> Option<i16> {
+        Some(i16::from_be_bytes(
+            bytes.get(i * 2..i * 2 + 2)?.try_into().ok()?,
+        ))
+    };
+    let digit_count = word(0)? as usize;
+    let weight = word(1)? as isize;
+    let sign = word(2)? as u16;
+    let scale = word(3)? as usize;
+    let digits = (0..digit_count)
+        .map(|i| word(4 + i))
+        .collect::<Option<Vec<_>>>()?;
+    let digit = |position: isize| -> i16 {
+        usize::try_from(position)
+            .ok()
+            .and_then(|position| digits.get(position).copied())
+            .unwrap_or(0)
+    };
+
+    match sign {
+        0xC000 => return Some("NaN".to_string()),
+        0xD000 => return Some("Infinity".to_string()),
+        0xF000 => return Some("-Infinity".to_string()),
+        _ => {}
+    }
+
+    let mut text = String::new();
+    if sign == 0x4000 {
+        text.push('-');
+    }
+    if weight < 0 {
+        text.push('0');
+    } else {
+        text.push_str(&digit(0).to_string());
+        for position in 1..=weight {
+            text.push_str(&format!("{:04}", digit(position)));
+        }
+    }
+    if scale > 0 {
+        let mut fraction = String::new();
+        for group in 0..scale.div_ceil(4) {
+            fraction.push_str(&format!("{:04}", digit(weight + 1 + group as isize)));
+        }
+        fraction.truncate(scale);
+        text.push('.');
+        text.push_str(&fraction);
+    }
+    Some(text)
+}
+
+fn row_to_json(row: &PgRow) -> Value {
+    let json_map = row
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let column_name = column.name();
+            let column_type = ColumnType::from_type_name(column.type_info().name());
+
+            let value: Value = match column_type {
+                ColumnType::Uuid => match row.try_get::<Uuid, _>(i) {
+                    Ok(uuid) => Value::String(uuid.to_string()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Timestamp => match row.try_get::<NaiveDateTime, _>(i) {
+                    Ok(timestamp) => Value::String(timestamp.to_string()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::TimestampTz => match row.try_get::<DateTime<Utc>, _>(i) {
+                    Ok(timestamp) => Value::String(timestamp.to_rfc3339()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::SmallInt => match row.try_get::<i16, _>(i) {
+                    Ok(int_val) => Value::Number(int_val.into()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Int => match row.try_get::<i32, _>(i) {
+                    Ok(int_val) => Value::Number(int_val.into()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::BigInt => match row.try_get::<i64, _>(i) {
+                    Ok(int_val) => Value::Number(int_val.into()),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Numeric => match row.try_get_raw(i).map(numeric_text) {
+                    Ok(Some(text)) => number_value(&text),
+                    _ => Value::Null,
+                },
+                ColumnType::Text => match row.try_get::<String, _>(i) {
+                    Ok(text) => Value::String(text),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Bytes => match row.try_get::<Vec<u8>, _>(i) {
+                    Ok(bytes) => binary_value(&bytes),
+                    Err(_) => Value::Null,
+                },
+                ColumnType::Unknown => match row.try_get::<String, _>(i) {
+                    Ok(val) => Value::String(val),
+                    Err(_) => Value::Null,
+                },
+            };
+
+            (column_name.to_string(), value)
+        })
+        .collect();
+
+    Value::Object(json_map)
+}
+
 #[async_trait]
 impl DbClient for PostgresClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&self.pool)
             .await
             .map_err(DbError::Sqlx)?;
-        Ok(())
+        Ok(result.rows_affected())
     }
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
         let rows = sqlx::query(query)
@@ -63,52 +209,28 @@ impl DbClient for PostgresClient {
             .await
             .map_err(DbError::Sqlx)?;
 
-        let results = rows
-            .iter()
-            .map(|row| {
-                let json_map = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, column)| {
-                        let column_name = column.name();
-                        let column_type = ColumnType::from_type_name(column.type_info().name());
-
-                        let value: Value = match column_type {
-                            ColumnType::Uuid => match row.try_get::<Uuid, _>(i) {
-                                Ok(uuid) => Value::String(uuid.to_string()),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Timestamp => match row.try_get::<NaiveDateTime, _>(i) {
-                                Ok(timestamp) => Value::String(timestamp.to_string()),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Int => match row.try_get::<i32, _>(i) {
-                                Ok(int_val) => Value::Number(int_val.into()),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Text => match row.try_get::<String, _>(i) {
-                                Ok(text) => Value::String(text),
-                                Err(_) => Value::Null,
-                            },
-                            ColumnType::Unknown => match row.try_get::<String, _>(i) {
-                                Ok(val) => Value::String(val),
-                                Err(_) => Value::Null,
-                            },
-                        };
-
-                        (column_name.to_string(), value)
-                    })
-                    .collect();
-
-                Value::Object(json_map)
-            })
-            .collect();
+        Ok(rows.iter().map(row_to_json).collect())
+    }
+
+    async fn query_limited(
+        &self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&self.pool);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows.try_next().await.map_err(DbError::Sqlx)? {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
 
-        Ok(results)
+        Ok((results, false))
     }
 
-    async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError> {
         let tx = self
             .pool
             .begin()
@@ -138,10 +260,14 @@ impl DbClient for PostgresClient {
     }
 
     async fn list_tables(&self) -> Result<Vec<String>, DbError> {
+        // Tables are listed as `schema.table`, so same-named tables in
+        // different schemas stay apart.
         let query = r#"
-            SELECT table_name
+            SELECT table_schema || '.' || table_name AS table_name
             FROM information_schema.tables
-            WHERE table_schema = 'public'
+            WHERE table_type = 'BASE TABLE'
+              AND table_schema NOT LIKE 'pg\_%' AND table_schema <> 'information_schema'
+            ORDER BY table_schema, table_name
         "#;
         let rows = sqlx::query(query)
             .fetch_all(&self.pool)
@@ -156,50 +282,330 @@ impl DbClient for PostgresClient {
         Ok(tables)
     }
 
-    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!(
-            r#"
-            SELECT column_name, data_type, is_nullable, column_default
-            FROM information_schema.columns
-            WHERE table_name = '{}'
-            "#,
-            table_name
-        );
-        let rows = sqlx::query(&query)
+    async fn list_objects(&self) -> Result<Vec<DbObject>, DbError> {
+        // Routines are named with their argument types, as overloads share
+        // a name; `describe_routine` looks them up by the same text.
+        let query = r#"
+            SELECT object_name, object_kind
+            FROM (
+                SELECT (n.nspname || '.' || c.relname)::text AS object_name,
+                       c.relkind::text AS object_kind
+                FROM pg_class c
+                JOIN pg_namespace n ON n.oid = c.relnamespace
+                WHERE c.relkind IN ('v', 'm', 'S')
+                  AND n.nspname NOT LIKE 'pg\_%' AND n.nspname <> 'information_schema'
+                UNION ALL
+                SELECT (n.nspname || '.' || p.proname
+                            || '(' || oidvectortypes(p.proargtypes) || ')')::text,
+                       p.prokind::text
+                FROM pg_proc p
+                JOIN pg_namespace n ON n.oid = p.pronamespace
+                WHERE p.prokind IN ('f', 'p')
+                  AND n.nspname NOT LIKE 'pg\_%' AND n.nspname <> 'information_schema'
+            ) objects
+            ORDER BY position(object_kind IN 'vmSfp'), object_name
+        "#;
+        let rows = sqlx::query(query)
             .fetch_all(&self.pool)
             .await
             .map_err(DbError::Sqlx)?;
 
-        let columns = rows
+        let objects = rows
+            .iter()
+            .map(|row| DbObject {
+                name: row.try_get("object_name").unwrap_or_default(),
+                kind: match row.try_get::<String, _>("object_kind").as_deref() {
+                    Ok("v") => ObjectKind::View,
+                    Ok("m") => ObjectKind::MaterializedView,
+                    Ok("f") => ObjectKind::Function,
+                    Ok("p") => ObjectKind::Procedure,
+                    _ => ObjectKind::Sequence,
+                },
+            })
+            .collect();
+
+        Ok(objects)
+    }
+
+    async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
+        // A `schema.table` name is looked up in that schema; a bare name as
+        // before, in any schema for its columns and in `public` for the rest.
+        let (schema, name) = sql::split_qualified(table_name);
+        let rows = sqlx::query(
+            r#"
+            SELECT c.column_name, c.data_type, c.is_nullable, c.column_default,
+                   EXISTS (
+                       SELECT 1
+                       FROM information_schema.table_constraints tc
+                       JOIN information_schema.key_column_usage kcu
+                         ON kcu.constraint_name = tc.constraint_name
+                        AND kcu.table_schema = tc.table_schema
+                        AND kcu.table_name = tc.table_name
+                       WHERE tc.constraint_type = 'PRIMARY KEY'
+                         AND tc.table_schema = c.table_schema
+                         AND tc.table_name = c.table_name
+                         AND kcu.column_name = c.column_name
+                   ) AS is_primary_key,
+                   col_description(
+                       (quote_ident(c.table_schema) || '.' || quote_ident(c.table_name))::regclass,
+                       c.ordinal_position::int
+                   ) AS column_comment
+            FROM information_schema.columns c
+            WHERE c.table_name = $1 AND ($2::text IS NULL OR c.table_schema = $2)
+            ORDER BY c.ordinal_position
+            "#,
+        )
+        .bind(name)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let mut columns: Vec<ColumnSchema> = rows
             .iter()
             .map(|row| ColumnSchema {
                 name: row.try_get("column_name").unwrap(),
                 data_type: row.try_get("data_type").unwrap(),
                 is_nullable: row.try_get::<String, _>("is_nullable").unwrap() == "YES",
                 default: row.try_get("column_default").ok(),
+                is_primary_key: row.try_get("is_primary_key").unwrap_or(false),
+                comment: row.try_get("column_comment").ok().flatten(),
             })
             .collect();
 
+        // Views and materialized views have their query, sequences their
+        // parameters; base tables match no row.
+        let relation = sql::quote_table(&DbType::Postgres, table_name);
+        let definition_row = sqlx::query(
+            r#"
+            SELECT c.relkind::text AS relkind,
+                   CASE c.relkind
+                       WHEN 'S' THEN (
+                           SELECT format('START %s INCREMENT %s MINVALUE %s MAXVALUE %s%s',
+                                         s.seqstart, s.seqincrement, s.seqmin, s.seqmax,
+                                         CASE WHEN s.seqcycle THEN ' CYCLE' ELSE '' END)
+                           FROM pg_sequence s
+                           WHERE s.seqrelid = c.oid
+                       )
+                       ELSE pg_get_viewdef(c.oid, true)
+                   END AS definition
+            FROM pg_class c
+            WHERE c.oid = to_regclass($1) AND c.relkind IN ('v', 'm', 'S')
+            "#,
+        )
+        .bind(&relation)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+        let definition = definition_row
+            .as_ref()
+            .and_then(|row| row.try_get("definition").ok());
+
+        // information_schema leaves materialized views out.
+        let is_materialized = definition_row
+            .as_ref()
+            .and_then(|row| row.try_get::<String, _>("relkind").ok())
+            .is_some_and(|relkind| relkind == "m");
+        if is_materialized {
+            let rows = sqlx::query(
+                r#"
+                SELECT a.attname::text AS column_name,
+                       format_type(a.atttypid, a.atttypmod) AS data_type,
+                       NOT a.attnotnull AS is_nullable
+                FROM pg_attribute a
+                WHERE a.attrelid = to_regclass($1) AND a.attnum > 0 AND NOT a.attisdropped
+                ORDER BY a.attnum
+                "#,
+            )
+            .bind(&relation)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+            columns = rows
+                .iter()
+                .map(|row| ColumnSchema {
+                    name: row.try_get("column_name").unwrap_or_default(),
+                    data_type: row.try_get("data_type").unwrap_or_default(),
+                    is_nullable: row.try_get("is_nullable").unwrap_or(true),
+                    default: None,
+                    is_primary_key: false,
+                    comment: None,
+                })
+                .collect();
+        }
+
+        let index_rows = sqlx::query(
+            r#"
+            SELECT i.relname::text AS index_name, a.attname::text AS column_name,
+                   ix.indisunique AS is_unique
+            FROM pg_index ix
+            JOIN pg_class t ON t.oid = ix.indrelid
+            JOIN pg_class i ON i.oid = ix.indexrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            JOIN LATERAL unnest(ix.indkey) WITH ORDINALITY AS k(attnum, ord) ON true
+            JOIN pg_attribute a ON a.attrelid = t.oid AND a.attnum = k.attnum
+            WHERE t.relname = $1 AND n.nspname = COALESCE($2, 'public')
+            ORDER BY i.relname, k.ord
+            "#,
+        )
+        .bind(name)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let indexes = IndexSchema::from_rows(index_rows.iter().filter_map(|row| {
+            Some((
+                row.try_get("index_name").ok()?,
+                row.try_get("column_name").ok()?,
+                row.try_get("is_unique").ok()?,
+            ))
+        }));
+
+        let foreign_key_rows = sqlx::query(
+            r#"
+            SELECT con.conname::text AS constraint_name, a.attname::text AS column_name,
+                   rt.relname::text AS referenced_table, ra.attname::text AS referenced_column
+            FROM pg_constraint con
+            JOIN pg_class t ON t.oid = con.conrelid
+            JOIN pg_namespace n ON n.oid = t.relnamespace
+            JOIN pg_class rt ON rt.oid = con.confrelid
+            JOIN LATERAL unnest(con.conkey, con.confkey)
+                WITH ORDINALITY AS k(attnum, ref_attnum, ord) ON true
+            JOIN pg_attribute a ON a.attrelid = con.conrelid AND a.attnum = k.attnum
+            JOIN pg_attribute ra ON ra.attrelid = con.confrelid AND ra.attnum = k.ref_attnum
+            WHERE con.contype = 'f' AND t.relname = $1 AND n.nspname = COALESCE($2, 'public')
+            ORDER BY con.conname, k.ord
+            "#,
+        )
+        .bind(name)
+        .bind(schema)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let foreign_keys = ForeignKeySchema::from_rows(foreign_key_rows.iter().filter_map(|row| {
+            Some((
+                row.try_get("constraint_name").ok()?,
+                row.try_get("column_name").ok()?,
+                row.try_get("referenced_table").ok()?,
+                row.try_get("referenced_column").ok()?,
+            ))
+        }));
+
         Ok(TableSchema {
             table_name: table_name.to_string(),
             columns,
-            indexes: Vec::new(),
+            indexes,
+            foreign_keys,
+            definition,
+        })
+    }
+
+    async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError> {
+        let routine = sqlx::query(
+            r#"
+            SELECT (n.nspname || '.' || p.proname)::text AS routine_name,
+                   p.prokind::text AS prokind,
+                   pg_get_function_result(p.oid) AS return_type,
+                   pg_get_functiondef(p.oid) AS source
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            WHERE n.nspname || '.' || p.proname || '(' || oidvectortypes(p.proargtypes) || ')' = $1
+            "#,
+        )
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?
+        .ok_or_else(|| DbError::General(format!("Routine {} not found", name)))?;
+
+        // `proallargtypes` is only set when there are OUT arguments; modes
+        // are `i`n, `o`ut, `b`oth, `v`ariadic or `t`able columns.
+        let argument_rows = sqlx::query(
+            r#"
+            SELECT COALESCE(p.proargnames[a.i], '') AS argument_name,
+                   format_type(a.t, NULL) AS data_type,
+                   COALESCE(p.proargmodes[a.i]::text, 'i') AS mode
+            FROM pg_proc p
+            JOIN pg_namespace n ON n.oid = p.pronamespace
+            CROSS JOIN LATERAL unnest(COALESCE(p.proallargtypes, p.proargtypes::oid[]))
+                WITH ORDINALITY AS a(t, i)
+            WHERE n.nspname || '.' || p.proname || '(' || oidvectortypes(p.proargtypes) || ')' = $1
+              AND COALESCE(p.proargmodes[a.i]::text, 'i') <> 't'
+            ORDER BY a.i
+            "#,
+        )
+        .bind(name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let arguments = argument_rows
+            .iter()
+            .map(|row| RoutineArgument {
+                name: row.try_get("argument_name").unwrap_or_default(),
+                data_type: row.try_get("data_type").unwrap_or_default(),
+                is_input: row
+                    .try_get::<String, _>("mode")
+                    .is_ok_and(|mode| mode != "o"),
+            })
+            .collect();
+
+        Ok(RoutineSchema {
+            name: routine.try_get("routine_name").unwrap_or_default(),
+            kind: match routine.try_get::<String, _>("prokind").as_deref() {
+                Ok("p") => ObjectKind::Procedure,
+                _ => ObjectKind::Function,
+            },
+            arguments,
+            return_type: routine.try_get("return_type").ok().flatten(),
+            source: routine.try_get("source").ok().flatten(),
         })
     }
+
+    async fn use_database(&self, _database: &str) -> Result<(), DbError> {
+        // A Postgres connection is bound to one database for its whole lifetime.
+        Err(DbError::General(
+            "Postgres cannot switch databases on an open connection".to_string(),
+        ))
+    }
 }
 
-pub struct PostgresTransaction<'a> {
-    tx: sqlx::Transaction<'a, sqlx::Postgres>,
+pub struct PostgresTransaction {
+    tx: sqlx::Transaction<'static, sqlx::Postgres>,
 }
 
 #[async_trait]
-impl<'a> Transaction for PostgresTransaction<'a> {
-    async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+impl Transaction for PostgresTransaction {
+    async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&mut *self.tx)
             .await
             .map_err(|e| DbError::Transaction(e.to_string()))?;
-        Ok(())
+        Ok(result.rows_affected())
+    }
+
+    async fn query_transaction(
+        &mut self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&mut *self.tx);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .map_err(|e| DbError::Transaction(e.to_string()))?
+        {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
+
+        Ok((results, false))
     }
 
     async fn commit_transaction(self: Box<Self>) -> Result<(), DbError> {
@@ -231,12 +637,16 @@ mod tests {
 
         #[async_trait]
         impl DbClient for DbClientMock {
-            async fn execute(&self, query: &str) -> Result<(), DbError>;
+            async fn execute(&self, query: &str) -> Result<u64, DbError>;
             async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn query_limited(&self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn list_databases(&self) -> Result<Vec<String>, DbError>;
             async fn list_tables(&self) -> Result<Vec<String>, DbError>;
+            async fn list_objects(&self) -> Result<Vec<DbObject>, DbError>;
             async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
-            async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
+            async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError>;
+            async fn use_database(&self, database: &str) -> Result<(), DbError>;
+            async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError>;
         }
     }
 
@@ -273,12 +683,12 @@ mod tests {
             .with(predicate::eq(
                 "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_db
             .execute("INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')")
             .await;
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
     }
 
     #[tokio::test]
@@ -314,15 +724,21 @@ mod tests {
                     data_type: "INT".to_string(),
                     is_nullable: false,
                     default: None,
+                    is_primary_key: true,
+                    comment: None,
                 },
                 ColumnSchema {
                     name: "name".to_string(),
                     data_type: "VARCHAR".to_string(),
                     is_nullable: true,
                     default: None,
+                    is_primary_key: false,
+                    comment: None,
                 },
             ],
             indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            definition: None,
         };
 
         mock_db
@@ -342,7 +758,8 @@ mod tests {
 
         #[async_trait::async_trait]
         impl Transaction for Transaction {
-            async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError>;
+            async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError>;
+            async fn query_transaction(&mut self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn commit_transaction(self: Box<Self>) -> Result<(), DbError>;
             async fn rollback_transaction(self: Box<Self>) -> Result<(), DbError>;
         }
@@ -358,7 +775,7 @@ mod tests {
             .with(mockall::predicate::eq(
                 "INSERT INTO users (name) VALUES ('Bob')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let mock_tx = std::cell::RefCell::new(Some(mock_tx));
 
@@ -400,11 +817,37 @@ mod tests {
         mock_tx
             .expect_execute_transaction()
             .with(predicate::eq("INSERT INTO users (name) VALUES ('Alice')"))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_tx
             .execute_transaction("INSERT INTO users (name) VALUES ('Alice')")
             .await;
         assert!(result.is_ok());
     }
+
+    fn numeric(weight: i16, sign: u16, scale: u16, digits: &[i16]) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend((digits.len() as i16).to_be_bytes());
+        bytes.extend(weight.to_be_bytes());
+        bytes.extend(sign.to_be_bytes());
+        bytes.extend(scale.to_be_bytes());
+        for digit in digits {
+            bytes.extend(digit.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_numeric_from_binary() {
+        let text = |weight, sign, scale, digits: &[i16]| {
+            numeric_from_binary(&numeric(weight, sign, scale, digits)).unwrap()
+        };
+        assert_eq!(text(1, 0, 3, &[1, 2345, 6780]), "12345.678");
+        assert_eq!(text(-1, 0x4000, 4, &[5]), "-0.0005");
+        assert_eq!(text(-2, 0, 8, &[1]), "0.00000001");
+        assert_eq!(text(5, 0, 0, &[100]), format!("1{}", "0".repeat(22)));
+        assert_eq!(text(0, 0, 2, &[]), "0.00");
+        assert_eq!(text(0, 0xC000, 0, &[]), "NaN");
+        assert!(numeric_from_binary(&[0, 1]).is_none());
+    }
 }
@@ -1,10 +1,22 @@
 use async_trait::async_trait;
+use futures_util::TryStreamExt;
 use serde_json::Value;
-use sqlx::{sqlite::SqlitePoolOptions, Column, Pool, Row, Sqlite};
+use sqlx::{
+    sqlite::{SqlitePoolOptions, SqliteRow},
+    Column, Pool, Row, Sqlite, ValueRef,
+};
 
 use crate::{
     errors::DbError,
-    models::schema::{ColumnSchema, TableSchema},
+    models::{
+        connections::DbType,
+        schema::{
+            ColumnSchema, DbObject, ForeignKeySchema, IndexSchema, ObjectKind, RoutineSchema,
+            TableSchema,
+        },
+        value::binary_value,
+    },
+    sql,
 };
 
 use super::{DbClient, Transaction};
@@ -25,14 +37,48 @@ impl SqliteClient {
     }
 }
 
+fn row_to_json(row: &SqliteRow) -> Value {
+    let json_map = row
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            let column_name = column.name();
+            // NULL would otherwise decode as an empty string.
+            if row.try_get_raw(i).is_ok_and(|raw| raw.is_null()) {
+                return (column_name.to_string(), Value::Null);
+            }
+            let value: Value = match row.try_get::<String, _>(i) {
+                Ok(val) => Value::String(val),
+                Err(_) => match row.try_get::<i64, _>(i) {
+                    Ok(val) => Value::Number(val.into()),
+                    Err(_) => match row.try_get::<f64, _>(i) {
+                        Ok(val) => serde_json::Number::from_f64(val)
+                            .map(Value::Number)
+                            .unwrap_or(Value::Null),
+                        Err(_) => match row.try_get::<Vec<u8>, _>(i) {
+                            Ok(bytes) => binary_value(&bytes),
+                            Err(_) => Value::Null,
+                        },
+                    },
+                },
+            };
+
+            (column_name.to_string(), value)
+        })
+        .collect();
+
+    Value::Object(json_map)
+}
+
 #[async_trait]
 impl DbClient for SqliteClient {
-    async fn execute(&self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+    async fn execute(&self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&self.pool)
             .await
             .map_err(DbError::Sqlx)?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
     async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError> {
@@ -41,40 +87,28 @@ impl DbClient for SqliteClient {
             .await
             .map_err(DbError::Sqlx)?;
 
-        let results = rows
-            .iter()
-            .map(|row| {
-                let json_map = row
-                    .columns()
-                    .iter()
-                    .enumerate()
-                    .map(|(i, column)| {
-                        let column_name = column.name();
-                        let value: Value = match row.try_get::<String, _>(i) {
-                            Ok(val) => Value::String(val),
-                            Err(_) => match row.try_get::<i64, _>(i) {
-                                Ok(val) => Value::Number(val.into()),
-                                Err(_) => match row.try_get::<f64, _>(i) {
-                                    Ok(val) => serde_json::Number::from_f64(val)
-                                        .map(Value::Number)
-                                        .unwrap_or(Value::Null),
-                                    Err(_) => Value::Null,
-                                },
-                            },
-                        };
-
-                        (column_name.to_string(), value)
-                    })
-                    .collect();
-
-                Value::Object(json_map)
-            })
-            .collect();
+        Ok(rows.iter().map(row_to_json).collect())
+    }
 
-        Ok(results)
+    async fn query_limited(
+        &self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&self.pool);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows.try_next().await.map_err(DbError::Sqlx)? {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
+
+        Ok((results, false))
     }
 
-    async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError> {
+    async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError> {
         let tx = self
             .pool
             .begin()
@@ -108,8 +142,36 @@ impl DbClient for SqliteClient {
         Ok(tables)
     }
 
+    async fn list_objects(&self) -> Result<Vec<DbObject>, DbError> {
+        // SQLite has views, but no materialized views, sequences or routines.
+        let query = r#"
+            SELECT name
+            FROM sqlite_master
+            WHERE type = 'view'
+            ORDER BY name
+        "#;
+
+        let rows = sqlx::query(query)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(DbError::Sqlx)?;
+
+        let objects = rows
+            .iter()
+            .map(|row| DbObject {
+                name: row.try_get::<String, _>("name").unwrap_or_default(),
+                kind: ObjectKind::View,
+            })
+            .collect();
+
+        Ok(objects)
+    }
+
     async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError> {
-        let query = format!("PRAGMA table_info('{}')", table_name);
+        let query = format!(
+            "PRAGMA table_info({})",
+            sql::quote_identifier(&DbType::Sqlite, table_name)
+        );
         let rows = sqlx::query(&query)
             .fetch_all(&self.pool)
             .await
@@ -121,30 +183,129 @@ impl DbClient for SqliteClient {
                 name: row.try_get("name").unwrap(),
                 data_type: row.try_get("type").unwrap(),
                 is_nullable: row.try_get::<i64, _>("notnull").unwrap() == 0,
-                default: row.try_get("dflt_value").ok(),
+                default: row.try_get("dflt_value").ok().flatten(),
+                // `pk` is the column's position in the primary key, or 0.
+                is_primary_key: row.try_get::<i64, _>("pk").unwrap_or(0) > 0,
+                comment: None,
             })
             .collect();
 
+        let index_rows = sqlx::query(
+            r#"
+            SELECT il.name AS index_name, ii.name AS column_name, il."unique" AS is_unique
+            FROM pragma_index_list(?1) AS il
+            JOIN pragma_index_info(il.name) AS ii
+            ORDER BY il.name, ii.seqno
+            "#,
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let indexes = IndexSchema::from_rows(index_rows.iter().filter_map(|row| {
+            Some((
+                row.try_get("index_name").ok()?,
+                row.try_get("column_name").ok()?,
+                row.try_get::<i64, _>("is_unique").ok()? != 0,
+            ))
+        }));
+
+        // SQLite foreign keys are unnamed; `to` is NULL when the parent's primary key is implied.
+        let foreign_key_rows = sqlx::query(
+            r#"
+            SELECT id, "from" AS column_name, "table" AS referenced_table,
+                   "to" AS referenced_column
+            FROM pragma_foreign_key_list(?1)
+            ORDER BY id, seq
+            "#,
+        )
+        .bind(table_name)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(DbError::Sqlx)?;
+
+        let foreign_keys = ForeignKeySchema::from_rows(foreign_key_rows.iter().filter_map(|row| {
+            Some((
+                format!("fk_{}", row.try_get::<i64, _>("id").ok()?),
+                row.try_get("column_name").ok()?,
+                row.try_get("referenced_table").ok()?,
+                row.try_get::<Option<String>, _>("referenced_column")
+                    .ok()?
+                    .unwrap_or_else(|| "PRIMARY KEY".to_string()),
+            ))
+        }));
+
+        let definition =
+            sqlx::query("SELECT sql FROM sqlite_master WHERE type = 'view' AND name = ?1")
+                .bind(table_name)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(DbError::Sqlx)?
+                .and_then(|row| row.try_get("sql").ok());
+
         Ok(TableSchema {
             table_name: table_name.to_string(),
             columns,
-            indexes: Vec::new(),
+            indexes,
+            foreign_keys,
+            definition,
         })
     }
+
+    async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError> {
+        Err(DbError::General(format!(
+            "SQLite has no stored routines, so there is no {}",
+            name
+        )))
+    }
+
+    async fn use_database(&self, database: &str) -> Result<(), DbError> {
+        if database == "main" {
+            Ok(())
+        } else {
+            Err(DbError::General(format!(
+                "SQLite database '{}' is not attached",
+                database
+            )))
+        }
+    }
 }
 
-pub struct SqliteTransaction<'a> {
-    tx: sqlx::Transaction<'a, Sqlite>,
+pub struct SqliteTransaction {
+    tx: sqlx::Transaction<'static, Sqlite>,
 }
 
 #[async_trait]
-impl<'a> Transaction for SqliteTransaction<'a> {
-    async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError> {
-        sqlx::query(query)
+impl Transaction for SqliteTransaction {
+    async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError> {
+        let result = sqlx::query(query)
             .execute(&mut *self.tx)
             .await
             .map_err(|e| DbError::Transaction(e.to_string()))?;
-        Ok(())
+        Ok(result.rows_affected())
+    }
+
+    async fn query_transaction(
+        &mut self,
+        query: &str,
+        max_rows: usize,
+    ) -> Result<(Vec<serde_json::Value>, bool), DbError> {
+        let mut rows = sqlx::query(query).fetch(&mut *self.tx);
+        let mut results = Vec::new();
+
+        while let Some(row) = rows
+            .try_next()
+            .await
+            .map_err(|e| DbError::Transaction(e.to_string()))?
+        {
+            if results.len() == max_rows {
+                return Ok((results, true));
+            }
+            results.push(row_to_json(&row));
+        }
+
+        Ok((results, false))
     }
 
     async fn commit_transaction(self: Box<Self>) -> Result<(), DbError> {
@@ -165,6 +326,7 @@ impl<'a> Transaction for SqliteTransaction<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::value::binary_len;
     use async_trait::async_trait;
     use mockall::{
         mock,
@@ -176,12 +338,16 @@ mod tests {
 
         #[async_trait]
         impl DbClient for DbClientMock {
-            async fn execute(&self, query: &str) -> Result<(), DbError>;
+            async fn execute(&self, query: &str) -> Result<u64, DbError>;
             async fn query(&self, query: &str) -> Result<Vec<serde_json::Value>, DbError>;
+            async fn query_limited(&self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn list_databases(&self) -> Result<Vec<String>, DbError>;
             async fn list_tables(&self) -> Result<Vec<String>, DbError>;
+            async fn list_objects(&self) -> Result<Vec<DbObject>, DbError>;
             async fn describe_table(&self, table_name: &str) -> Result<TableSchema, DbError>;
-            async fn begin_transaction<'a>(&'a self) -> Result<Box<dyn Transaction + 'a>, DbError>;
+            async fn describe_routine(&self, name: &str) -> Result<RoutineSchema, DbError>;
+            async fn use_database(&self, database: &str) -> Result<(), DbError>;
+            async fn begin_transaction(&self) -> Result<Box<dyn Transaction + Send>, DbError>;
         }
     }
 
@@ -219,12 +385,12 @@ mod tests {
             .with(predicate::eq(
                 "INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_db
             .execute("INSERT INTO users (name, email) VALUES ('Alice', 'alice@example.com')")
             .await;
-        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), 1);
     }
 
     #[tokio::test]
@@ -260,15 +426,21 @@ mod tests {
                     data_type: "INTEGER".to_string(),
                     is_nullable: false,
                     default: None,
+                    is_primary_key: true,
+                    comment: None,
                 },
                 ColumnSchema {
                     name: "name".to_string(),
                     data_type: "TEXT".to_string(),
                     is_nullable: true,
                     default: None,
+                    is_primary_key: false,
+                    comment: None,
                 },
             ],
             indexes: Vec::new(),
+            foreign_keys: Vec::new(),
+            definition: None,
         };
 
         mock_db
@@ -288,7 +460,8 @@ mod tests {
 
         #[async_trait::async_trait]
         impl Transaction for Transaction {
-            async fn execute_transaction(&mut self, query: &str) -> Result<(), DbError>;
+            async fn execute_transaction(&mut self, query: &str) -> Result<u64, DbError>;
+            async fn query_transaction(&mut self, query: &str, max_rows: usize) -> Result<(Vec<serde_json::Value>, bool), DbError>;
             async fn commit_transaction(self: Box<Self>) -> Result<(), DbError>;
             async fn rollback_transaction(self: Box<Self>) -> Result<(), DbError>;
         }
@@ -304,7 +477,7 @@ mod tests {
             .with(mockall::predicate::eq(
                 "INSERT INTO users (name) VALUES ('Bob')",
             ))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let mock_tx = std::cell::RefCell::new(Some(mock_tx));
 
@@ -346,11 +519,39 @@ mod tests {
         mock_tx
             .expect_execute_transaction()
             .with(predicate::eq("INSERT INTO users (name) VALUES ('Alice')"))
-            .returning(|_| Ok(()));
+            .returning(|_| Ok(1));
 
         let result = mock_tx
             .execute_transaction("INSERT INTO users (name) VALUES ('Alice')")
             .await;
         assert!(result.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_query_limited_stops_after_max_rows() {
+        let client = SqliteClient::connect("sqlite::memory:").await.unwrap();
+        let numbers =
+            "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n WHERE x < 5) \
+                       SELECT x FROM n";
+
+        let (rows, truncated) = client.query_limited(numbers, 3).await.unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!(truncated);
+
+        let (rows, truncated) = client.query_limited(numbers, 5).await.unwrap();
+        assert_eq!(rows.len(), 5);
+        assert!(!truncated);
+    }
+
+    #[tokio::test]
+    async fn test_query_returns_blobs_as_binary_values() {
+        let client = SqliteClient::connect("sqlite::memory:").await.unwrap();
+        let rows = client
+            .query("SELECT X'00ff' AS data, 'text' AS label, '\\x41' AS hex")
+            .await
+            .unwrap();
+        assert_eq!(rows[0]["data"], binary_value(&[0x00, 0xff]));
+        assert_eq!(rows[0]["label"], "text");
+        assert_eq!(binary_len(&rows[0]["hex"]), None);
+    }
 }
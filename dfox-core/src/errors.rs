@@ -23,3 +23,30 @@ pub enum DbError {
     #[error("Error: {0}")]
     General(String),
 }
+
+impl DbError {
+    /// Whether the connection to the server is gone, e.g. because the server
+    /// closed it while idle, so the statement may work on a new connection.
+    pub fn is_connection_lost(&self) -> bool {
+        matches!(
+            self,
+            DbError::Sqlx(
+                sqlx::Error::Io(_) | sqlx::Error::PoolClosed | sqlx::Error::WorkerCrashed
+            )
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_connection_lost() {
+        let reset = std::io::Error::from(std::io::ErrorKind::ConnectionReset);
+        assert!(DbError::Sqlx(sqlx::Error::Io(reset)).is_connection_lost());
+        assert!(DbError::Sqlx(sqlx::Error::PoolClosed).is_connection_lost());
+        assert!(!DbError::Sqlx(sqlx::Error::RowNotFound).is_connection_lost());
+        assert!(!DbError::Connection("refused".to_string()).is_connection_lost());
+    }
+}
@@ -0,0 +1,371 @@
+//! Writers that serialize query results to common file formats.
+
+use std::{collections::HashMap, fs::File, io::Write, path::Path};
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+use serde_json::Value;
+
+use crate::{
+    errors::DbError,
+    models::{connections::DbType, value::binary_text},
+    sql,
+};
+
+/// Writes `rows` as CSV with `headers` as the first line.
+///
+/// Fields containing commas, quotes or line breaks are quoted, and SQL NULLs
+/// are written as empty fields.
+pub fn write_csv<W: Write>(
+    writer: &mut W,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let header_line: Vec<String> = headers.iter().map(|h| csv_field(h)).collect();
+    writeln!(writer, "{}", header_line.join(",")).map_err(|e| DbError::Export(e.to_string()))?;
+
+    for row in rows {
+        let fields: Vec<String> = headers
+            .iter()
+            .map(|header| csv_field(&plain_text(row.get(header))))
+            .collect();
+        writeln!(writer, "{}", fields.join(",")).map_err(|e| DbError::Export(e.to_string()))?;
+    }
+
+    Ok(())
+}
+
+/// Creates `path` and writes `rows` to it as CSV.
+pub fn export_csv(
+    path: &Path,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let mut file = File::create(path).map_err(|e| DbError::Export(e.to_string()))?;
+    write_csv(&mut file, headers, rows)
+}
+
+/// Writes `rows` as a pretty-printed JSON array of objects.
+///
+/// Keys in each object follow the order of `headers`; missing values are
+/// written as `null`.
+pub fn write_json<W: Write>(
+    writer: &mut W,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let ordered: Vec<OrderedRow> = rows.iter().map(|row| OrderedRow { headers, row }).collect();
+    serde_json::to_writer_pretty(&mut *writer, &ordered)
+        .map_err(|e| DbError::Export(e.to_string()))?;
+    writeln!(writer).map_err(|e| DbError::Export(e.to_string()))
+}
+
+/// Creates `path` and writes `rows` to it as JSON.
+pub fn export_json(
+    path: &Path,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let mut file = File::create(path).map_err(|e| DbError::Export(e.to_string()))?;
+    write_json(&mut file, headers, rows)
+}
+
+/// `rows` as a GitHub-flavored Markdown table, with `headers` as its header
+/// row.
+///
+/// Columns holding only numbers are right-aligned. Pipes in cells are
+/// escaped, line breaks become `<br>` and SQL NULLs are left empty.
+pub fn markdown_table(headers: &[String], rows: &[HashMap<String, Value>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+    let mut table = line(headers.iter().map(|h| markdown_cell(h)).collect());
+    table.push_str(&line(
+        headers
+            .iter()
+            .map(|header| {
+                let mut values = rows
+                    .iter()
+                    .filter_map(|row| row.get(header))
+                    .filter(|value| !value.is_null())
+                    .peekable();
+                let numeric = values.peek().is_some() && values.all(Value::is_number);
+                if numeric { "---:" } else { "---" }.to_string()
+            })
+            .collect(),
+    ));
+    for row in rows {
+        table.push_str(&line(
+            headers
+                .iter()
+                .map(|header| markdown_cell(&plain_text(row.get(header))))
+                .collect(),
+        ));
+    }
+    table
+}
+
+/// Creates `path` and writes `rows` to it as a Markdown table.
+pub fn export_markdown(
+    path: &Path,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let mut file = File::create(path).map_err(|e| DbError::Export(e.to_string()))?;
+    file.write_all(markdown_table(headers, rows).as_bytes())
+        .map_err(|e| DbError::Export(e.to_string()))
+}
+
+/// One `INSERT INTO table (...) VALUES (...);` line per row, with the table
+/// and column names quoted and the values written as `db_type` literals.
+pub fn insert_statements(
+    db_type: &DbType,
+    table: &str,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> String {
+    let columns: Vec<String> = headers
+        .iter()
+        .map(|header| sql::quote_identifier(db_type, header))
+        .collect();
+    let prefix = format!(
+        "INSERT INTO {} ({}) VALUES",
+        sql::quote_table(db_type, table),
+        columns.join(", ")
+    );
+
+    rows.iter()
+        .map(|row| {
+            let values: Vec<String> = headers
+                .iter()
+                .map(|header| sql_literal(db_type, row.get(header).unwrap_or(&Value::Null)))
+                .collect();
+            format!("{} ({});\n", prefix, values.join(", "))
+        })
+        .collect()
+}
+
+/// Creates `path` and writes `rows` to it as INSERT statements into `table`.
+pub fn export_inserts(
+    path: &Path,
+    db_type: &DbType,
+    table: &str,
+    headers: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), DbError> {
+    let mut file = File::create(path).map_err(|e| DbError::Export(e.to_string()))?;
+    file.write_all(insert_statements(db_type, table, headers, rows).as_bytes())
+        .map_err(|e| DbError::Export(e.to_string()))
+}
+
+/// `value` as a SQL literal. Arrays and objects are written as JSON text.
+fn sql_literal(db_type: &DbType, value: &Value) -> String {
+    if let Some(text) = binary_text(value) {
+        return match db_type {
+            // A quoted `\x...` string is cast to bytea on insert.
+            DbType::Postgres => sql::quote_literal(text),
+            DbType::MySql | DbType::Sqlite => format!("X'{}'", &text[2..]),
+        };
+    }
+    let text = match value {
+        Value::Null => return "NULL".to_string(),
+        Value::Bool(true) => return "TRUE".to_string(),
+        Value::Bool(false) => return "FALSE".to_string(),
+        Value::Number(number) => return number.to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    match db_type {
+        // MySQL treats backslashes in string literals as escapes by default.
+        DbType::MySql => sql::quote_literal(&text.replace('\\', "\\\\")),
+        DbType::Postgres | DbType::Sqlite => sql::quote_literal(&text),
+    }
+}
+
+/// A result row serialized with its keys in header order.
+struct OrderedRow<'a> {
+    headers: &'a [String],
+    row: &'a HashMap<String, Value>,
+}
+
+impl Serialize for OrderedRow<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.headers.len()))?;
+        for header in self.headers {
+            match self.row.get(header) {
+                Some(value) => match binary_text(value) {
+                    Some(text) => map.serialize_entry(header, text)?,
+                    None => map.serialize_entry(header, value)?,
+                },
+                None => map.serialize_entry(header, &Value::Null)?,
+            }
+        }
+        map.end()
+    }
+}
+
+/// Renders a value without JSON quoting; NULL becomes an empty string and
+/// binary values their `\x` hex text.
+fn plain_text(value: Option<&Value>) -> String {
+    match value {
+        None | Some(Value::Null) => String::new(),
+        Some(Value::String(s)) => s.clone(),
+        Some(other) => binary_text(other).map_or_else(|| other.to_string(), str::to_string),
+    }
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn markdown_cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::value::binary_value;
+    use serde_json::json;
+
+    fn headers() -> Vec<String> {
+        vec!["id".to_string(), "note".to_string()]
+    }
+
+    #[test]
+    fn test_write_csv_quotes_special_fields() {
+        let rows = vec![
+            HashMap::from([
+                ("id".to_string(), json!(1)),
+                ("note".to_string(), json!("plain")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), json!(2)),
+                ("note".to_string(), json!("a, \"quoted\"\nline")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), json!(3)),
+                ("note".to_string(), Value::Null),
+            ]),
+        ];
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &headers(), &rows).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "id,note\n1,plain\n2,\"a, \"\"quoted\"\"\nline\"\n3,\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv_without_rows_writes_header() {
+        let mut output = Vec::new();
+        write_csv(&mut output, &headers(), &[]).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "id,note\n");
+    }
+
+    #[test]
+    fn test_write_json_keeps_header_order() {
+        let rows = vec![HashMap::from([
+            ("note".to_string(), json!("hi")),
+            ("id".to_string(), json!(1)),
+        ])];
+        let headers = vec!["note".to_string(), "id".to_string(), "gone".to_string()];
+
+        let mut output = Vec::new();
+        write_json(&mut output, &headers, &rows).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "[\n  {\n    \"note\": \"hi\",\n    \"id\": 1,\n    \"gone\": null\n  }\n]\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_escapes_pipes_and_aligns_numbers() {
+        let rows = vec![
+            HashMap::from([
+                ("id".to_string(), json!(1)),
+                ("note".to_string(), json!("a | b\nc")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), Value::Null),
+                ("note".to_string(), json!(2)),
+            ]),
+        ];
+
+        assert_eq!(
+            markdown_table(&headers(), &rows),
+            "| id | note |\n| ---: | --- |\n| 1 | a \\| b<br>c |\n|  | 2 |\n"
+        );
+        assert_eq!(
+            markdown_table(&headers(), &[]),
+            "| id | note |\n| --- | --- |\n"
+        );
+    }
+
+    #[test]
+    fn test_insert_statements_quote_names_and_values() {
+        let rows = vec![
+            HashMap::from([
+                ("id".to_string(), json!(1)),
+                ("note".to_string(), json!("it's C:\\tmp")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), json!(2.5)),
+                ("note".to_string(), Value::Null),
+            ]),
+        ];
+
+        assert_eq!(
+            insert_statements(&DbType::Postgres, "order", &headers(), &rows),
+            "INSERT INTO \"order\" (\"id\", \"note\") VALUES (1, 'it''s C:\\tmp');\n\
+             INSERT INTO \"order\" (\"id\", \"note\") VALUES (2.5, NULL);\n"
+        );
+        assert_eq!(
+            insert_statements(&DbType::MySql, "order", &headers(), &rows[..1]),
+            "INSERT INTO `order` (`id`, `note`) VALUES (1, 'it''s C:\\\\tmp');\n"
+        );
+    }
+
+    #[test]
+    fn test_sql_literal_of_other_json_types() {
+        assert_eq!(sql_literal(&DbType::Sqlite, &json!(true)), "TRUE");
+        assert_eq!(
+            sql_literal(&DbType::Postgres, &json!({"tags": ["a'b"]})),
+            "'{\"tags\":[\"a''b\"]}'"
+        );
+        assert!(insert_statements(&DbType::Sqlite, "t", &headers(), &[]).is_empty());
+    }
+
+    #[test]
+    fn test_sql_literal_of_binary_values() {
+        let bytes = binary_value(&[0x01, 0xab]);
+        assert_eq!(sql_literal(&DbType::Sqlite, &bytes), "X'01ab'");
+        assert_eq!(sql_literal(&DbType::MySql, &bytes), "X'01ab'");
+        assert_eq!(sql_literal(&DbType::Postgres, &bytes), "'\\x01ab'");
+
+        // Text that merely looks like hex stays text.
+        let text = json!("\\x41");
+        assert_eq!(sql_literal(&DbType::Sqlite, &text), "'\\x41'");
+        assert_eq!(sql_literal(&DbType::MySql, &text), "'\\\\x41'");
+
+        let headers = vec!["data".to_string()];
+        let rows = vec![HashMap::from([("data".to_string(), bytes)])];
+        let mut csv = Vec::new();
+        write_csv(&mut csv, &headers, &rows).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "data\n\\x01ab\n");
+        let mut json = Vec::new();
+        write_json(&mut json, &headers, &rows).unwrap();
+        assert!(String::from_utf8(json)
+            .unwrap()
+            .contains(r#""data": "\\x01ab""#));
+    }
+}
@@ -1,41 +1,271 @@
-use db::{mysql::MySqlClient, postgres::PostgresClient, sqlite::SqliteClient, DbClient};
+use db::{
+    mysql::MySqlClient, postgres::PostgresClient, sqlite::SqliteClient, DbClient, Transaction,
+};
 use errors::DbError;
 use models::connections::{ConnectionConfig, DbType};
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
 
 pub mod db;
 pub mod errors;
+pub mod export;
 pub mod models;
+pub mod notices;
+pub mod sql;
+
+type BoxedClient = Box<dyn DbClient + Send + Sync>;
+type BoxedTransaction = Box<dyn Transaction + Send>;
 
 #[derive(Default)]
 pub struct DbManager {
-    pub connections: Arc<Mutex<Vec<Box<dyn DbClient + Send + Sync>>>>,
+    pub connections: Arc<Mutex<Vec<BoxedClient>>>,
+    /// Pools of databases switched away from, keyed by connection URL, so
+    /// switching back to them doesn't open a new pool.
+    idle_clients: Mutex<HashMap<String, BoxedClient>>,
+    /// URL of the client in `connections` opened by `switch_connection`.
+    active_url: Mutex<Option<String>>,
+    /// Transaction opened with `begin_transaction` on the active connection.
+    pub transaction: Mutex<Option<BoxedTransaction>>,
 }
 
 impl DbManager {
     pub fn new() -> Self {
         DbManager {
             connections: Arc::new(Mutex::new(Vec::new())),
+            idle_clients: Mutex::new(HashMap::new()),
+            active_url: Mutex::new(None),
+            transaction: Mutex::new(None),
         }
     }
 
     pub async fn add_connection(&self, config: ConnectionConfig) -> Result<(), DbError> {
-        match config.db_type {
-            DbType::Postgres => {
-                let client = PostgresClient::connect(&config.database_url).await?;
-                self.connections.lock().await.push(Box::new(client));
-            }
-            DbType::MySql => {
-                let client = MySqlClient::connect(&config.database_url).await?;
-                self.connections.lock().await.push(Box::new(client));
-            }
-            DbType::Sqlite => {
-                let client = SqliteClient::connect(&config.database_url).await?;
-                self.connections.lock().await.push(Box::new(client));
-            }
+        let client = connect(&config).await?;
+        self.connections.lock().await.push(client);
+        Ok(())
+    }
+
+    /// Makes `config` the only active connection.
+    ///
+    /// The previously active client is kept idle rather than dropped, and an
+    /// idle client for the same URL is reused instead of reconnecting. An open
+    /// transaction is rolled back first.
+    pub async fn switch_connection(&self, config: ConnectionConfig) -> Result<(), DbError> {
+        if let Some(transaction) = self.transaction.lock().await.take() {
+            transaction.rollback_transaction().await?;
+        }
+
+        let mut connections = self.connections.lock().await;
+        let mut active_url = self.active_url.lock().await;
+        let mut idle_clients = self.idle_clients.lock().await;
+
+        if active_url.as_deref() == Some(config.database_url.as_str()) && !connections.is_empty() {
+            return Ok(());
+        }
+
+        let client = match idle_clients.remove(&config.database_url) {
+            Some(client) => client,
+            None => connect(&config).await?,
+        };
+
+        if let (Some(url), Some(previous)) = (active_url.take(), connections.pop()) {
+            idle_clients.insert(url, previous);
+        }
+        connections.clear();
+        connections.push(client);
+        *active_url = Some(config.database_url);
+
+        Ok(())
+    }
+
+    /// Points the active connection at `database` without reconnecting, on
+    /// backends that can (MySQL's `USE`). As with `switch_connection`, an open
+    /// transaction is rolled back first.
+    pub async fn use_database(&self, database: &str) -> Result<(), DbError> {
+        if let Some(transaction) = self.transaction.lock().await.take() {
+            transaction.rollback_transaction().await?;
+        }
+
+        let connections = self.connections.lock().await;
+        let client = connections
+            .first()
+            .ok_or_else(|| DbError::Connection("No database connection available.".to_string()))?;
+        client.use_database(database).await?;
+        // The client no longer serves the database in the URL it was opened
+        // with, so it mustn't be reused for that URL later.
+        *self.active_url.lock().await = None;
+        Ok(())
+    }
+
+    /// Drops every client and any open transaction, e.g. once the server has
+    /// closed the connection, so the next `switch_connection` opens a new one.
+    pub async fn disconnect(&self) {
+        self.transaction.lock().await.take();
+        self.connections.lock().await.clear();
+        self.idle_clients.lock().await.clear();
+        self.active_url.lock().await.take();
+    }
+
+    /// Starts a transaction on the active connection. Until it is committed or
+    /// rolled back, statements should go through `transaction` instead of
+    /// `connections`.
+    pub async fn begin_transaction(&self) -> Result<(), DbError> {
+        let mut transaction = self.transaction.lock().await;
+        if transaction.is_some() {
+            return Err(DbError::Transaction(
+                "A transaction is already active.".to_string(),
+            ));
         }
 
+        let connections = self.connections.lock().await;
+        let client = connections
+            .first()
+            .ok_or_else(|| DbError::Connection("No database connection available.".to_string()))?;
+        *transaction = Some(client.begin_transaction().await?);
         Ok(())
     }
+
+    pub async fn commit_transaction(&self) -> Result<(), DbError> {
+        self.take_transaction().await?.commit_transaction().await
+    }
+
+    pub async fn rollback_transaction(&self) -> Result<(), DbError> {
+        self.take_transaction().await?.rollback_transaction().await
+    }
+
+    pub async fn in_transaction(&self) -> bool {
+        self.transaction.lock().await.is_some()
+    }
+
+    async fn take_transaction(&self) -> Result<BoxedTransaction, DbError> {
+        self.transaction
+            .lock()
+            .await
+            .take()
+            .ok_or_else(|| DbError::Transaction("No transaction is active.".to_string()))
+    }
+}
+
+async fn connect(config: &ConnectionConfig) -> Result<BoxedClient, DbError> {
+    Ok(match config.db_type {
+        DbType::Postgres => Box::new(PostgresClient::connect(&config.database_url).await?),
+        DbType::MySql => {
+            Box::new(MySqlClient::connect(&config.database_url, config.read_only).await?)
+        }
+        DbType::Sqlite => Box::new(SqliteClient::connect(&config.database_url).await?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sqlite_config(path: &std::path::Path) -> ConnectionConfig {
+        ConnectionConfig {
+            db_type: DbType::Sqlite,
+            database_url: format!("sqlite://{}?mode=rwc", path.display()),
+            read_only: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_switch_connection_reuses_idle_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = sqlite_config(&dir.path().join("first.db"));
+        let second = sqlite_config(&dir.path().join("second.db"));
+        let manager = DbManager::new();
+
+        manager.switch_connection(first.clone()).await.unwrap();
+        manager.switch_connection(second).await.unwrap();
+        assert_eq!(manager.idle_clients.lock().await.len(), 1);
+
+        manager.switch_connection(first).await.unwrap();
+        assert_eq!(manager.connections.lock().await.len(), 1);
+        // The first client came back out of the idle set; the second went in.
+        assert_eq!(manager.idle_clients.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_use_database_keeps_client_but_forgets_its_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = sqlite_config(&dir.path().join("use.db"));
+        let manager = DbManager::new();
+        manager.switch_connection(config.clone()).await.unwrap();
+        manager.begin_transaction().await.unwrap();
+
+        assert!(manager.use_database("other").await.is_err());
+        assert!(!manager.in_transaction().await);
+        assert!(manager.active_url.lock().await.is_some());
+
+        manager.use_database("main").await.unwrap();
+        assert_eq!(manager.connections.lock().await.len(), 1);
+        assert!(manager.active_url.lock().await.is_none());
+
+        // Switching back by URL opens the database again instead of assuming
+        // the current client still matches it.
+        manager.switch_connection(config).await.unwrap();
+        assert_eq!(
+            manager.active_url.lock().await.as_deref(),
+            Some(format!("sqlite://{}?mode=rwc", dir.path().join("use.db").display()).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_forgets_every_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = sqlite_config(&dir.path().join("drop.db"));
+        let manager = DbManager::new();
+        manager.switch_connection(config.clone()).await.unwrap();
+        manager
+            .switch_connection(sqlite_config(&dir.path().join("other.db")))
+            .await
+            .unwrap();
+        manager.begin_transaction().await.unwrap();
+
+        manager.disconnect().await;
+        assert!(manager.connections.lock().await.is_empty());
+        assert!(manager.idle_clients.lock().await.is_empty());
+        assert!(!manager.in_transaction().await);
+
+        manager.switch_connection(config).await.unwrap();
+        assert_eq!(manager.connections.lock().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_rollback_discards_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let manager = DbManager::new();
+        manager
+            .switch_connection(sqlite_config(&dir.path().join("tx.db")))
+            .await
+            .unwrap();
+        manager.connections.lock().await[0]
+            .execute("CREATE TABLE items (id INTEGER)")
+            .await
+            .unwrap();
+
+        manager.begin_transaction().await.unwrap();
+        assert!(manager.begin_transaction().await.is_err());
+        {
+            let mut transaction = manager.transaction.lock().await;
+            let transaction = transaction.as_mut().unwrap();
+            transaction
+                .execute_transaction("INSERT INTO items VALUES (1)")
+                .await
+                .unwrap();
+            let (rows, _) = transaction
+                .query_transaction("SELECT id FROM items", 10)
+                .await
+                .unwrap();
+            assert_eq!(rows.len(), 1);
+        }
+        manager.rollback_transaction().await.unwrap();
+        assert!(!manager.in_transaction().await);
+
+        let rows = manager.connections.lock().await[0]
+            .query("SELECT id FROM items")
+            .await
+            .unwrap();
+        assert!(rows.is_empty());
+        assert!(manager.commit_transaction().await.is_err());
+    }
 }
@@ -11,4 +11,8 @@ pub enum DbType {
 pub struct ConnectionConfig {
     pub db_type: DbType,
     pub database_url: String,
+    /// Has the MySQL client set each session read-only, which its URL can't
+    /// ask for. Postgres and SQLite take the setting in `database_url`.
+    #[serde(default)]
+    pub read_only: bool,
 }
@@ -1,2 +1,3 @@
 pub mod connections;
 pub mod schema;
+pub mod value;
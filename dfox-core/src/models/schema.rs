@@ -5,6 +5,117 @@ pub struct TableSchema {
     pub table_name: String,
     pub columns: Vec<ColumnSchema>,
     pub indexes: Vec<IndexSchema>,
+    pub foreign_keys: Vec<ForeignKeySchema>,
+    /// How a view is defined (its query, or the whole `CREATE VIEW` on
+    /// SQLite), or a sequence's parameters. `None` for base tables.
+    #[serde(default)]
+    pub definition: Option<String>,
+}
+
+/// Kinds of database objects listed besides base tables.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ObjectKind {
+    View,
+    MaterializedView,
+    Sequence,
+    Function,
+    Procedure,
+}
+
+impl ObjectKind {
+    /// Short tag shown next to the object's name.
+    pub fn marker(self) -> &'static str {
+        match self {
+            ObjectKind::View => "V",
+            ObjectKind::MaterializedView => "MV",
+            ObjectKind::Sequence => "S",
+            ObjectKind::Function => "F",
+            ObjectKind::Procedure => "P",
+        }
+    }
+
+    /// Heading of the section listing objects of this kind.
+    pub fn section(self) -> &'static str {
+        match self {
+            ObjectKind::View => "Views",
+            ObjectKind::MaterializedView => "Materialized views",
+            ObjectKind::Sequence => "Sequences",
+            ObjectKind::Function => "Functions",
+            ObjectKind::Procedure => "Procedures",
+        }
+    }
+
+    /// Whether objects of this kind are called rather than queried.
+    pub fn is_routine(self) -> bool {
+        matches!(self, ObjectKind::Function | ObjectKind::Procedure)
+    }
+}
+
+/// A view, materialized view, sequence, function or procedure. Views and
+/// sequences are named like the tables of their backend; Postgres routines
+/// also carry their argument types, e.g. `public.add(integer, integer)`, as
+/// they may be overloaded.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DbObject {
+    pub name: String,
+    pub kind: ObjectKind,
+}
+
+/// A function or stored procedure, as `describe_routine` finds it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoutineSchema {
+    /// Name to call it by, `schema.name` on Postgres.
+    pub name: String,
+    /// `ObjectKind::Function` or `ObjectKind::Procedure`.
+    pub kind: ObjectKind,
+    pub arguments: Vec<RoutineArgument>,
+    /// What a function returns; `None` for procedures.
+    pub return_type: Option<String>,
+    /// The routine's `CREATE` statement, or only its body on MySQL.
+    pub source: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RoutineArgument {
+    /// Empty for unnamed Postgres arguments.
+    pub name: String,
+    pub data_type: String,
+    /// Whether the caller passes a value in, as for `IN` and `INOUT`
+    /// arguments; `OUT` arguments only hand one back.
+    pub is_input: bool,
+}
+
+impl RoutineSchema {
+    /// The routine's arguments and return type, e.g.
+    /// `add(a integer, b integer) RETURNS integer`.
+    pub fn signature(&self) -> String {
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|argument| {
+                let mode = if argument.is_input { "" } else { "OUT " };
+                let name = if argument.name.is_empty() {
+                    String::new()
+                } else {
+                    format!("{} ", argument.name)
+                };
+                format!("{}{}{}", mode, name, argument.data_type)
+            })
+            .collect();
+        let returns = match &self.return_type {
+            Some(return_type) => format!(" RETURNS {}", return_type),
+            None => String::new(),
+        };
+        format!("{}({}){}", self.name, arguments.join(", "), returns)
+    }
+
+    /// Arguments the caller passes a value for, in order.
+    pub fn inputs(&self) -> Vec<&RoutineArgument> {
+        self.arguments
+            .iter()
+            .filter(|argument| argument.is_input)
+            .collect()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -13,6 +124,9 @@ pub struct ColumnSchema {
     pub data_type: String,
     pub is_nullable: bool,
     pub default: Option<String>,
+    pub is_primary_key: bool,
+    /// The column's comment, where the backend supports them.
+    pub comment: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -21,3 +135,117 @@ pub struct IndexSchema {
     pub columns: Vec<String>,
     pub is_unique: bool,
 }
+
+impl IndexSchema {
+    /// Builds indexes from `(index, column, is_unique)` rows ordered by index
+    /// name and then by column position.
+    pub fn from_rows(rows: impl IntoIterator<Item = (String, String, bool)>) -> Vec<Self> {
+        let mut indexes: Vec<IndexSchema> = Vec::new();
+        for (name, column, is_unique) in rows {
+            match indexes.last_mut() {
+                Some(index) if index.name == name => index.columns.push(column),
+                _ => indexes.push(IndexSchema {
+                    name,
+                    columns: vec![column],
+                    is_unique,
+                }),
+            }
+        }
+        indexes
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForeignKeySchema {
+    pub name: String,
+    pub columns: Vec<String>,
+    pub referenced_table: String,
+    pub referenced_columns: Vec<String>,
+}
+
+impl ForeignKeySchema {
+    /// Builds foreign keys from `(constraint, column, referenced table,
+    /// referenced column)` rows ordered by constraint and column position.
+    pub fn from_rows(
+        rows: impl IntoIterator<Item = (String, String, String, String)>,
+    ) -> Vec<Self> {
+        let mut foreign_keys: Vec<ForeignKeySchema> = Vec::new();
+        for (name, column, referenced_table, referenced_column) in rows {
+            match foreign_keys.last_mut() {
+                Some(foreign_key) if foreign_key.name == name => {
+                    foreign_key.columns.push(column);
+                    foreign_key.referenced_columns.push(referenced_column);
+                }
+                _ => foreign_keys.push(ForeignKeySchema {
+                    name,
+                    columns: vec![column],
+                    referenced_table,
+                    referenced_columns: vec![referenced_column],
+                }),
+            }
+        }
+        foreign_keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn s(value: &str) -> String {
+        value.to_string()
+    }
+
+    #[test]
+    fn test_routine_signature_marks_out_arguments() {
+        let routine = RoutineSchema {
+            name: s("public.split_name"),
+            kind: ObjectKind::Procedure,
+            arguments: vec![
+                RoutineArgument {
+                    name: s("full_name"),
+                    data_type: s("text"),
+                    is_input: true,
+                },
+                RoutineArgument {
+                    name: s("first"),
+                    data_type: s("text"),
+                    is_input: false,
+                },
+            ],
+            return_type: None,
+            source: None,
+        };
+
+        assert_eq!(
+            routine.signature(),
+            "public.split_name(full_name text, OUT first text)"
+        );
+        assert_eq!(routine.inputs().len(), 1);
+    }
+
+    #[test]
+    fn test_index_rows_are_grouped_by_name() {
+        let indexes = IndexSchema::from_rows(vec![
+            (s("users_pkey"), s("id"), true),
+            (s("users_name_idx"), s("last_name"), false),
+            (s("users_name_idx"), s("first_name"), false),
+        ]);
+
+        assert_eq!(indexes.len(), 2);
+        assert_eq!(indexes[1].columns, vec!["last_name", "first_name"]);
+        assert!(indexes[0].is_unique);
+    }
+
+    #[test]
+    fn test_foreign_key_rows_are_grouped_by_name() {
+        let foreign_keys = ForeignKeySchema::from_rows(vec![
+            (s("fk_order"), s("order_id"), s("orders"), s("id")),
+            (s("fk_order"), s("order_rev"), s("orders"), s("rev")),
+        ]);
+
+        assert_eq!(foreign_keys.len(), 1);
+        assert_eq!(foreign_keys[0].columns, vec!["order_id", "order_rev"]);
+        assert_eq!(foreign_keys[0].referenced_columns, vec!["id", "rev"]);
+    }
+}
@@ -0,0 +1,105 @@
+//! Column values that have no JSON type of their own, carried in result rows
+//! in forms no text value can take.
+
+use serde_json::{Map, Number, Value};
+
+/// Key of the one-entry object a binary value is carried in. Text columns
+/// always come back as strings, and PostgreSQL refuses NUL in `jsonb`, so no
+/// other value looks like this.
+const BINARY_KEY: &str = "\u{0}binary";
+
+/// A number as the driver printed it, e.g. a `NUMERIC` or `BIGINT UNSIGNED`.
+///
+/// The digits are kept exactly, however many there are, rather than going
+/// through `f64`. Text that isn't a JSON number, like `NaN`, stays a string.
+pub fn number_value(text: &str) -> Value {
+    match text.parse::<Number>() {
+        Ok(number) => Value::Number(number),
+        Err(_) => Value::String(text.to_string()),
+    }
+}
+
+/// A binary (`bytea`, `BLOB`, ...) value, tagged so it can't be taken for
+/// text, holding the `\x`-prefixed hex string PostgreSQL itself prints them
+/// as.
+pub fn binary_value(bytes: &[u8]) -> Value {
+    let mut text = String::with_capacity(2 + bytes.len() * 2);
+    text.push_str("\\x");
+    for byte in bytes {
+        text.push_str(&format!("{:02x}", byte));
+    }
+    Value::Object(Map::from_iter([(
+        BINARY_KEY.to_string(),
+        Value::String(text),
+    )]))
+}
+
+/// The `\x`-prefixed hex text of a value made by [`binary_value`], as
+/// exports write it, or `None` for any other value.
+pub fn binary_text(value: &Value) -> Option<&str> {
+    match value.as_object()? {
+        map if map.len() == 1 => map.get(BINARY_KEY)?.as_str(),
+        _ => None,
+    }
+}
+
+/// The hex digits of a value made by [`binary_value`], or `None` for any
+/// other value.
+fn binary_hex(value: &Value) -> Option<&str> {
+    let hex = binary_text(value)?.strip_prefix("\\x")?;
+    (hex.len() % 2 == 0 && hex.bytes().all(|b| b.is_ascii_hexdigit())).then_some(hex)
+}
+
+/// How many bytes a value made by [`binary_value`] holds, without decoding
+/// them.
+pub fn binary_len(value: &Value) -> Option<usize> {
+    binary_hex(value).map(|hex| hex.len() / 2)
+}
+
+/// The bytes of a value made by [`binary_value`], or `None` for any other
+/// value.
+pub fn binary_bytes(value: &Value) -> Option<Vec<u8>> {
+    let hex = binary_hex(value)?;
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_value_keeps_every_digit() {
+        let exact = "123456789012345678901234567890.000000000000000001";
+        assert_eq!(number_value(exact).to_string(), exact);
+        assert_eq!(
+            number_value("18446744073709551615").as_u64(),
+            Some(u64::MAX)
+        );
+        assert_eq!(number_value("-0.50").to_string(), "-0.50");
+        assert_eq!(number_value("NaN"), Value::String("NaN".to_string()));
+    }
+
+    #[test]
+    fn test_binary_round_trip() {
+        let value = binary_value(&[0x00, 0xde, 0xad, 0xff]);
+        assert_eq!(binary_text(&value), Some("\\x00deadff"));
+        assert_eq!(binary_bytes(&value), Some(vec![0x00, 0xde, 0xad, 0xff]));
+        assert_eq!(binary_bytes(&binary_value(&[])), Some(vec![]));
+        assert_eq!(binary_len(&value), Some(4));
+    }
+
+    #[test]
+    fn test_text_that_looks_like_hex_is_not_binary() {
+        for text in ["\\x41", "\\x", "\\xdeadbeef"] {
+            let value = Value::String(text.to_string());
+            assert_eq!(binary_text(&value), None);
+            assert_eq!(binary_len(&value), None);
+            assert_eq!(binary_bytes(&value), None);
+        }
+        assert_eq!(binary_bytes(&serde_json::json!({"x": "\\x41"})), None);
+        assert_eq!(binary_bytes(&Value::Null), None);
+    }
+}
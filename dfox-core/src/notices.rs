@@ -0,0 +1,87 @@
+//! NOTICE and WARNING messages sent by the server while a statement runs.
+//!
+//! sqlx doesn't return these with query results; it only passes them to the
+//! `log` crate under the `sqlx::postgres::notice` target. [`capture_notices`]
+//! installs a logger that keeps them until [`take_notices`] collects them.
+
+use std::sync::Mutex;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+const NOTICE_TARGET: &str = "sqlx::postgres::notice";
+
+static NOTICES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct NoticeLogger;
+
+impl Log for NoticeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == NOTICE_TARGET
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // sqlx maps the server's severities onto log levels; map them back.
+        let severity = match record.level() {
+            Level::Error => "ERROR",
+            Level::Warn => "WARNING",
+            Level::Info => "NOTICE",
+            Level::Debug => "DEBUG",
+            Level::Trace => "INFO",
+        };
+        if let Ok(mut notices) = NOTICES.lock() {
+            notices.push(format!("{}: {}", severity, record.args()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Starts keeping server notices. Does nothing if another logger has already
+/// been installed.
+///
+/// Records below `Info` are filtered out before they are built, which keeps
+/// sqlx's per-query debug logging cheap. That drops the server's `DEBUG`,
+/// `INFO` and `LOG` messages, which it only sends once `client_min_messages`
+/// is lowered.
+pub fn capture_notices() {
+    if log::set_logger(&NoticeLogger).is_ok() {
+        log::set_max_level(LevelFilter::Info);
+    }
+}
+
+/// The notices received since the last call, oldest first, formatted like
+/// `NOTICE: table "items" does not exist, skipping`.
+pub fn take_notices() -> Vec<String> {
+    NOTICES
+        .lock()
+        .map(|mut notices| std::mem::take(&mut *notices))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notices_are_kept_until_taken() {
+        capture_notices();
+        take_notices();
+
+        log::info!(target: NOTICE_TARGET, "relation \"items\" already exists, skipping");
+        log::warn!(target: NOTICE_TARGET, "there is no transaction in progress");
+        log::info!(target: "sqlx::query", "SELECT 1");
+        log::trace!(target: NOTICE_TARGET, "below the level kept");
+
+        assert_eq!(
+            take_notices(),
+            vec![
+                "NOTICE: relation \"items\" already exists, skipping",
+                "WARNING: there is no transaction in progress",
+            ]
+        );
+        assert!(take_notices().is_empty());
+    }
+}
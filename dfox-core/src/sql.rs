@@ -0,0 +1,710 @@
+//! Helpers for inspecting raw SQL text before it is sent to the server.
+
+use crate::models::{
+    connections::DbType,
+    schema::{ObjectKind, RoutineSchema},
+};
+
+/// A piece of SQL text as seen by [`spans`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Span {
+    /// A quoted string or identifier, or a Postgres dollar-quoted body.
+    Quoted,
+    /// A `-- line` comment, up to but not including its newline.
+    LineComment,
+    /// A `/* block */` comment.
+    BlockComment,
+    /// Any other single character.
+    Char(char),
+}
+
+/// The byte index just past the quoted text opening at `start`, or `None` if
+/// `sql` has no quote there. Unterminated quotes run to the end of `sql`.
+///
+/// With `backslash_escapes`, as in MySQL, a backslash escapes the next
+/// character inside `'...'` and `"..."`; Postgres and SQLite take it
+/// literally. A Postgres `$tag$ ... $tag$` body only ends at the same tag.
+fn quoted_end(sql: &str, start: usize, backslash_escapes: bool) -> Option<usize> {
+    let rest = &sql[start..];
+    let quote = rest.chars().next()?;
+    match quote {
+        '\'' | '"' | '`' => {
+            let mut chars = rest.char_indices().skip(1);
+            while let Some((i, c)) = chars.next() {
+                if c == '\\' && backslash_escapes && quote != '`' {
+                    chars.next();
+                } else if c == quote {
+                    return Some(start + i + 1);
+                }
+            }
+            Some(sql.len())
+        }
+        '$' => {
+            let tag_len = rest[1..].find(|c: char| !(c.is_alphanumeric() || c == '_'))?;
+            if !rest[1 + tag_len..].starts_with('$')
+                || rest[1..].starts_with(|c: char| c.is_ascii_digit())
+            {
+                return None;
+            }
+            let tag = &rest[..tag_len + 2];
+            let body = &rest[tag.len()..];
+            Some(
+                body.find(tag)
+                    .map_or(sql.len(), |end| start + tag.len() + end + tag.len()),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// Splits `sql` into [`Span`]s, each with the byte range it covers, as
+/// `db_type` quotes it.
+fn spans<'a>(
+    db_type: &DbType,
+    sql: &'a str,
+) -> impl Iterator<Item = (Span, std::ops::Range<usize>)> + 'a {
+    let backslash_escapes = matches!(db_type, DbType::MySql);
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        let rest = &sql[start..];
+        let c = rest.chars().next()?;
+        // `$` inside a word, as in Postgres `a$b`, doesn't open a dollar quote.
+        let in_word = c == '$'
+            && sql[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|prev| prev.is_alphanumeric() || prev == '_');
+        let quoted = if in_word {
+            None
+        } else {
+            quoted_end(sql, start, backslash_escapes)
+        };
+        let (span, end) = if let Some(end) = quoted {
+            (Span::Quoted, end)
+        } else if rest.starts_with("--") {
+            (
+                Span::LineComment,
+                rest.find('\n').map_or(sql.len(), |end| start + end),
+            )
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            (
+                Span::BlockComment,
+                comment.find("*/").map_or(sql.len(), |end| start + end + 4),
+            )
+        } else {
+            (Span::Char(c), start + c.len_utf8())
+        };
+        let range = start..end;
+        start = end;
+        Some((span, range))
+    })
+}
+
+/// Removes `-- line` and `/* block */` comments from `sql`.
+///
+/// Quoted strings and identifiers (`'...'`, `"..."`, `` `...` ``, `$$...$$`)
+/// are copied verbatim, so comment markers inside them are left untouched.
+pub fn strip_comments(db_type: &DbType, sql: &str) -> String {
+    let mut result = String::with_capacity(sql.len());
+    for (span, range) in spans(db_type, sql) {
+        match span {
+            Span::LineComment => {}
+            Span::BlockComment => result.push(' '),
+            _ => result.push_str(&sql[range]),
+        }
+    }
+    result
+}
+
+/// Returns `true` if `sql` contains nothing but whitespace and comments.
+pub fn is_blank(db_type: &DbType, sql: &str) -> bool {
+    strip_comments(db_type, sql).trim().is_empty()
+}
+
+/// Splits `sql` into its `;`-separated statements, dropping blank ones.
+///
+/// Semicolons inside quotes, Postgres dollar-quoted bodies or comments do not
+/// end a statement.
+pub fn split_statements<'a>(db_type: &DbType, sql: &'a str) -> Vec<&'a str> {
+    let mut statements = Vec::new();
+    let mut start = 0;
+    for (span, range) in spans(db_type, sql) {
+        if span == Span::Char(';') {
+            statements.push(&sql[start..range.start]);
+            start = range.end;
+        }
+    }
+    statements.push(&sql[start..]);
+
+    statements
+        .into_iter()
+        .filter(|statement| !is_blank(db_type, statement))
+        .map(str::trim)
+        .collect()
+}
+
+/// `sql` with comments dropped, each run of whitespace outside quotes turned
+/// into one space and statements joined by `; `, so scripts that differ only
+/// in layout compare equal.
+pub fn normalize(db_type: &DbType, sql: &str) -> String {
+    let statements: Vec<String> = split_statements(db_type, sql)
+        .into_iter()
+        .map(|statement| {
+            let stripped = strip_comments(db_type, statement);
+            let stripped = stripped.trim();
+            let mut normalized = String::with_capacity(stripped.len());
+            for (span, range) in spans(db_type, stripped) {
+                match span {
+                    Span::Char(c) if !c.is_whitespace() => normalized.push(c),
+                    Span::Char(_) => {
+                        if !normalized.ends_with(' ') {
+                            normalized.push(' ');
+                        }
+                    }
+                    _ => normalized.push_str(&stripped[range]),
+                }
+            }
+            normalized
+        })
+        .collect();
+    statements.join("; ")
+}
+
+/// Upper-cased words of `statement`, ignoring comments and quoted text.
+fn keywords(db_type: &DbType, statement: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (span, _) in spans(db_type, statement) {
+        match span {
+            Span::Char(c) if c.is_alphanumeric() || c == '_' => {
+                word.push(c.to_ascii_uppercase());
+            }
+            _ => {
+                if !word.is_empty() {
+                    words.push(std::mem::take(&mut word));
+                }
+            }
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+
+    words
+}
+
+/// Returns `true` for statements that can destroy data wholesale: `DROP`,
+/// `TRUNCATE`, and `DELETE`/`UPDATE` without a `WHERE` clause.
+///
+/// `EXPLAIN ANALYZE` runs the statement it explains, so that statement is
+/// what is checked.
+pub fn is_destructive(db_type: &DbType, statement: &str) -> bool {
+    let mut words = keywords(db_type, statement);
+    if words.first().is_some_and(|word| word == "EXPLAIN")
+        && words
+            .iter()
+            .any(|word| word == "ANALYZE" || word == "ANALYSE")
+    {
+        // Options such as `(ANALYZE, BUFFERS)` come before the statement.
+        let start = words.iter().position(|word| {
+            matches!(
+                word.as_str(),
+                "SELECT" | "WITH" | "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "DROP" | "TRUNCATE"
+            )
+        });
+        words.drain(..start.unwrap_or(0));
+    }
+    match words.first().map(String::as_str) {
+        Some("DROP" | "TRUNCATE") => true,
+        Some("DELETE" | "UPDATE") => !words.iter().any(|word| word == "WHERE"),
+        _ => false,
+    }
+}
+
+/// Returns the first statement in `sql` that [`is_destructive`].
+pub fn find_destructive<'a>(db_type: &DbType, sql: &'a str) -> Option<&'a str> {
+    split_statements(db_type, sql)
+        .into_iter()
+        .find(|statement| is_destructive(db_type, statement))
+}
+
+/// Returns `true` for statements that only read: `SHOW`, `DESCRIBE`, and
+/// `SELECT`, `WITH`, `VALUES`, `TABLE` or `EXPLAIN` without a word that makes
+/// them write.
+///
+/// `SELECT ... INTO` creates a table, a `WITH` can wrap a data-changing
+/// statement, and `EXPLAIN ANALYZE` runs the statement it explains.
+/// `SELECT ... FOR UPDATE` takes row locks, so it is refused as well.
+pub fn is_read_only(db_type: &DbType, statement: &str) -> bool {
+    let words = keywords(db_type, statement);
+    let writes = |word: &String| {
+        matches!(
+            word.as_str(),
+            "INSERT" | "UPDATE" | "DELETE" | "MERGE" | "INTO" | "CREATE" | "DROP" | "ALTER"
+        )
+    };
+    match words.first().map(String::as_str) {
+        Some("SHOW" | "DESCRIBE" | "DESC") => true,
+        Some("SELECT" | "WITH" | "VALUES" | "TABLE" | "EXPLAIN") => !words.iter().any(writes),
+        _ => false,
+    }
+}
+
+/// Returns `true` for statements whose result is a set of rows rather than
+/// a count of affected ones: `SELECT`, `WITH`, `VALUES`, `TABLE`, `SHOW`,
+/// `DESCRIBE`, `EXPLAIN`, `PRAGMA` and `CALL`, and any statement with
+/// `RETURNING`.
+///
+/// Leading comments are skipped, so `-- note\nSELECT 1` returns rows too.
+pub fn returns_rows(db_type: &DbType, statement: &str) -> bool {
+    let words = keywords(db_type, statement);
+    match words.first().map(String::as_str) {
+        Some(
+            "SELECT" | "WITH" | "VALUES" | "TABLE" | "SHOW" | "DESCRIBE" | "DESC" | "EXPLAIN"
+            | "PRAGMA" | "CALL",
+        ) => true,
+        _ => words.iter().any(|word| word == "RETURNING"),
+    }
+}
+
+/// Returns the first statement in `sql` that isn't [`is_read_only`].
+pub fn find_write<'a>(db_type: &DbType, sql: &'a str) -> Option<&'a str> {
+    split_statements(db_type, sql)
+        .into_iter()
+        .find(|statement| !is_read_only(db_type, statement))
+}
+
+/// Identifiers a schema-changing statement (`CREATE`, `ALTER`, `DROP`,
+/// `RENAME`, `COMMENT`) mentions, with quotes removed; empty for any other
+/// statement.
+///
+/// Used to tell which tables' cached schemas a statement may have changed.
+pub fn ddl_identifiers(db_type: &DbType, statement: &str) -> Vec<String> {
+    let is_ddl = matches!(
+        keywords(db_type, statement).first().map(String::as_str),
+        Some("CREATE" | "ALTER" | "DROP" | "RENAME" | "COMMENT")
+    );
+    if !is_ddl {
+        return Vec::new();
+    }
+
+    let stripped = strip_comments(db_type, statement);
+    let mut identifiers = Vec::new();
+    let mut word = String::new();
+    let mut chars = stripped.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            identifiers.push(std::mem::take(&mut word));
+        }
+        match c {
+            '"' | '`' => {
+                let quoted: String = chars.by_ref().take_while(|&inner| inner != c).collect();
+                identifiers.push(quoted);
+            }
+            '\'' => {
+                for inner in chars.by_ref() {
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    if !word.is_empty() {
+        identifiers.push(word);
+    }
+
+    identifiers
+}
+
+/// `name` quoted as an identifier for `db_type`, so spaces, quotes and
+/// reserved words in it can't change the statement it is put into.
+///
+/// MySQL uses backticks; Postgres and SQLite use double quotes. The quote
+/// character is doubled inside the name.
+pub fn quote_identifier(db_type: &DbType, name: &str) -> String {
+    let quote = match db_type {
+        DbType::MySql => '`',
+        DbType::Postgres | DbType::Sqlite => '"',
+    };
+    let escaped = name.replace(quote, &quote.to_string().repeat(2));
+    format!("{}{}{}", quote, escaped, quote)
+}
+
+/// Splits a table name listed as `schema.table`, as Postgres tables are,
+/// at its first dot. Names without a dot have no schema.
+pub fn split_qualified(name: &str) -> (Option<&str>, &str) {
+    match name.split_once('.') {
+        Some((schema, table)) => (Some(schema), table),
+        None => (None, name),
+    }
+}
+
+/// A table name quoted for `db_type` like [`quote_identifier`], except that
+/// a Postgres `schema.table` has each part quoted.
+pub fn quote_table(db_type: &DbType, name: &str) -> String {
+    match (db_type, split_qualified(name)) {
+        (DbType::Postgres, (Some(schema), table)) => format!(
+            "{}.{}",
+            quote_identifier(db_type, schema),
+            quote_identifier(db_type, table)
+        ),
+        _ => quote_identifier(db_type, name),
+    }
+}
+
+/// `value` as a single-quoted SQL string literal, with `'` doubled.
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// The statement that calls `routine` with `values` for its input arguments:
+/// `SELECT` for a function and `CALL` for a procedure.
+///
+/// Values are passed as string literals for the server to convert to the
+/// argument types, and empty ones as `NULL`. A procedure's `OUT` arguments
+/// get `NULL` on Postgres and a user variable named after them on MySQL.
+pub fn call_statement(db_type: &DbType, routine: &RoutineSchema, values: &[String]) -> String {
+    let mut values = values.iter();
+    let mut arguments = Vec::new();
+    for argument in &routine.arguments {
+        if argument.is_input {
+            arguments.push(match values.next() {
+                Some(value) if !value.is_empty() => quote_literal(value),
+                _ => "NULL".to_string(),
+            });
+        } else if routine.kind == ObjectKind::Procedure {
+            arguments.push(match db_type {
+                DbType::MySql => format!("@{}", quote_identifier(db_type, &argument.name)),
+                _ => "NULL".to_string(),
+            });
+        }
+    }
+
+    let call = format!(
+        "{}({})",
+        quote_table(db_type, &routine.name),
+        arguments.join(", ")
+    );
+    match (routine.kind, db_type) {
+        (ObjectKind::Procedure, _) => format!("CALL {}", call),
+        // Set-returning functions give their rows this way too.
+        (_, DbType::Postgres) => format!("SELECT * FROM {}", call),
+        _ => format!("SELECT {}", call),
+    }
+}
+
+/// The `SELECT` that reads back the output arguments a MySQL procedure call
+/// from [`call_statement`] left in `@` variables, or `None` when there are
+/// none. It must run on the same connection as the call.
+pub fn call_outputs_query(db_type: &DbType, routine: &RoutineSchema) -> Option<String> {
+    if !matches!(db_type, DbType::MySql) || routine.kind != ObjectKind::Procedure {
+        return None;
+    }
+    let outputs: Vec<String> = routine
+        .arguments
+        .iter()
+        .filter(|argument| !argument.is_input)
+        .map(|argument| {
+            let name = quote_identifier(db_type, &argument.name);
+            format!("@{} AS {}", name, name)
+        })
+        .collect();
+    (!outputs.is_empty()).then(|| format!("SELECT {}", outputs.join(", ")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_line_and_block_comments() {
+        let sql = "SELECT 1 -- one\n/* two */ FROM t";
+        assert_eq!(
+            strip_comments(&DbType::Postgres, sql),
+            "SELECT 1 \n  FROM t"
+        );
+    }
+
+    #[test]
+    fn test_comment_markers_inside_strings_are_kept() {
+        let sql = "SELECT '--not a comment', \"/*col*/\"";
+        assert_eq!(strip_comments(&DbType::Postgres, sql), sql);
+    }
+
+    #[test]
+    fn test_is_blank() {
+        assert!(is_blank(&DbType::Postgres, ""));
+        assert!(is_blank(&DbType::Postgres, "  \n\t "));
+        assert!(is_blank(
+            &DbType::Postgres,
+            "-- just a note\n/* and a block */\n"
+        ));
+        assert!(!is_blank(&DbType::Postgres, "-- note\nSELECT 1"));
+    }
+
+    #[test]
+    fn test_normalize_collapses_whitespace_outside_quotes() {
+        assert_eq!(
+            normalize(
+                &DbType::Postgres,
+                "SELECT *\n  FROM users -- all\nWHERE name = 'a  b';\n"
+            ),
+            "SELECT * FROM users WHERE name = 'a  b'"
+        );
+        assert_eq!(
+            normalize(&DbType::Postgres, "SELECT 1;SELECT\t2"),
+            "SELECT 1; SELECT 2"
+        );
+        assert_ne!(
+            normalize(&DbType::Postgres, "SELECT 'a b'"),
+            normalize(&DbType::Postgres, "SELECT 'a  b'")
+        );
+    }
+
+    #[test]
+    fn test_split_statements_ignores_quoted_semicolons() {
+        let sql = "SELECT ';'; -- a; b\nSELECT 2;\n\n";
+        assert_eq!(
+            split_statements(&DbType::Postgres, sql),
+            vec!["SELECT ';'", "-- a; b\nSELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_keeps_dollar_quoted_bodies() {
+        let function = "CREATE FUNCTION f() RETURNS int AS $body$\nBEGIN\n  \
+                        RAISE NOTICE 'a;b $$ c';\n  RETURN 1;\nEND;\n$body$ LANGUAGE plpgsql";
+        let sql = format!("{function};\nSELECT $$x;y$$, $1;");
+        assert_eq!(
+            split_statements(&DbType::Postgres, &sql),
+            vec![function, "SELECT $$x;y$$, $1"]
+        );
+        assert_eq!(
+            split_statements(&DbType::Postgres, "SELECT a$b; SELECT 2"),
+            vec!["SELECT a$b", "SELECT 2"]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_honors_mysql_backslash_escapes() {
+        assert_eq!(
+            split_statements(
+                &DbType::MySql,
+                r#"SELECT 'it\'s; x'; SELECT "a\"; b"; SELECT 3"#
+            ),
+            vec![r#"SELECT 'it\'s; x'"#, r#"SELECT "a\"; b""#, "SELECT 3"]
+        );
+        assert_eq!(
+            strip_comments(&DbType::MySql, r"SELECT 'a\\' -- c"),
+            r"SELECT 'a\\' "
+        );
+    }
+
+    #[test]
+    fn test_backslash_is_literal_in_postgres_and_sqlite_strings() {
+        for db_type in [DbType::Postgres, DbType::Sqlite] {
+            assert_eq!(
+                split_statements(&db_type, r"INSERT INTO t VALUES ('C:\'); SELECT 1;"),
+                vec![r"INSERT INTO t VALUES ('C:\')", "SELECT 1"]
+            );
+            let sql = r"SELECT '\'; DROP TABLE users; --'";
+            assert_eq!(find_destructive(&db_type, sql), Some("DROP TABLE users"));
+            assert_eq!(find_write(&db_type, sql), Some("DROP TABLE users"));
+        }
+    }
+
+    #[test]
+    fn test_is_destructive() {
+        assert!(is_destructive(&DbType::Postgres, "drop table users"));
+        assert!(is_destructive(&DbType::Postgres, "TRUNCATE users"));
+        assert!(is_destructive(&DbType::Postgres, "DELETE FROM users"));
+        assert!(is_destructive(
+            &DbType::Postgres,
+            "UPDATE users SET name = 'where'"
+        ));
+        assert!(!is_destructive(
+            &DbType::Postgres,
+            "DELETE FROM users WHERE id = 1"
+        ));
+        assert!(!is_destructive(&DbType::Postgres, "SELECT * FROM users"));
+        assert!(!is_destructive(
+            &DbType::Postgres,
+            "-- drop table users\nSELECT 1"
+        ));
+        assert!(is_destructive(
+            &DbType::Postgres,
+            "EXPLAIN (ANALYZE, BUFFERS) DELETE FROM users"
+        ));
+        assert!(!is_destructive(
+            &DbType::Postgres,
+            "EXPLAIN DELETE FROM users"
+        ));
+    }
+
+    #[test]
+    fn test_find_destructive() {
+        let sql = "SELECT 1; DELETE FROM users";
+        assert_eq!(
+            find_destructive(&DbType::Postgres, sql),
+            Some("DELETE FROM users")
+        );
+        assert_eq!(find_destructive(&DbType::Postgres, "SELECT 1"), None);
+    }
+
+    #[test]
+    fn test_is_read_only() {
+        assert!(is_read_only(
+            &DbType::Postgres,
+            "select * from users where note = 'delete me'"
+        ));
+        assert!(is_read_only(&DbType::Postgres, "SHOW TABLES"));
+        assert!(is_read_only(&DbType::Postgres, "EXPLAIN SELECT 1"));
+        assert!(is_read_only(
+            &DbType::Postgres,
+            "WITH t AS (SELECT 1) SELECT * FROM t"
+        ));
+        assert!(!is_read_only(
+            &DbType::Postgres,
+            "INSERT INTO users VALUES (1)"
+        ));
+        assert!(!is_read_only(
+            &DbType::Postgres,
+            "SELECT * INTO backup FROM users"
+        ));
+        assert!(!is_read_only(
+            &DbType::Postgres,
+            "WITH gone AS (DELETE FROM users RETURNING *) SELECT 1"
+        ));
+        assert!(!is_read_only(&DbType::Postgres, "SET search_path TO audit"));
+    }
+
+    #[test]
+    fn test_returns_rows() {
+        assert!(returns_rows(&DbType::Postgres, "-- note\nSELECT 1"));
+        assert!(returns_rows(
+            &DbType::Postgres,
+            "/* a */ with t as (select 1) select * from t"
+        ));
+        assert!(returns_rows(&DbType::Postgres, "SHOW TABLES"));
+        assert!(returns_rows(&DbType::Postgres, "PRAGMA table_info(users)"));
+        assert!(returns_rows(&DbType::Postgres, "CALL tally(1, @total)"));
+        assert!(returns_rows(
+            &DbType::Postgres,
+            "DELETE FROM users RETURNING id"
+        ));
+        assert!(!returns_rows(
+            &DbType::Postgres,
+            "DELETE FROM users WHERE note = 'returning'"
+        ));
+        assert!(!returns_rows(
+            &DbType::Postgres,
+            "-- SELECT\nUPDATE users SET id = 1"
+        ));
+    }
+
+    #[test]
+    fn test_find_write() {
+        let sql = "SELECT 1; UPDATE users SET name = 'x' WHERE id = 1";
+        assert_eq!(
+            find_write(&DbType::Postgres, sql),
+            Some("UPDATE users SET name = 'x' WHERE id = 1")
+        );
+        assert_eq!(find_write(&DbType::Postgres, "SELECT 1; SHOW TABLES"), None);
+    }
+
+    #[test]
+    fn test_ddl_identifiers() {
+        assert_eq!(
+            ddl_identifiers(
+                &DbType::Postgres,
+                "ALTER TABLE \"Order Items\" ADD note text -- 'x'"
+            ),
+            vec!["ALTER", "TABLE", "Order Items", "ADD", "note", "text"]
+        );
+        assert_eq!(
+            ddl_identifiers(&DbType::Postgres, "COMMENT ON TABLE users IS 'see orders'"),
+            vec!["COMMENT", "ON", "TABLE", "users", "IS"]
+        );
+        assert!(ddl_identifiers(&DbType::Postgres, "SELECT * FROM users").is_empty());
+    }
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(
+            quote_identifier(&DbType::Postgres, "order items"),
+            "\"order items\""
+        );
+        assert_eq!(
+            quote_identifier(&DbType::Postgres, "say \"hi\""),
+            "\"say \"\"hi\"\"\""
+        );
+        assert_eq!(quote_identifier(&DbType::Sqlite, "select"), "\"select\"");
+        assert_eq!(quote_identifier(&DbType::MySql, "order"), "`order`");
+        assert_eq!(quote_identifier(&DbType::MySql, "a`b c"), "`a``b c`");
+        // The other dialect's quote needs no escaping.
+        assert_eq!(quote_identifier(&DbType::MySql, "a\"b"), "`a\"b`");
+    }
+
+    #[test]
+    fn test_quote_table_quotes_each_postgres_part() {
+        assert_eq!(
+            quote_table(&DbType::Postgres, "analytics.page views"),
+            "\"analytics\".\"page views\""
+        );
+        assert_eq!(quote_table(&DbType::Postgres, "users"), "\"users\"");
+        assert_eq!(quote_table(&DbType::Sqlite, "a.b"), "\"a.b\"");
+        assert_eq!(split_qualified("public.users"), (Some("public"), "users"));
+    }
+
+    #[test]
+    fn test_call_statement_selects_functions_and_calls_procedures() {
+        use crate::models::schema::RoutineArgument;
+
+        let argument = |name: &str, is_input| RoutineArgument {
+            name: name.to_string(),
+            data_type: "text".to_string(),
+            is_input,
+        };
+        let mut routine = RoutineSchema {
+            name: "public.greet".to_string(),
+            kind: ObjectKind::Function,
+            arguments: vec![argument("name", true), argument("title", true)],
+            return_type: Some("text".to_string()),
+            source: None,
+        };
+        let values = vec!["O'Brien".to_string(), String::new()];
+        assert_eq!(
+            call_statement(&DbType::Postgres, &routine, &values),
+            "SELECT * FROM \"public\".\"greet\"('O''Brien', NULL)"
+        );
+
+        routine.name = "tally".to_string();
+        routine.kind = ObjectKind::Procedure;
+        routine.arguments.push(argument("total", false));
+        assert_eq!(
+            call_statement(&DbType::MySql, &routine, &values),
+            "CALL `tally`('O''Brien', NULL, @`total`)"
+        );
+        assert_eq!(
+            call_outputs_query(&DbType::MySql, &routine).as_deref(),
+            Some("SELECT @`total` AS `total`")
+        );
+        assert_eq!(call_outputs_query(&DbType::Postgres, &routine), None);
+        routine.arguments.pop();
+        assert_eq!(call_outputs_query(&DbType::MySql, &routine), None);
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("users"), "'users'");
+        assert_eq!(quote_literal("o'brien"), "'o''brien'");
+    }
+}
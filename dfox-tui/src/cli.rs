@@ -0,0 +1,175 @@
+use std::{
+    env, fs,
+    io::{self, IsTerminal, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use clap::{Parser, ValueEnum};
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    terminal,
+};
+
+/// Command-line options for the dfox TUI.
+#[derive(Parser, Debug)]
+#[command(name = "dfox", version, about = "Terminal database manager")]
+pub struct Cli {
+    /// Read the connection password from this file instead of typing it.
+    #[arg(long, value_name = "PATH", conflicts_with = "password_stdin")]
+    pub password_file: Option<PathBuf>,
+
+    /// Read the connection password from standard input.
+    #[arg(long)]
+    pub password_stdin: bool,
+
+    /// Abort queries that run longer than this many seconds; 0 disables the limit.
+    #[arg(long, value_name = "SECS", default_value_t = 30)]
+    pub query_timeout: u64,
+
+    /// Refuse statements that write, and open PostgreSQL and SQLite
+    /// connections read-only.
+    #[arg(long)]
+    pub read_only: bool,
+
+    /// Rows fetched per page when previewing a table [default: 100, or
+    /// `page_size` from the config file].
+    #[arg(long, value_name = "ROWS")]
+    pub page_size: Option<usize>,
+
+    /// Try a failed connection again this many times, waiting twice as long
+    /// before each try; 0 disables retrying.
+    #[arg(long, value_name = "TIMES", default_value_t = 3)]
+    pub connect_retries: u32,
+
+    /// Run this query, print its rows to standard output and exit instead of
+    /// starting the TUI.
+    #[arg(short = 'e', long, value_name = "SQL")]
+    pub execute: Option<String>,
+
+    /// Database to run `--execute` on, e.g. `postgres://user@host/db` or
+    /// `sqlite:app.db`; defaults to `DATABASE_URL`.
+    #[arg(long, value_name = "URL", requires = "execute")]
+    pub url: Option<String>,
+
+    /// How `--execute` prints rows.
+    #[arg(long, value_enum, default_value_t = OutputFormat::Table, requires = "execute")]
+    pub format: OutputFormat,
+}
+
+/// Output of `--execute`.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum OutputFormat {
+    /// Aligned columns under a header.
+    Table,
+    Csv,
+    Json,
+}
+
+impl Cli {
+    /// Returns the password from the configured source, if any.
+    pub fn read_password(&self) -> io::Result<Option<String>> {
+        if let Some(path) = &self.password_file {
+            let contents = fs::read_to_string(path)?;
+            return Ok(Some(trim_line_ending(contents)));
+        }
+
+        if self.password_stdin {
+            let password = if io::stdin().is_terminal() {
+                read_password_from_terminal()?
+            } else {
+                let mut line = String::new();
+                io::stdin().read_line(&mut line)?;
+                trim_line_ending(line)
+            };
+            return Ok(Some(password));
+        }
+
+        Ok(None)
+    }
+
+    /// The database `--execute` runs on: `--url`, else `DATABASE_URL`.
+    pub fn database_url(&self) -> Option<String> {
+        self.url.clone().or_else(|| env::var("DATABASE_URL").ok())
+    }
+
+    /// Returns the query timeout, or `None` when it is disabled.
+    pub fn query_timeout(&self) -> Option<Duration> {
+        (self.query_timeout > 0).then(|| Duration::from_secs(self.query_timeout))
+    }
+}
+
+/// Reads a line from the terminal in raw mode so the password is never echoed.
+fn read_password_from_terminal() -> io::Result<String> {
+    eprint!("Password: ");
+    io::stderr().flush()?;
+
+    terminal::enable_raw_mode()?;
+    let mut password = String::new();
+    let result = loop {
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                KeyCode::Enter => break Ok(()),
+                // Raw mode turns off the terminal's own handling of these.
+                KeyCode::Char('c' | 'd') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "Password input cancelled",
+                    ))
+                }
+                KeyCode::Char(c) => password.push(c),
+                KeyCode::Backspace => {
+                    password.pop();
+                }
+                KeyCode::Esc => {
+                    break Err(io::Error::new(
+                        io::ErrorKind::Interrupted,
+                        "Password input cancelled",
+                    ))
+                }
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    terminal::disable_raw_mode()?;
+    eprintln!();
+
+    result.map(|_| password)
+}
+
+fn trim_line_ending(mut value: String) -> String {
+    while value.ends_with('\n') || value.ends_with('\r') {
+        value.pop();
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trim_line_ending() {
+        assert_eq!(trim_line_ending("secret\n".to_string()), "secret");
+        assert_eq!(trim_line_ending("secret\r\n".to_string()), "secret");
+        assert_eq!(trim_line_ending("se cret \n\n".to_string()), "se cret ");
+        assert_eq!(trim_line_ending("secret".to_string()), "secret");
+        assert_eq!(trim_line_ending(String::new()), "");
+    }
+
+    #[test]
+    fn test_password_file_is_read_without_its_line_ending() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("password");
+        fs::write(&path, "s3cret \r\n").unwrap();
+
+        let cli = Cli::parse_from(["dfox", "--password-file", path.to_str().unwrap()]);
+        assert_eq!(cli.read_password().unwrap().as_deref(), Some("s3cret "));
+
+        let missing = Cli::parse_from(["dfox", "--password-file", "/nonexistent/password"]);
+        assert!(missing.read_password().is_err());
+        assert_eq!(Cli::parse_from(["dfox"]).read_password().unwrap(), None);
+    }
+}
@@ -1,44 +1,887 @@
 use std::collections::HashMap;
 
-use dfox_core::models::schema::TableSchema;
+use dfox_core::{
+    db::Transaction,
+    errors::DbError,
+    models::{
+        connections::DbType,
+        schema::{DbObject, ObjectKind, RoutineSchema, TableSchema},
+    },
+    sql, DbManager,
+};
+
+use crate::ui::{DatabaseClientUI, DatabaseType};
 
 mod mysql;
 mod postgres;
+mod sqlite;
 
 pub trait PostgresUI {
-    async fn execute_sql_query(
-        &mut self,
-        query: &str,
-    ) -> Result<(Vec<HashMap<String, serde_json::Value>>, Option<String>), Box<dyn std::error::Error>>;
+    /// Runs the backend's EXPLAIN for `query` and returns the plan, one line per step.
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>>;
     async fn describe_table(
         &self,
         table_name: &str,
     ) -> Result<TableSchema, Box<dyn std::error::Error>>;
-    async fn fetch_databases(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    /// The statements that recreate `table_name`: its `CREATE TABLE`, then
+    /// any indexes or triggers the server keeps separately.
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>>;
     async fn fetch_tables(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>>;
     async fn update_tables(&mut self);
     async fn connect_to_selected_db(
         &mut self,
         db_name: &str,
     ) -> Result<(), Box<dyn std::error::Error>>;
     async fn connect_to_default_db(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Client sessions open on the server, for the session list.
+    async fn fetch_server_sessions(&self)
+        -> Result<Vec<ServerSession>, Box<dyn std::error::Error>>;
+    /// Ends the server session `id`, as listed by `fetch_server_sessions`.
+    async fn terminate_server_session(&self, id: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Version, user, database, encoding and settings of the connection.
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>>;
 }
 
 pub trait MySQLUI {
-    async fn execute_sql_query(
+    /// Runs the backend's EXPLAIN for `query` and returns the plan, one line per step.
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    async fn describe_table(
+        &self,
+        table_name: &str,
+    ) -> Result<TableSchema, Box<dyn std::error::Error>>;
+    /// The statements that recreate `table_name`: its `CREATE TABLE`, then
+    /// any indexes or triggers the server keeps separately.
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>>;
+    async fn fetch_tables(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>>;
+    async fn update_tables(&mut self);
+    async fn connect_to_selected_db(
         &mut self,
-        query: &str,
-    ) -> Result<(Vec<HashMap<String, serde_json::Value>>, Option<String>), Box<dyn std::error::Error>>;
+        db_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>>;
+    async fn connect_to_default_db(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Client sessions open on the server, for the session list.
+    async fn fetch_server_sessions(&self)
+        -> Result<Vec<ServerSession>, Box<dyn std::error::Error>>;
+    /// Ends the server session `id`, as listed by `fetch_server_sessions`.
+    async fn terminate_server_session(&self, id: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Version, user, database, encoding and settings of the connection.
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>>;
+}
+
+pub trait SqliteUI {
+    /// Runs the backend's EXPLAIN for `query` and returns the plan, one line per step.
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>>;
     async fn describe_table(
         &self,
         table_name: &str,
     ) -> Result<TableSchema, Box<dyn std::error::Error>>;
-    async fn fetch_databases(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    /// The statements that recreate `table_name`: its `CREATE TABLE`, then
+    /// any indexes or triggers the server keeps separately.
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>>;
     async fn fetch_tables(&self) -> Result<Vec<String>, Box<dyn std::error::Error>>;
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>>;
     async fn update_tables(&mut self);
     async fn connect_to_selected_db(
         &mut self,
         db_name: &str,
     ) -> Result<(), Box<dyn std::error::Error>>;
     async fn connect_to_default_db(&mut self) -> Result<(), Box<dyn std::error::Error>>;
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Client sessions open on the server, for the session list.
+    async fn fetch_server_sessions(&self)
+        -> Result<Vec<ServerSession>, Box<dyn std::error::Error>>;
+    /// Ends the server session `id`, as listed by `fetch_server_sessions`.
+    async fn terminate_server_session(&self, id: &str) -> Result<(), Box<dyn std::error::Error>>;
+    /// Version, user, database, encoding and settings of the connection.
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>>;
+}
+
+/// Routes each database call to the backend chosen on the db type selection screen.
+impl DatabaseClientUI {
+    /// Runs one statement and records its column order in `sql_query_columns`.
+    ///
+    /// If the server closed the connection, it is opened again. A read-only
+    /// statement is then retried once, unless a transaction was lost with
+    /// it; any other may already have been applied, so it is not rerun.
+    pub async fn execute_sql_query(
+        &mut self,
+        query: &str,
+    ) -> Result<(Vec<HashMap<String, serde_json::Value>>, Option<String>), Box<dyn std::error::Error>>
+    {
+        let db_type = self.db_type.db_type();
+        let outcome = match run_statement(&self.db_manager, &db_type, query).await {
+            Err(err) if is_connection_lost(err.as_ref()) && !self.transaction_active => {
+                self.reconnect().await?;
+                self.status_message =
+                    Some("Reconnected after the connection was lost.".to_string());
+                if !sql::is_read_only(&db_type, query) {
+                    return Err(LOST_WRITE_MESSAGE.into());
+                }
+                run_statement(&self.db_manager, &db_type, query).await
+            }
+            result => result,
+        }
+        .map_err(|err| err.to_string())?;
+        self.sql_query_columns = outcome.columns;
+        Ok((outcome.rows, outcome.message))
+    }
+
+    pub async fn explain_query(
+        &self,
+        query: &str,
+    ) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::explain_query(self, query).await,
+            DatabaseType::MySQL => MySQLUI::explain_query(self, query).await,
+            DatabaseType::SQLite => SqliteUI::explain_query(self, query).await,
+        }
+    }
+
+    pub async fn describe_table(
+        &self,
+        table_name: &str,
+    ) -> Result<TableSchema, Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::describe_table(self, table_name).await,
+            DatabaseType::MySQL => MySQLUI::describe_table(self, table_name).await,
+            DatabaseType::SQLite => SqliteUI::describe_table(self, table_name).await,
+        }
+    }
+
+    pub async fn fetch_databases(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        Ok(list_databases(&self.db_manager)
+            .await
+            .map_err(|err| err.to_string())?)
+    }
+
+    pub async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::table_ddl(self, table_name).await,
+            DatabaseType::MySQL => MySQLUI::table_ddl(self, table_name).await,
+            DatabaseType::SQLite => SqliteUI::table_ddl(self, table_name).await,
+        }
+    }
+
+    pub async fn update_tables(&mut self) {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::update_tables(self).await,
+            DatabaseType::MySQL => MySQLUI::update_tables(self).await,
+            DatabaseType::SQLite => SqliteUI::update_tables(self).await,
+        }
+        self.update_objects().await;
+    }
+
+    /// Views, materialized views and sequences of the current database.
+    pub async fn fetch_objects(&self) -> Result<Vec<DbObject>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            Ok(client.list_objects().await?)
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    /// Arguments and source of the function or procedure `name`.
+    pub async fn describe_routine(
+        &self,
+        name: &str,
+    ) -> Result<RoutineSchema, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            Ok(client.describe_routine(name).await?)
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    pub async fn connect_to_selected_db(
+        &mut self,
+        db_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::connect_to_selected_db(self, db_name).await,
+            DatabaseType::MySQL => MySQLUI::connect_to_selected_db(self, db_name).await,
+            DatabaseType::SQLite => SqliteUI::connect_to_selected_db(self, db_name).await,
+        }
+    }
+
+    pub async fn connect_to_default_db(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::connect_to_default_db(self).await,
+            DatabaseType::MySQL => MySQLUI::connect_to_default_db(self).await,
+            DatabaseType::SQLite => SqliteUI::connect_to_default_db(self).await,
+        }
+    }
+
+    pub async fn switch_database(
+        &mut self,
+        db_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::switch_database(self, db_name).await,
+            DatabaseType::MySQL => MySQLUI::switch_database(self, db_name).await,
+            DatabaseType::SQLite => SqliteUI::switch_database(self, db_name).await,
+        }
+    }
+
+    pub async fn fetch_server_sessions(
+        &self,
+    ) -> Result<Vec<ServerSession>, Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::fetch_server_sessions(self).await,
+            DatabaseType::MySQL => MySQLUI::fetch_server_sessions(self).await,
+            DatabaseType::SQLite => SqliteUI::fetch_server_sessions(self).await,
+        }
+    }
+
+    pub async fn terminate_server_session(
+        &self,
+        id: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::terminate_server_session(self, id).await,
+            DatabaseType::MySQL => MySQLUI::terminate_server_session(self, id).await,
+            DatabaseType::SQLite => SqliteUI::terminate_server_session(self, id).await,
+        }
+    }
+
+    pub async fn fetch_connection_info(
+        &self,
+    ) -> Result<ConnectionInfo, Box<dyn std::error::Error>> {
+        match self.db_type {
+            DatabaseType::Postgres => PostgresUI::fetch_connection_info(self).await,
+            DatabaseType::MySQL => MySQLUI::fetch_connection_info(self).await,
+            DatabaseType::SQLite => SqliteUI::fetch_connection_info(self).await,
+        }
+    }
+}
+
+/// What the server reports about the connection, for the diagnostics screen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionInfo {
+    pub version: String,
+    pub user: String,
+    pub database: String,
+    pub encoding: String,
+    /// `(name, value)` pairs: the settings changed from their defaults on
+    /// Postgres, `SHOW VARIABLES` on MySQL and a few pragmas on SQLite.
+    pub settings: Vec<(String, String)>,
+}
+
+impl ConnectionInfo {
+    /// Reads the `version`, `user_name`, `db_name` and `encoding` columns of
+    /// the first of `rows`.
+    fn from_row(rows: &[serde_json::Value], settings: Vec<(String, String)>) -> Self {
+        let row = rows.first();
+        let text = |key: &str| {
+            row.and_then(|row| row.get(key))
+                .map_or_else(String::new, value_text)
+        };
+        ConnectionInfo {
+            version: text("version"),
+            user: text("user_name"),
+            database: text("db_name"),
+            encoding: text("encoding"),
+            settings,
+        }
+    }
+
+    /// Lines of the diagnostics screen, with the settings aligned in a
+    /// column below the overview.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Server version: {}", self.version),
+            format!("User:           {}", self.user),
+            format!("Database:       {}", self.database),
+            format!("Encoding:       {}", self.encoding),
+        ];
+        if !self.settings.is_empty() {
+            let width = self
+                .settings
+                .iter()
+                .map(|(name, _)| name.chars().count())
+                .max()
+                .unwrap_or(0);
+            lines.push(String::new());
+            lines.push(format!("Settings ({}):", self.settings.len()));
+            for (name, value) in &self.settings {
+                lines.push(format!("  {:<width$}  {}", name, value, width = width));
+            }
+        }
+        lines
+    }
+}
+
+/// `(name, value)` pairs from the `name_key` and `value_key` columns of
+/// `rows`.
+fn setting_pairs(
+    rows: &[serde_json::Value],
+    name_key: &str,
+    value_key: &str,
+) -> Vec<(String, String)> {
+    rows.iter()
+        .filter_map(|row| {
+            let name = row.get(name_key)?.as_str()?.to_string();
+            Some((
+                name,
+                row.get(value_key).map_or_else(String::new, value_text),
+            ))
+        })
+        .collect()
+}
+
+/// A cell as plain text: strings without their JSON quotes, NULL as empty.
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// One client session on the server, as listed by `pg_stat_activity` or the
+/// MySQL process list.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerSession {
+    /// The Postgres pid or MySQL connection id.
+    pub id: String,
+    pub user: String,
+    pub database: String,
+    pub state: String,
+    /// How long the current or last query has been running, as the server
+    /// formats it.
+    pub duration: String,
+    pub query: String,
+    /// Whether this is the connection that listed the sessions.
+    pub own: bool,
+}
+
+impl ServerSession {
+    /// One line for the session list, with the query on a single line.
+    pub fn line(&self) -> String {
+        let query: Vec<&str> = self.query.split_whitespace().collect();
+        format!(
+            "{:>8}  {:<12} {:<16} {:<20} {:>10}  {}{}",
+            self.id,
+            self.user,
+            self.database,
+            self.state,
+            self.duration,
+            query.join(" "),
+            if self.own { "  (this connection)" } else { "" }
+        )
+    }
+}
+
+/// Reads the session list queries' rows, whose columns are all cast to text.
+fn server_sessions(rows: &[serde_json::Value]) -> Vec<ServerSession> {
+    let text = |row: &serde_json::Value, key: &str| {
+        row.get(key)
+            .and_then(serde_json::Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    rows.iter()
+        .filter(|row| row.get("id").is_some_and(serde_json::Value::is_string))
+        .map(|row| ServerSession {
+            id: text(row, "id"),
+            user: text(row, "user_name"),
+            database: text(row, "db_name"),
+            state: text(row, "state"),
+            duration: text(row, "duration"),
+            query: text(row, "query"),
+            own: matches!(text(row, "own").as_str(), "true" | "t" | "1"),
+        })
+        .collect()
+}
+
+/// Checks that a session id from the list is a plain number before it is
+/// put into a statement.
+fn session_id(id: &str) -> Result<u64, Box<dyn std::error::Error>> {
+    id.parse()
+        .map_err(|_| format!("Not a session id: {}", id).into())
+}
+
+/// Rows, column order and message of a statement run by [`run_statement`].
+pub struct StatementOutcome {
+    pub rows: Vec<HashMap<String, serde_json::Value>>,
+    /// Empty for statements that return no rows.
+    pub columns: Vec<String>,
+    pub message: Option<String>,
+}
+
+impl StatementOutcome {
+    fn from_rows(rows: Vec<serde_json::Value>, truncated: bool) -> Self {
+        StatementOutcome {
+            columns: result_columns(&rows),
+            message: truncated.then(|| truncated_rows_message(rows.len())),
+            rows: result_maps(rows),
+        }
+    }
+
+    fn from_rows_affected(rows_affected: u64) -> Self {
+        StatementOutcome {
+            rows: Vec::new(),
+            columns: Vec::new(),
+            message: Some(affected_rows_message(rows_affected)),
+        }
+    }
+}
+
+/// Runs one statement on the open transaction, or else on the current
+/// connection, keeping at most `MAX_RESULT_ROWS` of its rows.
+///
+/// Only the `DbManager` is borrowed, not the UI, so statements can run on a
+/// background task while the UI keeps drawing. `db_type` says how the
+/// connected backend quotes strings, to tell whether `query` returns rows.
+pub async fn run_statement(
+    db_manager: &DbManager,
+    db_type: &DbType,
+    query: &str,
+) -> Result<StatementOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    run_statement_limited(db_manager, db_type, query, MAX_RESULT_ROWS).await
+}
+
+/// Like [`run_statement`], but keeping at most `max_rows` rows.
+pub async fn run_statement_limited(
+    db_manager: &DbManager,
+    db_type: &DbType,
+    query: &str,
+    max_rows: usize,
+) -> Result<StatementOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let query_trimmed = query.trim();
+    let is_select = sql::returns_rows(db_type, query_trimmed);
+
+    // An open transaction pins its own connection; statements must run on it.
+    if let Some(transaction) = db_manager.transaction.lock().await.as_mut() {
+        return run_in_transaction(transaction.as_mut(), db_type, query_trimmed, max_rows).await;
+    }
+
+    let connections = db_manager.connections.lock().await;
+    let client = connections
+        .first()
+        .ok_or("No database connection available.")?;
+    Ok(if is_select {
+        let (rows, truncated) = client.query_limited(query_trimmed, max_rows).await?;
+        StatementOutcome::from_rows(rows, truncated)
+    } else {
+        StatementOutcome::from_rows_affected(client.execute(query_trimmed).await?)
+    })
+}
+
+async fn run_in_transaction(
+    transaction: &mut (dyn Transaction + Send),
+    db_type: &DbType,
+    query: &str,
+    max_rows: usize,
+) -> Result<StatementOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    Ok(if sql::returns_rows(db_type, query) {
+        let (rows, truncated) = transaction.query_transaction(query, max_rows).await?;
+        StatementOutcome::from_rows(rows, truncated)
+    } else {
+        StatementOutcome::from_rows_affected(transaction.execute_transaction(query).await?)
+    })
+}
+
+/// Runs `query` for its rows on the open transaction, or else on the current
+/// connection, as [`run_statement`] does, keeping at most `MAX_RESULT_ROWS`.
+async fn query_rows(
+    db_manager: &DbManager,
+    query: &str,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    if let Some(transaction) = db_manager.transaction.lock().await.as_mut() {
+        return Ok(transaction
+            .query_transaction(query, MAX_RESULT_ROWS)
+            .await?
+            .0);
+    }
+    let connections = db_manager.connections.lock().await;
+    let client = connections
+        .first()
+        .ok_or("No database connection available.")?;
+    Ok(client.query_limited(query, MAX_RESULT_ROWS).await?.0)
+}
+
+/// Runs `statements` in order on one connection, so session state such as
+/// MySQL `@` variables set by one is seen by the next, and returns the
+/// outcome of each. They run on the open transaction, or else in one of
+/// their own that is committed once they have all succeeded.
+pub async fn run_statements_together(
+    db_manager: &DbManager,
+    db_type: &DbType,
+    statements: &[String],
+) -> Result<Vec<StatementOutcome>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut outcomes = Vec::new();
+    if let Some(transaction) = db_manager.transaction.lock().await.as_mut() {
+        for statement in statements {
+            outcomes.push(
+                run_in_transaction(transaction.as_mut(), db_type, statement, MAX_RESULT_ROWS)
+                    .await?,
+            );
+        }
+        return Ok(outcomes);
+    }
+
+    let mut transaction = {
+        let connections = db_manager.connections.lock().await;
+        let client = connections
+            .first()
+            .ok_or("No database connection available.")?;
+        client.begin_transaction().await?
+    };
+    for statement in statements {
+        match run_in_transaction(transaction.as_mut(), db_type, statement, MAX_RESULT_ROWS).await {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(err) => {
+                let _ = transaction.rollback_transaction().await;
+                return Err(err);
+            }
+        }
+    }
+    transaction.commit_transaction().await?;
+    Ok(outcomes)
+}
+
+/// Why a statement that could change data was not retried after the
+/// connection was lost while it ran.
+pub const LOST_WRITE_MESSAGE: &str = "The connection was lost while the statement ran, so it \
+     may or may not have been applied. Reconnected; check before running it again.";
+
+/// Whether `err`, as returned by [`run_statement`], means the server closed
+/// the connection rather than rejecting the statement.
+pub fn is_connection_lost(err: &(dyn std::error::Error + Send + Sync + 'static)) -> bool {
+    err.downcast_ref::<DbError>()
+        .is_some_and(DbError::is_connection_lost)
+}
+
+/// Databases on the server of the current connection.
+///
+/// Like [`run_statement`], this only borrows the `DbManager`, so the list can
+/// load on a background task.
+pub async fn list_databases(
+    db_manager: &DbManager,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let connections = db_manager.connections.lock().await;
+    let client = connections
+        .first()
+        .ok_or("No database connection available.")?;
+    Ok(client.list_databases().await?)
+}
+
+/// Column names of a query result in the order the server returned them.
+fn result_columns(rows: &[serde_json::Value]) -> Vec<String> {
+    match rows.first() {
+        Some(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Query result rows as column -> value maps.
+fn result_maps(rows: Vec<serde_json::Value>) -> Vec<HashMap<String, serde_json::Value>> {
+    rows.into_iter()
+        .filter_map(|row| match row {
+            serde_json::Value::Object(map) => Some(map.into_iter().collect()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Collects `name_key` -> `count_key` pairs from catalog query rows.
+///
+/// Counts may arrive as numbers or, when cast to text, as strings.
+fn parse_row_counts(
+    rows: &[serde_json::Value],
+    name_key: &str,
+    count_key: &str,
+) -> HashMap<String, u64> {
+    rows.iter()
+        .filter_map(|row| {
+            let name = row.get(name_key)?.as_str()?;
+            let count = match row.get(count_key)? {
+                serde_json::Value::Number(number) => number.as_u64()?,
+                serde_json::Value::String(text) => text.parse().ok()?,
+                _ => return None,
+            };
+            Some((name.to_string(), count))
+        })
+        .collect()
+}
+
+/// Splits the text plans Postgres and MySQL return in the `key` column into lines.
+///
+/// Postgres returns one row per line; MySQL's `FORMAT=TREE` returns the whole
+/// tree in a single multi-line value. Both are already indented.
+fn plan_text_lines(rows: &[serde_json::Value], key: &str) -> Vec<String> {
+    rows.iter()
+        .filter_map(|row| row.get(key)?.as_str())
+        .flat_map(str::lines)
+        .map(str::to_string)
+        .collect()
+}
+
+/// The string values of the `key` column.
+fn text_values(rows: &[serde_json::Value], key: &str) -> Vec<String> {
+    rows.iter()
+        .filter_map(|row| row.get(key)?.as_str())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The `CREATE` statement of a Postgres view, materialized view or sequence,
+/// from the definition `describe_table` found for it.
+fn postgres_create_object(kind: ObjectKind, quoted_name: &str, definition: &str) -> String {
+    let definition = definition.trim();
+    match kind {
+        ObjectKind::View => format!("CREATE VIEW {} AS\n{}", quoted_name, definition),
+        ObjectKind::MaterializedView => {
+            format!(
+                "CREATE MATERIALIZED VIEW {} AS\n{}",
+                quoted_name, definition
+            )
+        }
+        ObjectKind::Sequence => format!("CREATE SEQUENCE {} {}", quoted_name, definition),
+        // Routine sources are whole `CREATE` statements already.
+        ObjectKind::Function | ObjectKind::Procedure => definition.to_string(),
+    }
+}
+
+/// Assembles a Postgres `CREATE TABLE` from the column and table constraint
+/// definitions the catalog helpers rendered.
+fn postgres_create_table(quoted_table: &str, columns: &[String], constraints: &[String]) -> String {
+    let definitions: Vec<String> = columns
+        .iter()
+        .chain(constraints)
+        .map(|definition| format!("    {}", definition))
+        .collect();
+    format!(
+        "CREATE TABLE {} (\n{}\n)",
+        quoted_table,
+        definitions.join(",\n")
+    )
+}
+
+/// Joins DDL statements into one script, each ending in `;`.
+fn ddl_script(statements: &[String]) -> Result<String, Box<dyn std::error::Error>> {
+    if statements.is_empty() {
+        return Err("Table not found.".into());
+    }
+    let statements: Vec<String> = statements
+        .iter()
+        .map(|statement| format!("{};", statement.trim_end().trim_end_matches(';')))
+        .collect();
+    Ok(statements.join("\n\n"))
+}
+
+/// Indents SQLite's `EXPLAIN QUERY PLAN` rows into a tree using their
+/// `id`/`parent` links.
+fn sqlite_plan_lines(rows: &[serde_json::Value]) -> Vec<String> {
+    let number = |row: &serde_json::Value, key: &str| match row.get(key) {
+        Some(serde_json::Value::Number(number)) => number.as_i64(),
+        Some(serde_json::Value::String(text)) => text.parse().ok(),
+        _ => None,
+    };
+
+    let mut depths: HashMap<i64, usize> = HashMap::new();
+    rows.iter()
+        .filter_map(|row| {
+            let detail = row.get("detail")?.as_str()?;
+            let depth = number(row, "parent")
+                .and_then(|parent| depths.get(&parent))
+                .map_or(0, |depth| depth + 1);
+            if let Some(id) = number(row, "id") {
+                depths.insert(id, depth);
+            }
+            Some(format!("{}{}", "  ".repeat(depth), detail))
+        })
+        .collect()
+}
+
+/// Most rows kept from a SELECT; the driver stops reading the result there.
+const MAX_RESULT_ROWS: usize = 10_000;
+
+/// Notice shown when a SELECT returned more rows than the `kept` ones.
+fn truncated_rows_message(kept: usize) -> String {
+    format!(
+        "Showing the first {} rows; the rest were not fetched.",
+        kept
+    )
+}
+
+/// Confirmation shown after a statement that returns no rows.
+fn affected_rows_message(rows_affected: u64) -> String {
+    let rows = if rows_affected == 1 { "row" } else { "rows" };
+    format!("Query OK, {} {} affected.", rows_affected, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::sqlite_ui;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_commented_and_cte_queries_return_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let ui = sqlite_ui(&dir.path().join("rows.db")).await;
+
+        for query in [
+            "-- note\nSELECT 1 AS n",
+            "/* block */ SELECT 1 AS n",
+            "WITH t AS (SELECT 1 AS n) SELECT n FROM t",
+            "VALUES (1)",
+        ] {
+            let outcome = run_statement(&ui.db_manager, &DbType::Sqlite, query)
+                .await
+                .unwrap();
+            assert_eq!(outcome.rows.len(), 1, "{query}");
+        }
+
+        // Temporary tables belong to one connection, like MySQL `@` variables.
+        let outcomes = run_statements_together(
+            &ui.db_manager,
+            &DbType::Sqlite,
+            &[
+                "CREATE TEMP TABLE outputs AS SELECT 7 AS total".to_string(),
+                "SELECT total FROM outputs".to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        assert_eq!(outcomes[1].rows[0]["total"], json!(7));
+    }
+
+    #[test]
+    fn test_result_columns_keep_select_order() {
+        let rows = vec![json!({ "id": 1, "name": "a", "email": "a@example.com" })];
+        assert_eq!(result_columns(&rows), vec!["id", "name", "email"]);
+        assert!(result_columns(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_row_counts_accepts_numbers_and_text() {
+        let rows = vec![
+            json!({ "table_name": "users", "row_count": 1203 }),
+            json!({ "table_name": "orders", "row_count": "42" }),
+            json!({ "table_name": "empty", "row_count": null }),
+        ];
+        let counts = parse_row_counts(&rows, "table_name", "row_count");
+        assert_eq!(counts.get("users"), Some(&1203));
+        assert_eq!(counts.get("orders"), Some(&42));
+        assert_eq!(counts.get("empty"), None);
+    }
+
+    #[test]
+    fn test_plan_text_lines() {
+        let postgres = vec![
+            json!({"QUERY PLAN": "Hash Join  (cost=1.00..2.00 rows=1 width=8)"}),
+            json!({"QUERY PLAN": "  ->  Seq Scan on users"}),
+        ];
+        assert_eq!(
+            plan_text_lines(&postgres, "QUERY PLAN"),
+            vec![
+                "Hash Join  (cost=1.00..2.00 rows=1 width=8)",
+                "  ->  Seq Scan on users"
+            ]
+        );
+
+        let mysql = vec![json!({"EXPLAIN": "-> Filter: (id = 1)\n    -> Table scan on users"})];
+        assert_eq!(plan_text_lines(&mysql, "EXPLAIN").len(), 2);
+    }
+
+    #[test]
+    fn test_sqlite_plan_lines_are_indented_by_parent() {
+        let rows = vec![
+            json!({"id": 2, "parent": 0, "notused": 0, "detail": "SCAN users"}),
+            json!({"id": 4, "parent": 0, "notused": 0, "detail": "CORRELATED SCALAR SUBQUERY 1"}),
+            json!({"id": 7, "parent": 4, "notused": 0, "detail": "SEARCH orders USING INDEX"}),
+        ];
+        assert_eq!(
+            sqlite_plan_lines(&rows),
+            vec![
+                "SCAN users",
+                "CORRELATED SCALAR SUBQUERY 1",
+                "  SEARCH orders USING INDEX"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_postgres_create_table() {
+        let columns = vec![
+            "id integer NOT NULL".to_string(),
+            "name text DEFAULT 'x'::text".to_string(),
+        ];
+        let constraints = vec!["CONSTRAINT t_pkey PRIMARY KEY (id)".to_string()];
+        let create = postgres_create_table("\"t\"", &columns, &constraints);
+        let index = "CREATE INDEX t_name ON public.t USING btree (name)".to_string();
+
+        assert_eq!(
+            ddl_script(&[create, index]).unwrap(),
+            "CREATE TABLE \"t\" (\n    id integer NOT NULL,\n    name text DEFAULT 'x'::text,\n    \
+             CONSTRAINT t_pkey PRIMARY KEY (id)\n);\n\n\
+             CREATE INDEX t_name ON public.t USING btree (name);"
+        );
+        assert!(ddl_script(&[]).is_err());
+    }
+
+    #[test]
+    fn test_postgres_create_object() {
+        let view = postgres_create_object(
+            ObjectKind::View,
+            "\"public\".\"active_users\"",
+            " SELECT id\n   FROM users\n  WHERE active;",
+        );
+        assert_eq!(
+            ddl_script(&[view]).unwrap(),
+            "CREATE VIEW \"public\".\"active_users\" AS\nSELECT id\n   FROM users\n  WHERE active;"
+        );
+        assert_eq!(
+            postgres_create_object(ObjectKind::Sequence, "\"s\"", "START 1 INCREMENT 1"),
+            "CREATE SEQUENCE \"s\" START 1 INCREMENT 1"
+        );
+    }
+
+    #[test]
+    fn test_server_sessions_from_text_rows() {
+        let rows = vec![
+            json!({
+                "id": "4021", "user_name": "app", "db_name": "shop", "state": "active",
+                "duration": "00:00:12", "query": "SELECT *\n  FROM orders", "own": "false"
+            }),
+            json!({ "id": "4022", "user_name": null, "own": "true" }),
+            json!({ "id": null }),
+        ];
+        let sessions = server_sessions(&rows);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, "4021");
+        assert!(!sessions[0].own);
+        assert!(sessions[0]
+            .line()
+            .ends_with("00:00:12  SELECT * FROM orders"));
+        assert_eq!(sessions[1].user, "");
+        assert!(sessions[1].line().ends_with("(this connection)"));
+
+        assert_eq!(session_id("4021").unwrap(), 4021);
+        assert!(session_id("1; DROP TABLE x").is_err());
+    }
+
+    #[test]
+    fn test_affected_rows_message() {
+        assert_eq!(affected_rows_message(1), "Query OK, 1 row affected.");
+        assert_eq!(affected_rows_message(0), "Query OK, 0 rows affected.");
+    }
 }
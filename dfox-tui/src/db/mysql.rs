@@ -1,52 +1,28 @@
 use std::{collections::HashMap, time::Duration};
 
-use dfox_core::db::{mysql::MySqlClient, DbClient};
+use dfox_core::{
+    db::{mysql::MySqlClient, DbClient},
+    models::connections::{ConnectionConfig, DbType},
+    sql,
+};
 use tokio::time::timeout;
 
-use crate::ui::DatabaseClientUI;
+use crate::ui::{DatabaseClientUI, DatabaseType};
 
-use super::MySQLUI;
+use super::{
+    ddl_script, parse_row_counts, plan_text_lines, query_rows, server_sessions, session_id,
+    setting_pairs, text_values, ConnectionInfo, MySQLUI, ServerSession,
+};
 
 impl MySQLUI for DatabaseClientUI {
-    async fn execute_sql_query(
-        &mut self,
-        query: &str,
-    ) -> Result<(Vec<HashMap<String, serde_json::Value>>, Option<String>), Box<dyn std::error::Error>>
-    {
-        let db_manager = self.db_manager.clone();
-        let connections = db_manager.connections.lock().await;
-
-        if let Some(client) = connections.first() {
-            let query_trimmed = query.trim();
-            let query_upper = query_trimmed.to_uppercase();
-
-            if query_upper.starts_with("SELECT") {
-                let rows: Vec<serde_json::Value> = client.query(query_trimmed).await?;
-
-                let hash_map_results: Vec<HashMap<String, serde_json::Value>> = rows
-                    .into_iter()
-                    .filter_map(|row| {
-                        if let serde_json::Value::Object(map) = row {
-                            Some(
-                                map.into_iter()
-                                    .collect::<HashMap<String, serde_json::Value>>(),
-                            )
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                self.sql_query_result = hash_map_results.clone();
-                Ok((hash_map_results, None))
-            } else {
-                client.execute(query_trimmed).await?;
-                let success_message = "Non-SELECT query executed successfully.".to_string();
-                Ok((Vec::new(), Some(success_message)))
-            }
-        } else {
-            Err("No database connection available.".into())
-        }
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let statement = query.trim().trim_end_matches(';');
+        let rows = query_rows(
+            &self.db_manager,
+            &format!("EXPLAIN FORMAT=TREE {}", statement),
+        )
+        .await?;
+        Ok(plan_text_lines(&rows, "EXPLAIN"))
     }
 
     async fn describe_table(
@@ -64,13 +40,21 @@ impl MySQLUI for DatabaseClientUI {
         }
     }
 
-    async fn fetch_databases(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>> {
         let db_manager = self.db_manager.clone();
         let connections = db_manager.connections.lock().await;
 
         if let Some(client) = connections.first() {
-            let databases = client.list_databases().await?;
-            Ok(databases)
+            let rows = client
+                .query(&format!(
+                    "SHOW CREATE TABLE {}",
+                    sql::quote_identifier(&DbType::MySql, table_name)
+                ))
+                .await?;
+            // Views come back as `Create View` instead.
+            let mut statements = text_values(&rows, "Create Table");
+            statements.extend(text_values(&rows, "Create View"));
+            ddl_script(&statements)
         } else {
             Err("No database connection available.".into())
         }
@@ -88,14 +72,39 @@ impl MySQLUI for DatabaseClientUI {
         }
     }
 
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            // TABLE_ROWS is an estimate for InnoDB, but avoids scanning every table.
+            let rows = client
+                .query(
+                    "SELECT TABLE_NAME AS table_name, CAST(TABLE_ROWS AS CHAR) AS row_count \
+                     FROM information_schema.TABLES \
+                     WHERE TABLE_SCHEMA = DATABASE()",
+                )
+                .await?;
+            Ok(parse_row_counts(&rows, "table_name", "row_count"))
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
     async fn update_tables(&mut self) {
-        match self.fetch_tables().await {
+        match MySQLUI::fetch_tables(self).await {
             Ok(tables) => {
                 self.tables = tables;
                 self.selected_table = 0;
+                // Counts are decoration; a failure just leaves them out.
+                self.table_row_counts = MySQLUI::fetch_table_row_counts(self)
+                    .await
+                    .unwrap_or_default();
             }
             Err(err) => {
-                println!("Error fetching tables: {}", err);
+                self.connection_error_message = Some(format!("Error fetching tables: {}", err));
                 self.tables = Vec::new();
                 self.selected_table = 0;
             }
@@ -106,21 +115,24 @@ impl MySQLUI for DatabaseClientUI {
         &mut self,
         db_name: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let db_manager = self.db_manager.clone();
-        let mut connections = db_manager.connections.lock().await;
-        connections.clear();
+        // USE on the open server connection is much cheaper than a new pool.
+        // Reconnecting remains the fallback, e.g. when the database can only
+        // be reached with its own URL.
+        if self.db_manager.use_database(db_name).await.is_ok() {
+            return Ok(());
+        }
 
-        let connection_string = format!(
-            "mysql://{}:{}@{}:{}/{}",
-            self.connection_input.username,
-            self.connection_input.password,
-            self.connection_input.hostname,
-            self.connection_input.port,
-            db_name,
-        );
+        let connection_string = self
+            .connection_input
+            .connection_url(DatabaseType::MySQL, db_name);
 
-        let client = MySqlClient::connect(&connection_string).await?;
-        connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
+        self.db_manager
+            .switch_connection(ConnectionConfig {
+                db_type: DbType::MySql,
+                database_url: connection_string,
+                read_only: self.connection_input.read_only,
+            })
+            .await?;
 
         Ok(())
     }
@@ -129,17 +141,13 @@ impl MySQLUI for DatabaseClientUI {
         let db_manager = self.db_manager.clone();
         let mut connections = db_manager.connections.lock().await;
 
-        let connection_string = format!(
-            "mysql://{}:{}@{}:{}/mysql",
-            self.connection_input.username,
-            self.connection_input.password,
-            self.connection_input.hostname,
-            self.connection_input.port
-        );
+        let connection_string = self
+            .connection_input
+            .server_url(DatabaseType::MySQL, "mysql");
 
         let result = timeout(
             Duration::from_secs(3),
-            MySqlClient::connect(&connection_string),
+            MySqlClient::connect(&connection_string, self.connection_input.read_only),
         )
         .await;
 
@@ -158,4 +166,72 @@ impl MySQLUI for DatabaseClientUI {
             }
         }
     }
+
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        self.db_manager.use_database(db_name).await?;
+        Ok(())
+    }
+
+    async fn fetch_server_sessions(
+        &self,
+    ) -> Result<Vec<ServerSession>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        // The same list as SHOW FULL PROCESSLIST, cast to text so every
+        // server version decodes it alike.
+        let rows = client
+            .query(
+                "SELECT CAST(ID AS CHAR) AS id, \
+                        CAST(USER AS CHAR) AS user_name, \
+                        CAST(COALESCE(DB, '') AS CHAR) AS db_name, \
+                        CAST(CONCAT(COMMAND, IF(COALESCE(STATE, '') = '', '', CONCAT(': ', STATE))) AS CHAR) AS state, \
+                        CAST(SEC_TO_TIME(TIME) AS CHAR) AS duration, \
+                        CAST(COALESCE(INFO, '') AS CHAR) AS query, \
+                        CAST(ID = CONNECTION_ID() AS CHAR) AS own \
+                 FROM information_schema.PROCESSLIST \
+                 WHERE COMMAND <> 'Daemon' \
+                 ORDER BY ID",
+            )
+            .await?;
+        Ok(server_sessions(&rows))
+    }
+
+    async fn terminate_server_session(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let id = session_id(id)?;
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        client.execute(&format!("KILL {}", id)).await?;
+        Ok(())
+    }
+
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        let overview = client
+            .query(
+                "SELECT CAST(VERSION() AS CHAR) AS version, \
+                        CAST(CURRENT_USER() AS CHAR) AS user_name, \
+                        CAST(COALESCE(DATABASE(), '') AS CHAR) AS db_name, \
+                        CAST(CONCAT(@@character_set_database, ' (client ', \
+                            @@character_set_client, ')') AS CHAR) AS encoding",
+            )
+            .await?;
+        let settings = client.query("SHOW VARIABLES").await?;
+        Ok(ConnectionInfo::from_row(
+            &overview,
+            setting_pairs(&settings, "Variable_name", "Value"),
+        ))
+    }
 }
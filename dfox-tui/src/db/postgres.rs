@@ -2,55 +2,27 @@ use std::{collections::HashMap, time::Duration};
 
 use dfox_core::{
     db::{postgres::PostgresClient, DbClient},
-    models::schema::TableSchema,
+    models::{
+        connections::{ConnectionConfig, DbType},
+        schema::TableSchema,
+    },
+    sql,
 };
 use tokio::time::timeout;
 
-use crate::ui::DatabaseClientUI;
+use crate::ui::{DatabaseClientUI, DatabaseType};
 
-use super::PostgresUI;
+use super::{
+    ddl_script, parse_row_counts, plan_text_lines, postgres_create_object, postgres_create_table,
+    query_rows, server_sessions, session_id, setting_pairs, text_values, ConnectionInfo,
+    PostgresUI, ServerSession,
+};
 
 impl PostgresUI for DatabaseClientUI {
-    async fn execute_sql_query(
-        &mut self,
-        query: &str,
-    ) -> Result<(Vec<HashMap<String, serde_json::Value>>, Option<String>), Box<dyn std::error::Error>>
-    {
-        let db_manager = self.db_manager.clone();
-        let connections = db_manager.connections.lock().await;
-
-        if let Some(client) = connections.first() {
-            let query_trimmed = query.trim();
-            let query_upper = query_trimmed.to_uppercase();
-
-            if query_upper.starts_with("SELECT") {
-                let rows: Vec<serde_json::Value> = client.query(query_trimmed).await?;
-
-                let hash_map_results: Vec<HashMap<String, serde_json::Value>> = rows
-                    .into_iter()
-                    .filter_map(|row| {
-                        if let serde_json::Value::Object(map) = row {
-                            Some(
-                                map.into_iter()
-                                    .collect::<HashMap<String, serde_json::Value>>(),
-                            )
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
-
-                self.sql_query_result = hash_map_results.clone();
-
-                Ok((hash_map_results, None))
-            } else {
-                client.execute(query_trimmed).await?;
-                let success_message = "Non-SELECT query executed successfully.".to_string();
-                Ok((Vec::new(), Some(success_message)))
-            }
-        } else {
-            Err("No database connection available.".into())
-        }
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let statement = query.trim().trim_end_matches(';');
+        let rows = query_rows(&self.db_manager, &format!("EXPLAIN {}", statement)).await?;
+        Ok(plan_text_lines(&rows, "QUERY PLAN"))
     }
 
     async fn describe_table(
@@ -67,15 +39,70 @@ impl PostgresUI for DatabaseClientUI {
         }
     }
 
-    async fn fetch_databases(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>> {
         let db_manager = self.db_manager.clone();
         let connections = db_manager.connections.lock().await;
-        if let Some(client) = connections.first() {
-            let databases = client.list_databases().await?;
-            Ok(databases)
-        } else {
-            Err("No database connection found".into())
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        // Postgres has no SHOW CREATE TABLE, so the statement is put together
+        // from the catalog, with the pg_get_* helpers rendering each part.
+        let quoted_table = sql::quote_table(&DbType::Postgres, table_name);
+        if let Some(kind) = self.object_kind(table_name) {
+            let definition = client.describe_table(table_name).await?.definition;
+            return ddl_script(&[postgres_create_object(
+                kind,
+                &quoted_table,
+                &definition.unwrap_or_default(),
+            )]);
         }
+        let relation = format!("{}::regclass", sql::quote_literal(&quoted_table));
+        let columns = client
+            .query(&format!(
+                "SELECT quote_ident(a.attname) || ' ' || format_type(a.atttypid, a.atttypmod) \
+                     || CASE WHEN a.attnotnull THEN ' NOT NULL' ELSE '' END \
+                     || COALESCE(' DEFAULT ' || pg_get_expr(d.adbin, d.adrelid), '') \
+                     AS definition \
+                 FROM pg_attribute a \
+                 LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum \
+                 WHERE a.attrelid = {} AND a.attnum > 0 AND NOT a.attisdropped \
+                 ORDER BY a.attnum",
+                relation
+            ))
+            .await?;
+        // NOT NULL is already part of the column definitions.
+        let constraints = client
+            .query(&format!(
+                "SELECT 'CONSTRAINT ' || quote_ident(conname) || ' ' || pg_get_constraintdef(oid) \
+                     AS definition \
+                 FROM pg_constraint \
+                 WHERE conrelid = {} AND contype IN ('p', 'u', 'f', 'c', 'x') \
+                 ORDER BY contype <> 'p', conname",
+                relation
+            ))
+            .await?;
+        // Indexes backing a primary key or unique constraint come with it.
+        let indexes = client
+            .query(&format!(
+                "SELECT pg_get_indexdef(i.indexrelid) AS definition \
+                 FROM pg_index i \
+                 WHERE i.indrelid = {} \
+                   AND NOT EXISTS ( \
+                       SELECT 1 FROM pg_constraint c \
+                       WHERE c.conindid = i.indexrelid AND c.contype IN ('p', 'u', 'x')) \
+                 ORDER BY i.indexrelid",
+                relation
+            ))
+            .await?;
+
+        let mut statements = vec![postgres_create_table(
+            &quoted_table,
+            &text_values(&columns, "definition"),
+            &text_values(&constraints, "definition"),
+        )];
+        statements.extend(text_values(&indexes, "definition"));
+        ddl_script(&statements)
     }
 
     async fn fetch_tables(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
@@ -90,14 +117,42 @@ impl PostgresUI for DatabaseClientUI {
         Ok(vec![])
     }
 
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            // Planner estimates are instant, unlike count(*) on large tables.
+            let rows = client
+                .query(
+                    "SELECT n.nspname || '.' || c.relname AS table_name, \
+                         c.reltuples::bigint::text AS row_count \
+                     FROM pg_class c \
+                     JOIN pg_namespace n ON n.oid = c.relnamespace \
+                     WHERE c.relkind = 'r' \
+                       AND n.nspname NOT LIKE 'pg\\_%' AND n.nspname <> 'information_schema'",
+                )
+                .await?;
+            Ok(parse_row_counts(&rows, "table_name", "row_count"))
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
     async fn update_tables(&mut self) {
-        match self.fetch_tables().await {
+        match PostgresUI::fetch_tables(self).await {
             Ok(tables) => {
                 self.tables = tables;
                 self.selected_table = 0;
+                // Counts are decoration; a failure just leaves them out.
+                self.table_row_counts = PostgresUI::fetch_table_row_counts(self)
+                    .await
+                    .unwrap_or_default();
             }
             Err(err) => {
-                println!("Error fetching tables: {}", err);
+                self.connection_error_message = Some(format!("Error fetching tables: {}", err));
                 self.tables = Vec::new();
                 self.selected_table = 0;
             }
@@ -108,21 +163,21 @@ impl PostgresUI for DatabaseClientUI {
         &mut self,
         db_name: &str,
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let db_manager = self.db_manager.clone();
-        let mut connections = db_manager.connections.lock().await;
-        connections.clear();
-
-        let connection_string = format!(
-            "postgres://{}:{}@{}:{}/{}",
-            self.connection_input.username,
-            self.connection_input.password,
-            self.connection_input.hostname,
-            self.connection_input.port,
-            db_name,
+        let connection_string = self.connection_input.read_only_url(
+            DatabaseType::Postgres,
+            self.connection_input
+                .connection_url(DatabaseType::Postgres, db_name),
         );
 
-        let client = PostgresClient::connect(&connection_string).await?;
-        connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
+        // A Postgres pool is tied to one database; the manager keeps the pools
+        // of databases we leave so switching back to them is instant.
+        self.db_manager
+            .switch_connection(ConnectionConfig {
+                db_type: DbType::Postgres,
+                database_url: connection_string,
+                read_only: self.connection_input.read_only,
+            })
+            .await?;
 
         Ok(())
     }
@@ -131,12 +186,10 @@ impl PostgresUI for DatabaseClientUI {
         let db_manager = self.db_manager.clone();
         let mut connections = db_manager.connections.lock().await;
 
-        let connection_string = format!(
-            "postgres://{}:{}@{}:{}/postgres",
-            self.connection_input.username,
-            self.connection_input.password,
-            self.connection_input.hostname,
-            self.connection_input.port
+        let connection_string = self.connection_input.read_only_url(
+            DatabaseType::Postgres,
+            self.connection_input
+                .server_url(DatabaseType::Postgres, "postgres"),
         );
 
         let result = timeout(
@@ -160,4 +213,88 @@ impl PostgresUI for DatabaseClientUI {
             }
         }
     }
+
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        // Postgres connections are bound to a database, so reconnect with the same credentials.
+        PostgresUI::connect_to_selected_db(self, db_name).await
+    }
+
+    async fn fetch_server_sessions(
+        &self,
+    ) -> Result<Vec<ServerSession>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        let rows = client
+            .query(
+                "SELECT pid::text AS id, \
+                        COALESCE(usename::text, '') AS user_name, \
+                        COALESCE(datname::text, '') AS db_name, \
+                        COALESCE(state, '') AS state, \
+                        COALESCE(date_trunc('second', now() - query_start)::text, '') AS duration, \
+                        COALESCE(query, '') AS query, \
+                        (pid = pg_backend_pid())::text AS own \
+                 FROM pg_stat_activity \
+                 WHERE backend_type = 'client backend' \
+                 ORDER BY pid",
+            )
+            .await?;
+        Ok(server_sessions(&rows))
+    }
+
+    async fn terminate_server_session(&self, id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let pid = session_id(id)?;
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        let rows = client
+            .query(&format!(
+                "SELECT pg_terminate_backend({})::text AS terminated",
+                pid
+            ))
+            .await?;
+        if text_values(&rows, "terminated") == ["true"] {
+            Ok(())
+        } else {
+            Err(format!("Session {} is no longer running.", pid).into())
+        }
+    }
+
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        let overview = client
+            .query(
+                "SELECT version() AS version, \
+                        current_user::text AS user_name, \
+                        current_database()::text AS db_name, \
+                        current_setting('server_encoding') || ' (client ' || \
+                            current_setting('client_encoding') || ')' AS encoding",
+            )
+            .await?;
+        // Of the few hundred settings, those not at their built-in default
+        // say how this server and session are configured.
+        let settings = client
+            .query(
+                "SELECT name, setting || COALESCE(' ' || unit, '') AS value \
+                 FROM pg_settings \
+                 WHERE source NOT IN ('default', 'override') \
+                 ORDER BY name",
+            )
+            .await?;
+        Ok(ConnectionInfo::from_row(
+            &overview,
+            setting_pairs(&settings, "name", "value"),
+        ))
+    }
 }
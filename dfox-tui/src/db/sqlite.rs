@@ -0,0 +1,246 @@
+use std::{collections::HashMap, time::Duration};
+
+use dfox_core::{
+    db::{sqlite::SqliteClient, DbClient},
+    models::{connections::DbType, schema::TableSchema},
+    sql,
+};
+use tokio::time::timeout;
+
+use crate::ui::{DatabaseClientUI, DatabaseType, WaitInput};
+
+use super::{
+    ddl_script, parse_row_counts, query_rows, sqlite_plan_lines, text_values, value_text,
+    ConnectionInfo, ServerSession, SqliteUI,
+};
+
+impl SqliteUI for DatabaseClientUI {
+    async fn explain_query(&self, query: &str) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let statement = query.trim().trim_end_matches(';');
+        let rows = query_rows(
+            &self.db_manager,
+            &format!("EXPLAIN QUERY PLAN {}", statement),
+        )
+        .await?;
+        Ok(sqlite_plan_lines(&rows))
+    }
+
+    async fn describe_table(
+        &self,
+        table_name: &str,
+    ) -> Result<TableSchema, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            let schema = client.describe_table(table_name).await?;
+            Ok(schema)
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    async fn table_ddl(&self, table_name: &str) -> Result<String, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            // SQLite keeps the original text of every statement; automatic
+            // indexes have none.
+            let rows = client
+                .query(&format!(
+                    "SELECT sql FROM sqlite_master \
+                     WHERE tbl_name = {} AND sql IS NOT NULL \
+                     ORDER BY type <> 'table', name",
+                    sql::quote_literal(table_name)
+                ))
+                .await?;
+            ddl_script(&text_values(&rows, "sql"))
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    async fn fetch_tables(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            let tables = client.list_tables().await?;
+            Ok(tables)
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    async fn fetch_table_row_counts(
+        &self,
+    ) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            // SQLite keeps no row statistics, so count each table. Esc or the
+            // quit key stops after the current one, leaving the rest uncounted.
+            let mut counts = HashMap::new();
+            for table in client.list_tables().await? {
+                if matches!(self.wait_input(), Some(WaitInput::Cancel | WaitInput::Quit)) {
+                    break;
+                }
+                let query = format!(
+                    "SELECT {} AS table_name, count(*) AS row_count FROM {}",
+                    sql::quote_literal(&table),
+                    sql::quote_identifier(&DbType::Sqlite, &table)
+                );
+                let rows = client.query(&query).await?;
+                counts.extend(parse_row_counts(&rows, "table_name", "row_count"));
+            }
+            Ok(counts)
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    async fn update_tables(&mut self) {
+        match SqliteUI::fetch_tables(self).await {
+            Ok(tables) => {
+                self.tables = tables;
+                self.selected_table = 0;
+                // Counts are decoration; a failure just leaves them out.
+                self.table_row_counts = match SqliteUI::fetch_table_row_counts(self).await {
+                    Ok(counts) => {
+                        if counts.len() < self.tables.len() {
+                            self.status_message = Some(format!(
+                                "Row counts interrupted; counted {} of {} tables.",
+                                counts.len(),
+                                self.tables.len()
+                            ));
+                        }
+                        counts
+                    }
+                    Err(_) => HashMap::new(),
+                };
+            }
+            Err(err) => {
+                self.connection_error_message = Some(format!("Error fetching tables: {}", err));
+                self.tables = Vec::new();
+                self.selected_table = 0;
+            }
+        }
+    }
+
+    async fn connect_to_selected_db(
+        &mut self,
+        _db_name: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // A SQLite file holds a single database, so the connection opened in
+        // `connect_to_default_db` is already pointing at it.
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if connections.is_empty() {
+            Err("No database connection available.".into())
+        } else {
+            Ok(())
+        }
+    }
+
+    async fn connect_to_default_db(&mut self) -> Result<(), Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let mut connections = db_manager.connections.lock().await;
+
+        let connection_string = self.connection_input.read_only_url(
+            DatabaseType::SQLite,
+            format!("sqlite://{}", self.connection_input.file_path),
+        );
+
+        let result = timeout(
+            Duration::from_secs(3),
+            SqliteClient::connect(&connection_string),
+        )
+        .await;
+
+        match result {
+            Ok(Ok(client)) => {
+                connections.push(Box::new(client) as Box<dyn DbClient + Send + Sync>);
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                self.connection_error_message = Some(format!("Connection error: {}", e));
+                Err(Box::new(e))
+            }
+            Err(_) => {
+                self.connection_error_message = Some("Connection timed out".to_string());
+                Err("Timed out while trying to connect".into())
+            }
+        }
+    }
+
+    async fn switch_database(&mut self, db_name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+
+        if let Some(client) = connections.first() {
+            client.use_database(db_name).await?;
+            Ok(())
+        } else {
+            Err("No database connection available.".into())
+        }
+    }
+
+    async fn fetch_server_sessions(
+        &self,
+    ) -> Result<Vec<ServerSession>, Box<dyn std::error::Error>> {
+        Err(SQLITE_HAS_NO_SESSIONS.into())
+    }
+
+    async fn terminate_server_session(&self, _id: &str) -> Result<(), Box<dyn std::error::Error>> {
+        Err(SQLITE_HAS_NO_SESSIONS.into())
+    }
+
+    async fn fetch_connection_info(&self) -> Result<ConnectionInfo, Box<dyn std::error::Error>> {
+        let db_manager = self.db_manager.clone();
+        let connections = db_manager.connections.lock().await;
+        let Some(client) = connections.first() else {
+            return Err("No database connection available.".into());
+        };
+
+        // SQLite has no users; the database is the file that was opened.
+        let overview = client
+            .query(
+                "SELECT 'SQLite ' || sqlite_version() AS version, \
+                        '' AS user_name, \
+                        (SELECT file FROM pragma_database_list WHERE name = 'main') AS db_name, \
+                        (SELECT encoding FROM pragma_encoding) AS encoding",
+            )
+            .await?;
+        let mut settings = Vec::new();
+        for pragma in SQLITE_SETTINGS {
+            let rows = client
+                .query(&format!("SELECT * FROM pragma_{}", pragma))
+                .await?;
+            if let Some(value) = rows
+                .first()
+                .and_then(|row| row.as_object()?.values().next())
+            {
+                settings.push((pragma.to_string(), value_text(value)));
+            }
+        }
+        Ok(ConnectionInfo::from_row(&overview, settings))
+    }
+}
+
+/// Pragmas listed as the settings of a SQLite connection.
+const SQLITE_SETTINGS: [&str; 8] = [
+    "auto_vacuum",
+    "busy_timeout",
+    "cache_size",
+    "foreign_keys",
+    "journal_mode",
+    "page_size",
+    "synchronous",
+    "user_version",
+];
+
+/// SQLite is a file opened in-process, with no server to list sessions of.
+const SQLITE_HAS_NO_SESSIONS: &str = "SQLite has no server sessions to list.";
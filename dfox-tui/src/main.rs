@@ -1,14 +1,49 @@
-use std::sync::Arc;
+use std::{io, sync::Arc};
 
+use clap::Parser;
+use cli::Cli;
 use dfox_core::DbManager;
-use ui::DatabaseClientUI;
+use ui::{Config, DatabaseClientUI};
+mod cli;
 mod db;
 mod ui;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    let password = cli.read_password()?;
+
     let db_manager = Arc::new(DbManager::new());
     let mut tui = DatabaseClientUI::new(db_manager);
+    match Config::load() {
+        Ok(config) => tui.apply_config(config),
+        Err(err) => {
+            eprintln!("{}", err);
+            tui.connection_error_message = Some(err);
+        }
+    }
+    tui.query_timeout = cli.query_timeout();
+    if let Some(page_size) = cli.page_size {
+        tui.page_size = page_size.max(1);
+    }
+    tui.connect_retries = cli.connect_retries;
+    tui.connection_input.read_only = cli.read_only;
+    if let Some(password) = password {
+        tui.connection_input.set_external_password(password);
+    }
+
+    if let Some(query) = &cli.execute {
+        let url = cli
+            .database_url()
+            .ok_or("--execute needs --url or DATABASE_URL")?;
+        let message = tui
+            .run_batch_query(&url, query, cli.format, &mut io::stdout().lock())
+            .await?;
+        if let Some(message) = message {
+            eprintln!("{}", message);
+        }
+        return Ok(());
+    }
     tui.run_ui().await?;
 
     Ok(())
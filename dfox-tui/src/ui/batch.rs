@@ -0,0 +1,175 @@
+use std::{collections::HashMap, io::Write};
+
+use dfox_core::{export, sql};
+use serde_json::Value;
+use url::Url;
+
+use crate::{cli::OutputFormat, db::run_statement_limited};
+
+use super::{
+    connection::{sqlite_url_path, url_database_type},
+    results::cell_text,
+    DatabaseClientUI, DatabaseType,
+};
+
+impl DatabaseClientUI {
+    /// Connects to `url`, runs `query` and writes every row it returns to
+    /// `out` as `format`, for `--execute`. Returns the message of a statement
+    /// without rows, such as its affected row count.
+    pub async fn run_batch_query(
+        &mut self,
+        url: &str,
+        query: &str,
+        format: OutputFormat,
+        out: &mut impl Write,
+    ) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        self.db_type = url_database_type(url)
+            .ok_or("The database URL must start with postgres://, mysql:// or sqlite:")?;
+        if self.connection_input.read_only {
+            if let Some(statement) = sql::find_write(&self.db_type.db_type(), query) {
+                return Err(format!(
+                    "Read-only mode only runs statements that read; refused: {}",
+                    statement
+                )
+                .into());
+            }
+        }
+        if self.db_type == DatabaseType::SQLite {
+            self.connection_input.file_path = sqlite_url_path(url);
+        } else {
+            let mut url = Url::parse(url)?;
+            if self.connection_input.external_password && url.password().is_none() {
+                url.set_password(Some(&self.connection_input.password))
+                    .map_err(|()| "The database URL can't hold a password")?;
+            }
+            self.connection_input.url = url.into();
+        }
+
+        self.connect_to_default_db().await?;
+        // Unlike the result grid, the output isn't capped at MAX_RESULT_ROWS.
+        let outcome =
+            run_statement_limited(&self.db_manager, &self.db_type.db_type(), query, usize::MAX)
+                .await
+                .map_err(|err| err.to_string())?;
+        write_rows(out, format, &outcome.columns, &outcome.rows)?;
+        Ok(outcome.message)
+    }
+}
+
+/// Writes a result to `out`; statements without columns write nothing.
+fn write_rows(
+    out: &mut impl Write,
+    format: OutputFormat,
+    columns: &[String],
+    rows: &[HashMap<String, Value>],
+) -> Result<(), Box<dyn std::error::Error>> {
+    if columns.is_empty() {
+        return Ok(());
+    }
+    match format {
+        OutputFormat::Table => out.write_all(text_table(columns, rows).as_bytes())?,
+        OutputFormat::Csv => export::write_csv(out, columns, rows)?,
+        OutputFormat::Json => export::write_json(out, columns, rows)?,
+    }
+    Ok(())
+}
+
+/// The rows as aligned plain-text columns under a header, the way `psql`
+/// prints them.
+fn text_table(columns: &[String], rows: &[HashMap<String, Value>]) -> String {
+    let cells: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| {
+            columns
+                .iter()
+                .map(|column| cell_text(row, column))
+                .collect()
+        })
+        .collect();
+    let widths: Vec<usize> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| {
+            cells
+                .iter()
+                .map(|row| row[i].chars().count())
+                .chain([column.chars().count()])
+                .max()
+                .unwrap_or_default()
+        })
+        .collect();
+    let line = |values: &[String]| {
+        let padded: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:<width$}", value, width = width))
+            .collect();
+        format!("{}\n", padded.join(" | ").trim_end())
+    };
+
+    let mut table = line(columns);
+    let rule: Vec<String> = widths.iter().map(|width| "-".repeat(*width)).collect();
+    table.push_str(&format!("{}\n", rule.join("-+-")));
+    for row in &cells {
+        table.push_str(&line(row));
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dfox_core::DbManager;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_query_prints_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = format!("sqlite://{}?mode=rwc", dir.path().join("x.db").display());
+        let run = |query: &'static str, format: OutputFormat| {
+            let url = url.clone();
+            async move {
+                let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+                let mut out = Vec::new();
+                let message = ui
+                    .run_batch_query(&url, query, format, &mut out)
+                    .await
+                    .unwrap();
+                (String::from_utf8(out).unwrap(), message)
+            }
+        };
+
+        run(
+            "CREATE TABLE users (id INTEGER, name TEXT)",
+            OutputFormat::Table,
+        )
+        .await;
+        let (out, message) = run(
+            "INSERT INTO users VALUES (1, 'ada'), (22, NULL)",
+            OutputFormat::Table,
+        )
+        .await;
+        assert!(out.is_empty());
+        assert_eq!(message.as_deref(), Some("Query OK, 2 rows affected."));
+
+        let (out, _) = run("SELECT id, name FROM users", OutputFormat::Table).await;
+        assert_eq!(out, "id | name\n---+-----\n1  | ada\n22 | NULL\n");
+        let (out, _) = run("SELECT id, name FROM users", OutputFormat::Csv).await;
+        assert_eq!(out, "id,name\n1,ada\n22,\n");
+
+        let (out, message) = run(
+            "-- every row\nWITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n \
+             WHERE i < 10005) SELECT i FROM n",
+            OutputFormat::Csv,
+        )
+        .await;
+        assert_eq!(out.lines().count(), 10_006);
+        assert!(out.ends_with("10005\n"));
+        assert_eq!(message, None);
+
+        let (out, _) = run("PRAGMA table_info(users)", OutputFormat::Csv).await;
+        assert!(out.starts_with("cid,name,type"));
+    }
+}
@@ -0,0 +1,28 @@
+use std::io::{self, Write};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Copies `text` to the system clipboard through the terminal.
+///
+/// Uses the OSC 52 escape sequence, which the terminal emulator handles, so it
+/// needs no display server and also works over SSH. Terminals that don't
+/// support it ignore the sequence.
+pub fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    stdout.write_all(osc52_sequence(text).as_bytes())?;
+    stdout.flush()
+}
+
+fn osc52_sequence(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(osc52_sequence("id\tname"), "\x1b]52;c;aWQJbmFtZQ==\x07");
+    }
+}
@@ -0,0 +1,253 @@
+use dfox_core::sql;
+
+use super::DatabaseClientUI;
+
+/// Candidates offered by the SQL editor's Tab completion popup.
+pub struct Completion {
+    /// Byte offset in `sql_editor_content` where the word being completed starts.
+    pub start: usize,
+    pub candidates: Vec<String>,
+    pub selected: usize,
+}
+
+/// The partially typed word before the cursor, e.g. `u.na` in `SELECT u.na`.
+#[derive(Debug, PartialEq)]
+struct CompletionWord<'a> {
+    /// Table name or alias before the last `.`, if any.
+    qualifier: Option<&'a str>,
+    prefix: &'a str,
+    start: usize,
+}
+
+fn word_before_cursor(sql: &str, cursor: usize) -> CompletionWord<'_> {
+    let before_cursor = &sql[..cursor];
+    let word_start = before_cursor
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .map_or(0, |idx| idx + 1);
+    let word = &before_cursor[word_start..];
+
+    match word.rsplit_once('.') {
+        Some((qualifier, prefix)) => CompletionWord {
+            qualifier: Some(qualifier),
+            prefix,
+            start: cursor - prefix.len(),
+        },
+        None => CompletionWord {
+            qualifier: None,
+            prefix: word,
+            start: word_start,
+        },
+    }
+}
+
+/// Finds the table `qualifier` refers to: either a table name itself or an
+/// alias introduced as `table alias` / `table AS alias` in `sql`. A
+/// `schema.table` name also goes by its table part.
+fn resolve_table(qualifier: &str, sql: &str, tables: &[String]) -> Option<String> {
+    let find_table = |name: &str| {
+        tables
+            .iter()
+            .find(|table| table.eq_ignore_ascii_case(name))
+            .or_else(|| {
+                tables
+                    .iter()
+                    .find(|table| sql::split_qualified(table).1.eq_ignore_ascii_case(name))
+            })
+            .cloned()
+    };
+
+    if let Some(table) = find_table(qualifier) {
+        return Some(table);
+    }
+
+    let words: Vec<&str> = sql
+        .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .filter(|word| !word.is_empty())
+        .collect();
+    words.iter().enumerate().find_map(|(i, word)| {
+        let alias = match words.get(i + 1) {
+            Some(next) if next.eq_ignore_ascii_case("AS") => words.get(i + 2),
+            next => next,
+        };
+        match alias {
+            Some(alias) if *alias == qualifier => find_table(word),
+            _ => None,
+        }
+    })
+}
+
+/// Names starting with `prefix`, ignoring case, sorted and without duplicates.
+fn matching_names<'a>(names: impl IntoIterator<Item = &'a str>, prefix: &str) -> Vec<String> {
+    let prefix = prefix.to_lowercase();
+    let mut matches: Vec<String> = names
+        .into_iter()
+        .filter(|name| name.to_lowercase().starts_with(&prefix))
+        .map(str::to_string)
+        .collect();
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+impl DatabaseClientUI {
+    /// Completes the word before the cursor with a table name, with a
+    /// column name after `table.` / `alias.`, or with a table name after
+    /// `schema.`.
+    ///
+    /// A single candidate is inserted directly; several open the popup.
+    /// Returns `false` when there is no word to complete.
+    pub async fn editor_complete(&mut self) -> bool {
+        let content = self.sql_editor_content.clone();
+        let word = word_before_cursor(&content, self.sql_editor_cursor);
+
+        let in_schema: Vec<&str> = word
+            .qualifier
+            .map(|qualifier| {
+                self.tables
+                    .iter()
+                    .filter_map(|table| match sql::split_qualified(table) {
+                        (Some(schema), name) if schema.eq_ignore_ascii_case(qualifier) => {
+                            Some(name)
+                        }
+                        _ => None,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let candidates = match word.qualifier {
+            Some(_) if !in_schema.is_empty() => matching_names(in_schema, word.prefix),
+            Some(qualifier) => {
+                let Some(table) = resolve_table(qualifier, &content, &self.tables) else {
+                    self.status_message = Some(format!("Unknown table or alias: {}", qualifier));
+                    return true;
+                };
+                if !self.table_schemas.contains_key(&table) {
+                    match self.describe_table(&table).await {
+                        Ok(schema) => {
+                            self.table_schemas.insert(table.clone(), schema);
+                        }
+                        Err(err) => {
+                            self.status_message = Some(format!("Error describing table: {}", err));
+                            return true;
+                        }
+                    }
+                }
+                let columns = self.table_schemas[&table]
+                    .columns
+                    .iter()
+                    .map(|column| column.name.as_str());
+                matching_names(columns, word.prefix)
+            }
+            None if word.prefix.is_empty() => return false,
+            None => {
+                let names = self.tables.iter().flat_map(|table| {
+                    let short_name = sql::split_qualified(table).1;
+                    [table.as_str(), short_name]
+                });
+                matching_names(names, word.prefix)
+            }
+        };
+
+        match candidates.as_slice() {
+            [] => self.status_message = Some("No completions.".to_string()),
+            [candidate] => {
+                let candidate = candidate.clone();
+                self.apply_completion(word.start, &candidate);
+            }
+            _ => {
+                self.completion = Some(Completion {
+                    start: word.start,
+                    candidates,
+                    selected: 0,
+                })
+            }
+        }
+        true
+    }
+
+    /// Inserts the candidate selected in the popup and closes it.
+    pub fn accept_completion(&mut self) {
+        if let Some(completion) = self.completion.take() {
+            if let Some(candidate) = completion.candidates.get(completion.selected) {
+                self.apply_completion(completion.start, candidate);
+            }
+        }
+    }
+
+    fn apply_completion(&mut self, start: usize, candidate: &str) {
+        self.editor_checkpoint();
+        self.sql_editor_selection = None;
+        self.sql_editor_content
+            .replace_range(start..self.sql_editor_cursor, candidate);
+        self.sql_editor_cursor = start + candidate.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tables() -> Vec<String> {
+        vec!["orders".to_string(), "users".to_string()]
+    }
+
+    #[test]
+    fn test_word_before_cursor() {
+        let sql = "SELECT u.na";
+        assert_eq!(
+            word_before_cursor(sql, sql.len()),
+            CompletionWord {
+                qualifier: Some("u"),
+                prefix: "na",
+                start: 9,
+            }
+        );
+        assert_eq!(
+            word_before_cursor("FROM us", 7),
+            CompletionWord {
+                qualifier: None,
+                prefix: "us",
+                start: 5,
+            }
+        );
+        assert_eq!(word_before_cursor("FROM ", 5).prefix, "");
+    }
+
+    #[test]
+    fn test_resolve_table_by_name_or_alias() {
+        let sql = "SELECT * FROM users u JOIN orders AS o ON o.user_id = u.id";
+        assert_eq!(
+            resolve_table("USERS", sql, &tables()).as_deref(),
+            Some("users")
+        );
+        assert_eq!(resolve_table("u", sql, &tables()).as_deref(), Some("users"));
+        assert_eq!(
+            resolve_table("o", sql, &tables()).as_deref(),
+            Some("orders")
+        );
+        assert_eq!(resolve_table("x", sql, &tables()), None);
+    }
+
+    #[test]
+    fn test_resolve_table_by_schema_qualified_name() {
+        let tables = vec!["public.users".to_string(), "analytics.events".to_string()];
+        let sql = "SELECT e. FROM analytics.events e";
+        assert_eq!(
+            resolve_table("e", sql, &tables).as_deref(),
+            Some("analytics.events")
+        );
+        assert_eq!(
+            resolve_table("public.users", sql, &tables).as_deref(),
+            Some("public.users")
+        );
+    }
+
+    #[test]
+    fn test_matching_names_ignores_case() {
+        assert_eq!(
+            matching_names(["Users", "orders", "user_roles"], "us"),
+            vec!["Users", "user_roles"]
+        );
+    }
+}
@@ -1,86 +1,398 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    panic,
+    sync::Arc,
+    time::Duration,
+};
 
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    cursor::Show,
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use dfox_core::{models::schema::TableSchema, DbManager};
-use ratatui::{backend::CrosstermBackend, Terminal};
+use dfox_core::{
+    models::{
+        connections::DbType,
+        schema::{DbObject, TableSchema},
+    },
+    notices, DbManager,
+};
+use ratatui::{backend::CrosstermBackend, layout::Rect, Terminal};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::io;
 
-use super::{UIHandler, UIRenderer};
+use super::{
+    completion::Completion,
+    config::ConnectionDefaults,
+    connection::{ConnectPrompt, ConnectRetry, ConnectionEnv, SslMode},
+    diagnostics::ConnectionInfoView,
+    editor::EditHistory,
+    history::QueryHistory,
+    keymap::{Action, Keymap},
+    query_task::PendingFetch,
+    result_cache::ResultCache,
+    results::{InsertExport, SortOrder, SPINNER_INTERVAL},
+    routines::RoutineCall,
+    saved_connections::SavedConnections,
+    server_sessions::ServerSessionList,
+    sessions::Session,
+    tables::{SchemaView, TablePreview},
+    theme::Theme,
+    timestamps::TimestampColumns,
+    UIHandler, UIRenderer,
+};
 
 pub struct DatabaseClientUI {
     pub db_manager: Arc<DbManager>,
     pub connection_input: ConnectionInput,
+    /// Connection defaults from environment variables such as `PGPASSWORD`.
+    pub connection_env: ConnectionEnv,
+    /// Connection defaults from the `[connection]` table of the config file.
+    pub connection_defaults: ConnectionDefaults,
+    pub saved_connections: SavedConnections,
+    /// Cursor into the saved connections for `db_type`; one past the end is "New connection".
+    pub selected_saved_connection: usize,
     pub current_screen: ScreenState,
     pub selected_db_type: usize,
+    pub db_type: DatabaseType,
     pub selected_database: usize,
     pub databases: Vec<String>,
+    /// Characters the database selection list is fuzzy-matched against;
+    /// empty shows every database. `selected_database` indexes the matches.
+    pub database_filter: String,
+    /// Set while keystrokes on the database selection screen go to its filter.
+    pub editing_database_filter: bool,
+    /// The database list loading for the database selection screen.
+    pub database_fetch: Option<PendingFetch<Vec<String>>>,
+    /// How many times a failed connection is tried again before giving up.
+    pub connect_retries: u32,
+    /// The next attempt of a connection that failed, while retries are left.
+    pub connect_retry: Option<ConnectRetry>,
+    /// The summary shown before connecting from the connection screen.
+    pub connect_prompt: Option<ConnectPrompt>,
     pub current_focus: FocusedWidget,
     pub selected_table: usize,
     pub tables: Vec<String>,
+    /// Row counts shown next to table names, refreshed with the tables list.
+    pub table_row_counts: HashMap<String, u64>,
+    /// Views, materialized views and sequences, listed after the tables
+    /// while `show_objects` is set.
+    pub objects: Vec<DbObject>,
+    /// Whether the Tables panel lists `objects` too, from `show_all_objects`
+    /// in the config file.
+    pub show_objects: bool,
+    /// First line of the Tables panel in view, scrolled by the renderer so the
+    /// selected table stays visible below an expanded schema.
+    pub tables_list_offset: usize,
+    /// Substring the Tables panel is filtered by; empty shows every table.
+    pub table_filter: String,
+    /// Set while keystrokes go to the table filter instead of the focused widget.
+    pub editing_table_filter: bool,
     pub sql_editor_content: String,
+    pub sql_editor_cursor: usize,
+    /// Where a Shift+arrow selection started; it runs from here to the cursor.
+    pub sql_editor_selection: Option<usize>,
+    /// First (column, line) of the SQL editor in view, scrolled by the
+    /// renderer so the cursor stays visible.
+    pub editor_scroll: (u16, u16),
+    /// Earlier and undone states of the SQL editor, for Ctrl+Z / Ctrl+Y.
+    pub edit_history: EditHistory,
+    /// Tab completion popup in the SQL editor, while open.
+    pub completion: Option<Completion>,
+    pub query_history: QueryHistory,
+    /// Whether `last_queries` refills the editor, from `remember_last_query`
+    /// in the config file.
+    pub remember_last_query: bool,
+    /// The last script run from the editor, keyed by `connection_label`.
+    pub last_queries: HashMap<String, String>,
+    /// Timestamp columns of the results and how to show them.
+    pub timestamp_columns: TimestampColumns,
     pub sql_query_result: Vec<HashMap<String, Value>>,
-    pub expanded_table: Option<usize>,
+    /// Column names of `sql_query_result` in the order the query selected them.
+    pub sql_query_columns: Vec<String>,
+    /// Index of the first result row shown in the Query Result panel.
+    pub result_scroll: usize,
+    /// Number of body rows that fit in the Query Result panel, updated on render.
+    pub result_viewport_rows: usize,
+    /// Index of the first result column shown in the Query Result panel.
+    pub result_column_offset: usize,
+    /// Row of the focused cell in the Query Result panel.
+    pub selected_result_row: usize,
+    /// Column of the focused cell in the Query Result panel.
+    pub selected_result_column: usize,
+    /// Full value of the focused result cell, shown in a popup while set.
+    pub cell_detail: Option<String>,
+    /// Table whose DDL the cell detail popup shows instead of a cell value.
+    pub cell_detail_table: Option<String>,
+    /// First wrapped line shown in the cell detail popup.
+    pub cell_detail_scroll: usize,
+    /// Characters scrolled past on the left while the popup isn't wrapping.
+    pub cell_detail_hscroll: usize,
+    /// Whether the cell detail popup wraps long lines; toggled with `w`.
+    pub cell_detail_wrap: bool,
+    /// Whether the cell detail popup numbers its lines; toggled with `n`.
+    pub cell_detail_line_numbers: bool,
+    /// Table paged through with `p`, `[` and `]`, if its rows are being shown.
+    pub table_preview: Option<TablePreview>,
+    /// Rows per table preview page.
+    pub page_size: usize,
+    /// Tables whose columns and indexes are shown in the schema tree.
+    pub expanded_tables: HashSet<String>,
     pub table_schemas: HashMap<String, TableSchema>,
     pub sql_query_error: Option<String>,
+    /// Longest an editor query may run before it is abandoned; `None` waits forever.
+    pub query_timeout: Option<Duration>,
     pub sql_query_success_message: Option<String>,
+    /// Outcome of each statement of the last multi-statement run, shown under the results.
+    pub script_log: Vec<String>,
+    /// NOTICE and WARNING messages the server sent during the last run.
+    pub server_messages: Vec<String>,
+    /// Error shown in a dialog over the current screen until a key is pressed.
     pub connection_error_message: Option<String>,
+    /// One-line feedback shown under the table view panels (e.g. export results).
+    pub status_message: Option<String>,
+    /// Whether a transaction is open, kept in step with the `DbManager` so
+    /// drawing never waits on the lock a running query holds.
+    pub transaction_active: bool,
+    pub result_footer: Option<FooterAggregate>,
+    /// Column the fetched rows were sorted by, and in which direction.
+    pub result_sort: Option<(String, SortOrder)>,
+    /// Whether sorting puts NULLs after the other values rather than before,
+    /// whichever the order.
+    pub result_nulls_last: bool,
+    /// Result columns kept on the left while the others scroll sideways, by
+    /// name, so they stay pinned when the query runs again.
+    pub pinned_columns: HashSet<String>,
+    /// Substring the fetched rows are narrowed to; empty shows every row.
+    pub result_filter: String,
+    /// Set while keystrokes go to the result filter.
+    pub editing_result_filter: bool,
+    /// The INSERT statements prompt, while open.
+    pub insert_export: Option<InsertExport>,
+    /// The argument prompt of a function or procedure being called.
+    pub routine_call: Option<RoutineCall>,
+    /// Every fetched row while `result_filter` hides some of them from
+    /// `sql_query_result`.
+    pub unfiltered_result: Option<Vec<HashMap<String, Value>>>,
+    /// Editor query results reused while fresh, when `result_cache_seconds`
+    /// is set.
+    pub result_cache: ResultCache,
+    /// Set while the Query Result panel shows a result from `result_cache`.
+    pub result_from_cache: bool,
+    pub current_database: Option<String>,
+    /// Cursor into `databases` while the F2 database switcher is open.
+    pub database_switcher: Option<usize>,
+    /// Every open connection, in the order the switcher lists them. The one
+    /// shown lives in the fields above, so its slot is `None`.
+    pub sessions: Vec<Option<Session>>,
+    /// Cursor into `sessions` while the F4 connection switcher is open; one
+    /// past the end is "New connection".
+    pub session_switcher: Option<usize>,
+    /// The F10 list of client sessions on the server, while open.
+    pub server_sessions: Option<ServerSessionList>,
+    /// Destructive statement awaiting a y/n confirmation before the editor query runs.
+    pub pending_destructive_query: Option<String>,
+    /// Whether that statement came from explaining the editor query with
+    /// `EXPLAIN ANALYZE`, so confirming explains rather than runs it.
+    pub pending_destructive_explain: bool,
+    /// Set after a lone `g` so a second `g` jumps to the top of the focused list.
+    pub pending_g: bool,
+    /// Set by handlers to leave the UI loop and restore the terminal.
+    pub should_quit: bool,
+    /// Where the table view panels were last drawn, for mapping mouse clicks.
+    pub panel_areas: PanelAreas,
+    /// Colors every screen is drawn with, picked in the config file.
+    pub theme: Theme,
+    /// Keys for the table view actions, with any set in the config file.
+    pub keymap: Keymap,
+    /// First line shown in the `?` key help overlay while it is open.
+    pub help_scroll: Option<usize>,
+    /// The table whose schema fills the table view, while it is open.
+    pub schema_view: Option<SchemaView>,
+    /// The connection diagnostics filling the table view, while open.
+    pub connection_info: Option<ConnectionInfoView>,
+}
+
+/// Screen areas of the table view panels.
+#[derive(Default)]
+pub struct PanelAreas {
+    pub tables: Rect,
+    pub editor: Rect,
+    pub results: Rect,
 }
 
 pub enum InputField {
+    Url,
     Username,
     Password,
     Hostname,
     Port,
+    Database,
+    SslMode,
+    SslRootCert,
 }
 
 pub struct ConnectionInput {
+    /// A full connection URI; when set it is used instead of the fields below.
+    pub url: String,
     pub username: String,
     pub password: String,
     pub hostname: String,
     pub port: String,
+    /// Database the server connection opens first; blank for the backend's
+    /// own (`postgres` or `mysql`).
+    pub database: String,
+    pub file_path: String,
+    pub ssl_mode: SslMode,
+    /// CA certificate used to verify the server with `verify-ca`/`verify-full`.
+    pub ssl_root_cert: String,
     pub current_field: InputField,
+    /// Set when the password was supplied via `--password-file`/`--password-stdin`.
+    pub external_password: bool,
+    /// Shows the password in clear text instead of `*`s; toggled with Ctrl+R.
+    pub show_password: bool,
+    /// Set by `--read-only`: the editor refuses statements that write, and
+    /// the connection itself is opened read-only where the backend allows.
+    pub read_only: bool,
 }
 
 impl ConnectionInput {
     pub fn new() -> Self {
         Self {
+            url: String::new(),
             username: String::new(),
             password: String::new(),
             hostname: String::new(),
             port: String::new(),
+            database: String::new(),
+            file_path: String::new(),
+            ssl_mode: SslMode::default(),
+            ssl_root_cert: String::new(),
             current_field: InputField::Username,
+            external_password: false,
+            show_password: false,
+            read_only: false,
+        }
+    }
+
+    pub fn set_external_password(&mut self, password: String) {
+        self.password = password;
+        self.external_password = true;
+    }
+
+    pub fn next_field(&self) -> InputField {
+        match self.current_field {
+            InputField::Url => InputField::Username,
+            InputField::Username if self.external_password => InputField::Hostname,
+            InputField::Username => InputField::Password,
+            InputField::Password => InputField::Hostname,
+            InputField::Hostname => InputField::Port,
+            InputField::Port => InputField::Database,
+            InputField::Database => InputField::SslMode,
+            InputField::SslMode => InputField::SslRootCert,
+            InputField::SslRootCert => InputField::SslRootCert,
+        }
+    }
+
+    pub fn previous_field(&self) -> InputField {
+        match self.current_field {
+            InputField::SslRootCert => InputField::SslMode,
+            InputField::SslMode => InputField::Database,
+            InputField::Database => InputField::Port,
+            InputField::Port => InputField::Hostname,
+            InputField::Hostname if self.external_password => InputField::Username,
+            InputField::Hostname => InputField::Password,
+            InputField::Password => InputField::Username,
+            InputField::Username => InputField::Url,
+            InputField::Url => InputField::Url,
         }
     }
 }
 
 pub enum ScreenState {
     DbTypeSelection,
+    SavedConnections,
     DatabaseSelection,
     ConnectionInput,
     TableView,
-    MessagePopup,
+    SqliteFileInput,
 }
 
-#[derive(Clone, PartialEq)]
+impl ScreenState {
+    /// Whether the screen has text fields or an editor, where `q` is typed
+    /// rather than quitting.
+    pub fn accepts_text(&self) -> bool {
+        matches!(
+            self,
+            ScreenState::ConnectionInput | ScreenState::SqliteFileInput | ScreenState::TableView
+        )
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum FocusedWidget {
     TablesList,
     SqlEditor,
-    _QueryResult,
+    QueryResult,
+}
+
+/// Aggregate shown in the optional footer row of the query result table.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FooterAggregate {
+    Sum,
+    Avg,
+    Count,
+}
+
+impl FooterAggregate {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FooterAggregate::Sum => "SUM",
+            FooterAggregate::Avg => "AVG",
+            FooterAggregate::Count => "COUNT",
+        }
+    }
+
+    /// Cycles off -> SUM -> AVG -> COUNT -> off.
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(FooterAggregate::Sum),
+            Some(FooterAggregate::Sum) => Some(FooterAggregate::Avg),
+            Some(FooterAggregate::Avg) => Some(FooterAggregate::Count),
+            Some(FooterAggregate::Count) => None,
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum DatabaseType {
+    #[serde(alias = "postgres")]
     Postgres,
+    #[serde(alias = "mysql")]
     MySQL,
+    #[serde(alias = "sqlite")]
     SQLite,
 }
 
 impl DatabaseType {
+    pub const ALL: [DatabaseType; 3] = [
+        DatabaseType::Postgres,
+        DatabaseType::MySQL,
+        DatabaseType::SQLite,
+    ];
+
+    pub fn from_index(index: usize) -> Self {
+        Self::ALL
+            .get(index)
+            .copied()
+            .unwrap_or(DatabaseType::Postgres)
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             DatabaseType::Postgres => "Postgres",
@@ -88,6 +400,25 @@ impl DatabaseType {
             DatabaseType::SQLite => "SQLite",
         }
     }
+
+    /// The core crate's name for this backend.
+    pub fn db_type(&self) -> DbType {
+        match self {
+            DatabaseType::Postgres => DbType::Postgres,
+            DatabaseType::MySQL => DbType::MySql,
+            DatabaseType::SQLite => DbType::Sqlite,
+        }
+    }
+
+    /// The port the backend's server listens on unless told otherwise;
+    /// empty for SQLite, which has no server.
+    pub fn default_port(&self) -> &'static str {
+        match self {
+            DatabaseType::Postgres => "5432",
+            DatabaseType::MySQL => "3306",
+            DatabaseType::SQLite => "",
+        }
+    }
 }
 
 impl DatabaseClientUI {
@@ -95,43 +426,120 @@ impl DatabaseClientUI {
         Self {
             db_manager,
             connection_input: ConnectionInput::new(),
+            connection_env: ConnectionEnv::default(),
+            connection_defaults: ConnectionDefaults::default(),
+            saved_connections: SavedConnections::default(),
+            selected_saved_connection: 0,
             current_screen: ScreenState::DbTypeSelection,
             selected_db_type: 0,
+            db_type: DatabaseType::Postgres,
             selected_database: 0,
             databases: Vec::new(),
+            database_filter: String::new(),
+            editing_database_filter: false,
+            database_fetch: None,
+            connect_retries: 0,
+            connect_retry: None,
+            connect_prompt: None,
             current_focus: FocusedWidget::TablesList,
             selected_table: 0,
             tables: Vec::new(),
+            table_row_counts: HashMap::new(),
+            objects: Vec::new(),
+            show_objects: false,
+            tables_list_offset: 0,
+            table_filter: String::new(),
+            editing_table_filter: false,
             sql_editor_content: String::new(),
+            sql_editor_cursor: 0,
+            sql_editor_selection: None,
+            editor_scroll: (0, 0),
+            edit_history: EditHistory::default(),
+            completion: None,
+            query_history: QueryHistory::default(),
+            remember_last_query: false,
+            last_queries: HashMap::new(),
+            timestamp_columns: TimestampColumns::default(),
             sql_query_result: Vec::new(),
-            expanded_table: None,
+            sql_query_columns: Vec::new(),
+            result_scroll: 0,
+            result_viewport_rows: 1,
+            result_column_offset: 0,
+            selected_result_row: 0,
+            selected_result_column: 0,
+            cell_detail: None,
+            cell_detail_table: None,
+            cell_detail_scroll: 0,
+            cell_detail_hscroll: 0,
+            cell_detail_wrap: true,
+            cell_detail_line_numbers: false,
+            table_preview: None,
+            page_size: 100,
+            expanded_tables: HashSet::new(),
             table_schemas: HashMap::new(),
             sql_query_error: None,
+            query_timeout: None,
             sql_query_success_message: None,
+            script_log: Vec::new(),
+            server_messages: Vec::new(),
             connection_error_message: None,
+            status_message: None,
+            transaction_active: false,
+            result_footer: None,
+            result_sort: None,
+            result_nulls_last: false,
+            pinned_columns: HashSet::new(),
+            result_filter: String::new(),
+            editing_result_filter: false,
+            insert_export: None,
+            routine_call: None,
+            unfiltered_result: None,
+            result_cache: ResultCache::default(),
+            result_from_cache: false,
+            current_database: None,
+            database_switcher: None,
+            sessions: vec![None],
+            session_switcher: None,
+            server_sessions: None,
+            pending_destructive_query: None,
+            pending_destructive_explain: false,
+            pending_g: false,
+            should_quit: false,
+            panel_areas: PanelAreas::default(),
+            theme: Theme::default(),
+            keymap: Keymap::default(),
+            help_scroll: None,
+            schema_view: None,
+            connection_info: None,
         }
     }
 
     pub fn current_input_index(&self) -> usize {
         match self.connection_input.current_field {
-            InputField::Username => 0,
-            InputField::Password => 1,
-            InputField::Hostname => 2,
-            InputField::Port => 3,
+            InputField::Url => 0,
+            InputField::Username => 1,
+            InputField::Password => 2,
+            InputField::Hostname => 3,
+            InputField::Port => 4,
+            InputField::Database => 5,
+            InputField::SslMode => 6,
+            InputField::SslRootCert => 7,
         }
     }
 
     pub async fn run_ui(&mut self) -> Result<(), io::Error> {
-        let _guard = TerminalGuard;
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
+        let _guard = TerminalGuard::new();
+        enter_terminal()?;
+        let backend = CrosstermBackend::new(io::stdout());
         let mut terminal = Terminal::new(backend)?;
 
+        self.query_history = QueryHistory::load();
+        self.saved_connections = SavedConnections::load();
+        notices::capture_notices();
+        self.load_connection_env().await;
         let result = self.ui_loop(&mut terminal).await;
-
-        terminal.show_cursor()?;
+        // Losing history is not worth failing the exit over.
+        let _ = self.query_history.save();
 
         result
     }
@@ -140,12 +548,20 @@ impl DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
-        loop {
+        while !self.should_quit {
+            self.finish_database_fetch().await;
+            self.retry_connection_if_due().await;
+
             match self.current_screen {
                 ScreenState::DbTypeSelection => {
                     UIRenderer::render_db_type_selection_screen(self, terminal).await?
                 }
-                ScreenState::MessagePopup => self.render_message_popup(terminal).await?,
+                ScreenState::SavedConnections => {
+                    UIRenderer::render_saved_connections_screen(self, terminal).await?
+                }
+                ScreenState::SqliteFileInput => {
+                    UIRenderer::render_sqlite_file_input_screen(self, terminal).await?
+                }
                 ScreenState::ConnectionInput => {
                     UIRenderer::render_connection_input_screen(self, terminal).await?
                 }
@@ -157,24 +573,153 @@ impl DatabaseClientUI {
                 }
             }
 
-            if let Event::Key(key) = event::read()? {
+            // The "Connecting to …" dialog is on screen now, so the attempt
+            // can block until it succeeds or times out.
+            if self.connect_prompt == Some(ConnectPrompt::Connecting) {
+                self.connect_prompt = None;
+                self.connect_from_input().await;
+                continue;
+            }
+
+            // Redraw the spinner until the database list arrives, and wake up
+            // for the next connection attempt.
+            let waiting = self.database_fetch.is_some() || self.connect_retry.is_some();
+            if waiting && !event::poll(SPINNER_INTERVAL)? {
+                continue;
+            }
+            let event = event::read()?;
+            if let Event::Paste(text) = &event {
+                self.handle_paste(text);
+                continue;
+            }
+
+            if let Event::Mouse(mouse) = event {
+                if matches!(self.current_screen, ScreenState::TableView) && !self.dialog_open() {
+                    UIHandler::handle_mouse_input(self, mouse).await;
+                }
+                continue;
+            }
+
+            if let Event::Key(key) = event {
+                // Dismissing the error of a failed attempt also stops the retries.
+                if self.connect_retry.is_some() {
+                    self.cancel_connect_retry();
+                    continue;
+                }
+
+                if self.connection_error_message.is_some() {
+                    self.connection_error_message = None;
+                    continue;
+                }
+
+                if self.help_scroll.is_some() {
+                    UIHandler::handle_help_input(self, key.code).await;
+                    continue;
+                }
+
+                if self.is_quit_key(key.code, key.modifiers) {
+                    self.should_quit = true;
+                    continue;
+                }
+
+                if key.code == KeyCode::Char('?')
+                    && !self.current_screen.accepts_text()
+                    && !self.editing_database_filter
+                {
+                    self.help_scroll = Some(0);
+                    continue;
+                }
+
                 match self.current_screen {
                     ScreenState::DbTypeSelection => {
                         UIHandler::handle_db_type_selection_input(self, key.code).await;
                     }
-                    ScreenState::MessagePopup => {
-                        UIHandler::handle_message_popup_input(self).await;
+                    ScreenState::SavedConnections => {
+                        UIHandler::handle_saved_connections_input(self, key.code).await;
+                    }
+                    ScreenState::SqliteFileInput => {
+                        UIHandler::handle_sqlite_file_input(self, key.code).await;
                     }
 
                     ScreenState::ConnectionInput => {
-                        UIHandler::handle_input_event(self, key.code).await?;
+                        UIHandler::handle_input_event(self, key.code, key.modifiers).await?;
                     }
                     ScreenState::DatabaseSelection => {
                         UIHandler::handle_database_selection_input(self, key.code).await?;
                     }
                     ScreenState::TableView => {
-                        if key.code == KeyCode::Esc {
-                            return Ok(());
+                        if self.pending_destructive_query.is_some() {
+                            UIHandler::handle_confirmation_input(self, key.code, terminal).await;
+                            continue;
+                        }
+
+                        if self.editing_table_filter {
+                            UIHandler::handle_table_filter_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.editing_result_filter {
+                            UIHandler::handle_result_filter_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.insert_export.is_some() {
+                            UIHandler::handle_insert_export_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.routine_call.is_some() {
+                            UIHandler::handle_routine_call_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.completion.is_some()
+                            && UIHandler::handle_completion_input(self, key.code).await
+                        {
+                            continue;
+                        }
+
+                        if self.cell_detail.is_some() {
+                            UIHandler::handle_cell_detail_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.schema_view.is_some() {
+                            UIHandler::handle_schema_view_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.connection_info.is_some() {
+                            UIHandler::handle_connection_info_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.server_sessions.is_some() {
+                            UIHandler::handle_server_sessions_input(self, key.code).await;
+                            continue;
+                        }
+
+                        let typing = self.current_focus == FocusedWidget::SqlEditor;
+                        let action = self.keymap.action(key.code, key.modifiers, typing);
+                        if action == Some(Action::Quit) {
+                            self.should_quit = true;
+                            continue;
+                        }
+
+                        if self.database_switcher.is_some() {
+                            UIHandler::handle_database_switcher_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if self.session_switcher.is_some() {
+                            UIHandler::handle_session_switcher_input(self, key.code).await;
+                            continue;
+                        }
+
+                        if let Some(action) = action {
+                            if self.run_action(action, terminal).await {
+                                continue;
+                            }
                         }
 
                         if let FocusedWidget::SqlEditor = self.current_focus {
@@ -186,21 +731,108 @@ impl DatabaseClientUI {
                             )
                             .await;
                         } else {
-                            UIHandler::handle_table_view_input(self, key.code, terminal).await;
+                            UIHandler::handle_table_view_input(self, key.code).await;
                         }
                     }
                 }
             }
         }
+
+        Ok(())
+    }
+
+    /// Inserts bracketed-paste text into the SQL editor when it has focus, so
+    /// embedded newlines can't trigger key bindings.
+    fn handle_paste(&mut self, text: &str) {
+        match self.current_screen {
+            ScreenState::ConnectionInput if self.connect_prompt.is_none() => {
+                self.connection_input.insert_str(text)
+            }
+            ScreenState::SqliteFileInput => self
+                .connection_input
+                .file_path
+                .extend(text.chars().filter(|c| !c.is_control())),
+            ScreenState::TableView
+                if self.current_focus == FocusedWidget::SqlEditor && !self.dialog_open() =>
+            {
+                self.editor_insert_str(text)
+            }
+            _ => {}
+        }
+    }
+
+    /// `q` quits from the selection screens. Screens that take typed text
+    /// never quit on it; the table view quits with its keymap's quit key.
+    pub fn is_quit_key(&self, key: KeyCode, modifiers: KeyModifiers) -> bool {
+        key == KeyCode::Char('q')
+            && !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT)
+            && !self.current_screen.accepts_text()
+            && !self.editing_database_filter
+    }
+
+    /// Whether a popup or prompt on the table view is capturing input.
+    pub fn dialog_open(&self) -> bool {
+        self.connection_error_message.is_some()
+            || self.pending_destructive_query.is_some()
+            || self.cell_detail.is_some()
+            || self.schema_view.is_some()
+            || self.connection_info.is_some()
+            || self.completion.is_some()
+            || self.database_switcher.is_some()
+            || self.session_switcher.is_some()
+            || self.server_sessions.is_some()
+            || self.editing_table_filter
+            || self.editing_result_filter
+            || self.insert_export.is_some()
+            || self.routine_call.is_some()
+            || self.help_scroll.is_some()
     }
 }
 
+/// Restores the terminal when the UI exits, whether it returns, errors or panics.
 struct TerminalGuard;
 
+impl TerminalGuard {
+    /// Also installs a panic hook that restores the terminal before the panic
+    /// message is printed, so the message isn't lost on the alternate screen.
+    fn new() -> Self {
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            default_hook(info);
+        }));
+        TerminalGuard
+    }
+}
+
 impl Drop for TerminalGuard {
     fn drop(&mut self) {
-        let _ = disable_raw_mode();
-        let mut stdout = io::stdout();
-        let _ = execute!(stdout, LeaveAlternateScreen, DisableMouseCapture);
+        restore_terminal();
+        // Put the default hook back so later panics behave normally.
+        let _ = panic::take_hook();
     }
 }
+
+/// Switches the terminal to the raw alternate screen the UI draws on.
+pub fn enter_terminal() -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )
+}
+
+/// Gives the terminal back in the state the UI found it in.
+pub fn restore_terminal() {
+    let _ = disable_raw_mode();
+    let mut stdout = io::stdout();
+    let _ = execute!(
+        stdout,
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste,
+        Show
+    );
+}
@@ -0,0 +1,196 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::Deserialize;
+
+use super::{
+    keymap::{Action, KeyBindings, Keymap},
+    theme::{Theme, ThemeName},
+    timestamps::TimestampFormat,
+    DatabaseClientUI, DatabaseType,
+};
+
+/// User settings read from the config file, e.g.
+///
+/// ```toml
+/// theme = "high-contrast"
+/// remember_last_query = true
+/// timestamps = "utc"
+/// page_size = 200
+/// show_all_objects = true
+/// result_cache_seconds = 60
+///
+/// [connection]
+/// db_type = "postgres"
+/// host = "localhost"
+/// port = 5432
+/// user = "app"
+///
+/// [keys]
+/// quit = "ctrl+q"
+/// execute = ["f5", "ctrl+r"]
+/// ```
+#[derive(Debug, Default, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub theme: ThemeName,
+    /// Put the last query run on a database back in the editor when its
+    /// table view is shown again.
+    #[serde(default)]
+    pub remember_last_query: bool,
+    /// How timestamp columns in the results are shown.
+    #[serde(default)]
+    pub timestamps: TimestampFormat,
+    /// Rows fetched per page when previewing a table, unless `--page-size`
+    /// is given.
+    pub page_size: Option<usize>,
+    /// List views, materialized views and sequences after the tables.
+    #[serde(default)]
+    pub show_all_objects: bool,
+    /// Reuse the result of a read-only query run again within this many
+    /// seconds instead of asking the server; unset or 0 turns it off.
+    pub result_cache_seconds: Option<u64>,
+    /// Connection fields to start from.
+    #[serde(default)]
+    pub connection: ConnectionDefaults,
+    /// Keys for table view actions, replacing their defaults.
+    #[serde(default)]
+    pub keys: HashMap<Action, KeyBindings>,
+}
+
+/// Connection details from the config file. They fill only the fields that
+/// the environment left empty, and only for `db_type` when it is set.
+#[derive(Debug, Default, Deserialize)]
+pub struct ConnectionDefaults {
+    /// Database type selected when dfox starts.
+    pub db_type: Option<DatabaseType>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub database: Option<String>,
+}
+
+impl Config {
+    /// Reads the config file, or gives the defaults if there is none. A file
+    /// that can't be read or parsed is an error, saying why.
+    pub fn load() -> Result<Self, String> {
+        config_file().map_or(Ok(Config::default()), |path| Config::read(&path))
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(err) => return Err(format!("Couldn't read {}: {}", path.display(), err)),
+        };
+        toml::from_str(&content)
+            .map_err(|err| format!("Ignoring {}, which has an error: {}", path.display(), err))
+    }
+}
+
+impl DatabaseClientUI {
+    /// Takes the settings of `config`. Command-line options are applied
+    /// after this, so they win.
+    pub fn apply_config(&mut self, config: Config) {
+        self.theme = Theme::named(config.theme);
+        self.keymap = Keymap::new(config.keys);
+        self.remember_last_query = config.remember_last_query;
+        self.show_objects = config.show_all_objects;
+        self.result_cache.ttl = config
+            .result_cache_seconds
+            .filter(|seconds| *seconds > 0)
+            .map(Duration::from_secs);
+        self.timestamp_columns.format = config.timestamps;
+        if let Some(page_size) = config.page_size {
+            self.page_size = page_size.max(1);
+        }
+        if let Some(db_type) = config.connection.db_type {
+            self.selected_db_type = DatabaseType::ALL
+                .iter()
+                .position(|candidate| *candidate == db_type)
+                .unwrap_or(0);
+        }
+        self.connection_defaults = config.connection;
+    }
+}
+
+/// `<config dir>/dfox/config.toml`, e.g. `~/.config/dfox/config.toml` on Linux.
+fn config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("dfox").join("config.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::components::ConnectionInput;
+
+    #[test]
+    fn test_read_reports_a_broken_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        assert!(Config::read(&path).is_ok());
+
+        fs::write(&path, "theme = \"neon\"").unwrap();
+        let err = Config::read(&path).unwrap_err();
+        assert!(err.starts_with("Ignoring "), "{err}");
+        assert!(err.contains("neon"), "{err}");
+    }
+
+    #[test]
+    fn test_theme_names() {
+        let config: Config = toml::from_str("theme = \"high-contrast\"").unwrap();
+        assert_eq!(config.theme, ThemeName::HighContrast);
+
+        let config: Config = toml::from_str("").unwrap();
+        assert_eq!(config.theme, ThemeName::Dark);
+
+        assert!(toml::from_str::<Config>("theme = \"neon\"").is_err());
+        assert!(!config.remember_last_query);
+        assert_eq!(config.timestamps, TimestampFormat::Raw);
+
+        let config: Config = toml::from_str("timestamps = \"local\"").unwrap();
+        assert_eq!(config.timestamps, TimestampFormat::Local);
+    }
+
+    #[test]
+    fn test_connection_section() {
+        let config: Config = toml::from_str(
+            "page_size = 250\n[connection]\ndb_type = \"mysql\"\nhost = \"db\"\nport = 3307",
+        )
+        .unwrap();
+        assert_eq!(config.page_size, Some(250));
+        assert_eq!(config.connection.db_type, Some(DatabaseType::MySQL));
+        assert_eq!(config.connection.port, Some(3307));
+
+        let mut ui = DatabaseClientUI::new(std::sync::Arc::new(dfox_core::DbManager::new()));
+        ui.apply_config(config);
+        assert_eq!(ui.page_size, 250);
+        assert_eq!(
+            DatabaseType::from_index(ui.selected_db_type),
+            DatabaseType::MySQL
+        );
+
+        ui.connection_input.hostname = "from-env".to_string();
+        ui.connection_input
+            .apply_defaults(&ui.connection_defaults, DatabaseType::MySQL);
+        assert_eq!(ui.connection_input.hostname, "from-env");
+        assert_eq!(ui.connection_input.port, "3307");
+
+        let mut input = ConnectionInput::new();
+        input.apply_defaults(&ui.connection_defaults, DatabaseType::Postgres);
+        assert!(input.port.is_empty());
+    }
+
+    #[test]
+    fn test_keys_section() {
+        let config: Config = toml::from_str("[keys]\nquit = \"ctrl+q\"").unwrap();
+        assert!(config.keys.contains_key(&Action::Quit));
+
+        assert!(toml::from_str::<Config>("[keys]\nquit = \"hyper+q\"").is_err());
+        assert!(toml::from_str::<Config>("[keys]\nfly = \"f\"").is_err());
+    }
+}
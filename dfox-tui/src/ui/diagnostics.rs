@@ -0,0 +1,55 @@
+use super::DatabaseClientUI;
+
+/// The connection diagnostics screen, while open.
+pub struct ConnectionInfoView {
+    pub lines: Vec<String>,
+    /// First line shown.
+    pub scroll: usize,
+}
+
+impl DatabaseClientUI {
+    /// Asks the server about the connection and shows the answer full screen.
+    pub async fn open_connection_info(&mut self) {
+        match self.fetch_connection_info().await {
+            Ok(info) => {
+                self.connection_info = Some(ConnectionInfoView {
+                    lines: info.lines(),
+                    scroll: 0,
+                });
+            }
+            Err(err) => {
+                self.sql_query_error = Some(format!("Error describing the connection: {}", err));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyCode;
+
+    use crate::ui::{sqlite_ui, UIHandler};
+
+    #[tokio::test]
+    async fn test_sqlite_connection_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("x.db");
+        let mut ui = sqlite_ui(&path).await;
+
+        ui.open_connection_info().await;
+        let view = ui.connection_info.as_ref().unwrap();
+        assert!(view.lines[0].starts_with("Server version: SQLite 3."));
+        assert!(view.lines[2].ends_with("x.db"));
+        assert_eq!(view.lines[3], "Encoding:       UTF-8");
+        assert!(view
+            .lines
+            .iter()
+            .any(|line| line.starts_with("  foreign_keys ")));
+
+        UIHandler::handle_connection_info_input(&mut ui, KeyCode::End).await;
+        let last_line = ui.connection_info.as_ref().unwrap().lines.len() - 1;
+        assert_eq!(ui.connection_info.as_ref().unwrap().scroll, last_line);
+        UIHandler::handle_connection_info_input(&mut ui, KeyCode::Esc).await;
+        assert!(ui.connection_info.is_none());
+    }
+}
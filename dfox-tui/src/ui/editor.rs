@@ -0,0 +1,650 @@
+use std::{
+    collections::VecDeque,
+    env, fs,
+    io::{self, Write},
+    ops::Range,
+    path::Path,
+    process::Command,
+};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use ratatui::{backend::CrosstermBackend, Terminal};
+
+use super::{
+    components::{enter_terminal, restore_terminal},
+    DatabaseClientUI,
+};
+
+/// How many earlier states of the SQL editor Ctrl+Z can go back to.
+const UNDO_LIMIT: usize = 100;
+
+/// The SQL editor's text and cursor at one point of its history.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EditorState {
+    content: String,
+    cursor: usize,
+}
+
+/// Kinds of edit that are undone together when several happen in a row,
+/// so Ctrl+Z takes back a typed word rather than its last letter.
+#[derive(Clone, Copy, PartialEq)]
+pub enum EditGroup {
+    Typing,
+    Deleting,
+}
+
+/// Undo and redo stacks of the SQL editor, holding at most [`UNDO_LIMIT`]
+/// states to go back to.
+#[derive(Default)]
+pub struct EditHistory {
+    undo: VecDeque<EditorState>,
+    redo: Vec<EditorState>,
+    last_group: Option<EditGroup>,
+}
+
+/// The run of edits that `key` continues: typed characters up to the next
+/// space or newline, or consecutive Backspace / Delete presses.
+pub fn edit_group(key: KeyCode, modifiers: KeyModifiers) -> Option<EditGroup> {
+    match key {
+        KeyCode::Char(c) if !c.is_whitespace() && !modifiers.contains(KeyModifiers::CONTROL) => {
+            Some(EditGroup::Typing)
+        }
+        KeyCode::Backspace | KeyCode::Delete => Some(EditGroup::Deleting),
+        _ => None,
+    }
+}
+
+/// The scroll `offset` along one axis of a view `size` cells long, moved as
+/// little as possible so `position` is in view.
+pub fn follow_cursor(offset: u16, position: u16, size: u16) -> u16 {
+    if position < offset {
+        position
+    } else if position >= offset.saturating_add(size) {
+        position.saturating_sub(size.saturating_sub(1))
+    } else {
+        offset
+    }
+}
+
+impl EditorState {
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+}
+
+impl EditHistory {
+    /// Remembers `before`, the state an edit started from, unless the edit
+    /// continues a run of the same `group`. Any undone states are dropped.
+    pub fn record(&mut self, before: EditorState, group: Option<EditGroup>) {
+        self.redo.clear();
+        if group.is_some() && group == self.last_group {
+            return;
+        }
+        self.last_group = group;
+        self.undo.push_back(before);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+    }
+
+    /// Ends the current run of edits, e.g. when the cursor moves away.
+    pub fn break_group(&mut self) {
+        self.last_group = None;
+    }
+}
+
+/// Cursor-aware editing of `sql_editor_content`.
+///
+/// `sql_editor_cursor` is a byte offset that always sits on a char boundary.
+impl DatabaseClientUI {
+    pub fn editor_insert_char(&mut self, c: char) {
+        self.editor_delete_selection();
+        self.sql_editor_content.insert(self.sql_editor_cursor, c);
+        self.sql_editor_cursor += c.len_utf8();
+    }
+
+    /// Inserts pasted text at the cursor, normalizing line endings to `\n`.
+    /// The paste is an edit of its own, undone apart from any typing.
+    pub fn editor_insert_str(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        self.editor_checkpoint();
+        self.editor_delete_selection();
+        self.sql_editor_content
+            .insert_str(self.sql_editor_cursor, &text);
+        self.sql_editor_cursor += text.len();
+    }
+
+    pub fn editor_backspace(&mut self) {
+        if self.editor_delete_selection() {
+            return;
+        }
+        if let Some(prev) = self.previous_char_boundary() {
+            self.sql_editor_content.remove(prev);
+            self.sql_editor_cursor = prev;
+        }
+    }
+
+    pub fn editor_delete(&mut self) {
+        if self.editor_delete_selection() {
+            return;
+        }
+        if self.sql_editor_cursor < self.sql_editor_content.len() {
+            self.sql_editor_content.remove(self.sql_editor_cursor);
+        }
+    }
+
+    /// Deletes from the start of the current line up to the cursor (Ctrl+U).
+    pub fn editor_delete_to_line_start(&mut self) {
+        if self.editor_delete_selection() {
+            return;
+        }
+        let end = self.sql_editor_cursor;
+        self.editor_move_home();
+        if self.sql_editor_cursor == end {
+            self.editor_backspace();
+        } else {
+            self.sql_editor_content
+                .replace_range(self.sql_editor_cursor..end, "");
+        }
+    }
+
+    /// Deletes the word before the cursor and any whitespace after it
+    /// (Ctrl+W).
+    pub fn editor_delete_word_before(&mut self) {
+        if self.editor_delete_selection() {
+            return;
+        }
+        let end = self.sql_editor_cursor;
+        let before = &self.sql_editor_content[..end];
+        let trimmed = before.trim_end();
+        let start = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| c.is_whitespace())
+            .map_or(0, |(idx, c)| idx + c.len_utf8());
+        self.sql_editor_content.replace_range(start..end, "");
+        self.sql_editor_cursor = start;
+    }
+
+    pub fn editor_move_left(&mut self) {
+        if let Some(prev) = self.previous_char_boundary() {
+            self.sql_editor_cursor = prev;
+        }
+    }
+
+    pub fn editor_move_right(&mut self) {
+        if let Some(c) = self.sql_editor_content[self.sql_editor_cursor..]
+            .chars()
+            .next()
+        {
+            self.sql_editor_cursor += c.len_utf8();
+        }
+    }
+
+    /// Moves the cursor to the same column on the previous line, or to the
+    /// end of that line if it is shorter.
+    pub fn editor_move_up(&mut self) {
+        let (column, _) = self.editor_cursor_position();
+        self.editor_move_home();
+        if self.sql_editor_cursor > 0 {
+            self.sql_editor_cursor -= 1;
+            self.editor_move_home();
+            self.editor_move_to_column(column);
+        }
+    }
+
+    /// Moves the cursor to the same column on the next line, or to the end
+    /// of that line if it is shorter.
+    pub fn editor_move_down(&mut self) {
+        let (column, _) = self.editor_cursor_position();
+        self.editor_move_end();
+        if self.sql_editor_cursor < self.sql_editor_content.len() {
+            self.sql_editor_cursor += 1;
+            self.editor_move_to_column(column);
+        }
+    }
+
+    /// Starts a selection at the cursor unless one is already being extended.
+    pub fn editor_start_selection(&mut self) {
+        self.sql_editor_selection
+            .get_or_insert(self.sql_editor_cursor);
+    }
+
+    /// Byte range of the selected text, if any is selected.
+    pub fn editor_selection(&self) -> Option<Range<usize>> {
+        let anchor = self.sql_editor_selection?;
+        let range = anchor.min(self.sql_editor_cursor)..anchor.max(self.sql_editor_cursor);
+        (!range.is_empty()).then_some(range)
+    }
+
+    /// The selected text, or the whole editor when nothing is selected.
+    pub fn editor_query(&self) -> &str {
+        match self.editor_selection() {
+            Some(range) => &self.sql_editor_content[range],
+            None => &self.sql_editor_content,
+        }
+    }
+
+    /// Moves the cursor to the start of the current line.
+    pub fn editor_move_home(&mut self) {
+        self.sql_editor_cursor = self.sql_editor_content[..self.sql_editor_cursor]
+            .rfind('\n')
+            .map_or(0, |idx| idx + 1);
+    }
+
+    /// Moves the cursor to the end of the current line.
+    pub fn editor_move_end(&mut self) {
+        self.sql_editor_cursor = self.sql_editor_content[self.sql_editor_cursor..]
+            .find('\n')
+            .map_or(self.sql_editor_content.len(), |idx| {
+                self.sql_editor_cursor + idx
+            });
+    }
+
+    /// Recalls the previous query from history while the cursor is on the first line.
+    pub fn editor_history_previous(&mut self) {
+        if self.editor_cursor_position().1 != 0 {
+            return;
+        }
+        if let Some(query) = self.query_history.previous(&self.sql_editor_content) {
+            self.sql_editor_content = query.to_string();
+            self.sql_editor_cursor = self.sql_editor_content.len();
+        }
+    }
+
+    /// Recalls the next query from history while the cursor is on the last line.
+    pub fn editor_history_next(&mut self) {
+        if self.sql_editor_content[self.sql_editor_cursor..].contains('\n') {
+            return;
+        }
+        if let Some(query) = self.query_history.next() {
+            self.sql_editor_content = query.to_string();
+            self.sql_editor_cursor = self.sql_editor_content.len();
+        }
+    }
+
+    /// Puts the last script run on the connected database back in the
+    /// editor, if that is turned on and the editor is empty.
+    pub fn restore_last_query(&mut self) {
+        if !self.remember_last_query || !self.sql_editor_content.is_empty() {
+            return;
+        }
+        if let Some(query) = self.last_queries.get(&self.connection_label()).cloned() {
+            self.editor_checkpoint();
+            self.sql_editor_cursor = query.len();
+            self.sql_editor_content = query;
+            self.sql_editor_selection = None;
+        }
+    }
+
+    pub fn editor_state(&self) -> EditorState {
+        EditorState {
+            content: self.sql_editor_content.clone(),
+            cursor: self.sql_editor_cursor,
+        }
+    }
+
+    /// Makes the current text an undo step of its own, before it is
+    /// replaced other than by typing (completion, INSERT export, `$EDITOR`).
+    pub fn editor_checkpoint(&mut self) {
+        self.edit_history.record(self.editor_state(), None);
+    }
+
+    /// Goes back to the text before the last edit (Ctrl+Z).
+    pub fn editor_undo(&mut self) {
+        if let Some(state) = self.edit_history.undo.pop_back() {
+            let current = self.editor_state();
+            self.edit_history.redo.push(current);
+            self.restore_editor_state(state);
+        }
+    }
+
+    /// Reapplies the last undone edit (Ctrl+Y).
+    pub fn editor_redo(&mut self) {
+        if let Some(state) = self.edit_history.redo.pop() {
+            let current = self.editor_state();
+            self.edit_history.undo.push_back(current);
+            self.restore_editor_state(state);
+        }
+    }
+
+    fn restore_editor_state(&mut self, state: EditorState) {
+        self.sql_editor_content = state.content;
+        self.sql_editor_cursor = state.cursor;
+        self.sql_editor_selection = None;
+        self.edit_history.break_group();
+    }
+
+    pub fn clear_sql_editor(&mut self) {
+        self.sql_editor_content.clear();
+        self.sql_editor_cursor = 0;
+        self.sql_editor_selection = None;
+    }
+
+    /// Returns the cursor as a (column, line) pair, counted in chars.
+    pub fn editor_cursor_position(&self) -> (u16, u16) {
+        let before_cursor = &self.sql_editor_content[..self.sql_editor_cursor];
+        let line = before_cursor.matches('\n').count();
+        let column = before_cursor
+            .rsplit('\n')
+            .next()
+            .map_or(0, |current_line| current_line.chars().count());
+        (column as u16, line as u16)
+    }
+
+    /// Removes the selected text, returning whether there was any.
+    fn editor_delete_selection(&mut self) -> bool {
+        let selection = self.editor_selection();
+        self.sql_editor_selection = None;
+        match selection {
+            Some(range) => {
+                self.sql_editor_cursor = range.start;
+                self.sql_editor_content.replace_range(range, "");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves right from the start of a line by up to `column` chars.
+    fn editor_move_to_column(&mut self, column: u16) {
+        for _ in 0..column {
+            if self.sql_editor_content[self.sql_editor_cursor..].starts_with('\n') {
+                break;
+            }
+            self.editor_move_right();
+        }
+    }
+
+    fn previous_char_boundary(&self) -> Option<usize> {
+        self.sql_editor_content[..self.sql_editor_cursor]
+            .char_indices()
+            .next_back()
+            .map(|(idx, _)| idx)
+    }
+
+    /// Opens the editor's text in `$VISUAL` or `$EDITOR` (`vi` if neither is
+    /// set) and loads it back once that editor exits. The TUI gives up the
+    /// terminal meanwhile.
+    pub fn edit_in_external_editor(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) {
+        let editor = ["VISUAL", "EDITOR"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|editor| !editor.trim().is_empty())
+            .unwrap_or_else(|| "vi".to_string());
+        // A new file only this user can read, removed when it is dropped.
+        let file = tempfile::Builder::new()
+            .prefix("dfox-query-")
+            .suffix(".sql")
+            .tempfile()
+            .and_then(|mut file| {
+                file.write_all(self.sql_editor_content.as_bytes())?;
+                file.flush()?;
+                Ok(file)
+            });
+        let file = match file {
+            Ok(file) => file,
+            Err(err) => {
+                self.status_message = Some(format!(
+                    "Query left unchanged; couldn't write it for the editor: {}",
+                    err
+                ));
+                return;
+            }
+        };
+
+        restore_terminal();
+        let status = editor_command(&editor, file.path()).status();
+        // Take the terminal back however the editor ended, before reading
+        // its result, so a failure here doesn't lose that.
+        let reentered = enter_terminal().and_then(|()| terminal.clear());
+        let edited = match status {
+            // Read by path, since editors often save by replacing the file.
+            Ok(status) if status.success() => fs::read_to_string(file.path()),
+            Ok(status) => Err(io::Error::other(format!(
+                "{} exited with {}",
+                editor, status
+            ))),
+            Err(err) => Err(err),
+        };
+
+        match edited {
+            Ok(text) => {
+                // Editors end the file with a newline the query didn't have.
+                let text = text.strip_suffix('\n').unwrap_or(&text);
+                self.editor_checkpoint();
+                self.sql_editor_content = text.strip_suffix('\r').unwrap_or(text).to_string();
+                self.sql_editor_cursor = self.sql_editor_content.len();
+                self.sql_editor_selection = None;
+            }
+            Err(err) => {
+                self.status_message =
+                    Some(format!("Query left unchanged; the editor failed: {}", err));
+            }
+        }
+        if let Err(err) = reentered {
+            self.status_message = Some(format!(
+                "Couldn't restore the screen after the editor: {}",
+                err
+            ));
+        }
+    }
+}
+
+/// `editor` run on `path`. The variable may carry arguments, as in
+/// `code --wait`.
+fn editor_command(editor: &str, path: &Path) -> Command {
+    let mut words = editor.split_whitespace();
+    let mut command = Command::new(words.next().unwrap_or("vi"));
+    command.args(words).arg(path);
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dfox_core::DbManager;
+
+    use super::*;
+
+    #[test]
+    fn test_follow_cursor_scrolls_only_when_it_leaves_the_view() {
+        assert_eq!(follow_cursor(0, 3, 10), 0);
+        assert_eq!(follow_cursor(0, 10, 10), 1);
+        assert_eq!(follow_cursor(5, 30, 10), 21);
+        assert_eq!(follow_cursor(21, 25, 10), 21);
+        assert_eq!(follow_cursor(21, 4, 10), 4);
+        assert_eq!(follow_cursor(0, 7, 0), 7);
+    }
+
+    fn editor(content: &str) -> DatabaseClientUI {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.sql_editor_content = content.to_string();
+        ui.sql_editor_cursor = content.len();
+        ui
+    }
+
+    #[test]
+    fn test_insert_in_middle() {
+        let mut ui = editor("SELCT 1");
+        ui.editor_move_home();
+        for _ in 0..3 {
+            ui.editor_move_right();
+        }
+        ui.editor_insert_char('E');
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        assert_eq!(ui.sql_editor_cursor, 4);
+    }
+
+    #[test]
+    fn test_insert_pasted_text() {
+        let mut ui = editor("SELECT 1");
+        ui.editor_move_home();
+        ui.editor_insert_str("-- pasted\r\nSELECT 2;\r");
+        assert_eq!(ui.sql_editor_content, "-- pasted\nSELECT 2;\nSELECT 1");
+        assert_eq!(ui.editor_cursor_position(), (0, 2));
+    }
+
+    #[test]
+    fn test_delete_to_line_start() {
+        let mut ui = editor("SELECT 1;\nSELECT 2;");
+        ui.editor_move_left();
+        ui.editor_delete_to_line_start();
+        assert_eq!(ui.sql_editor_content, "SELECT 1;\n;");
+        assert_eq!(ui.editor_cursor_position(), (0, 1));
+
+        ui.editor_delete_to_line_start();
+        assert_eq!(ui.sql_editor_content, "SELECT 1;;");
+        assert_eq!(ui.sql_editor_cursor, 9);
+    }
+
+    #[test]
+    fn test_delete_word_before() {
+        let mut ui = editor("SELECT name  FROM\tusérs  ");
+        ui.editor_delete_word_before();
+        assert_eq!(ui.sql_editor_content, "SELECT name  FROM\t");
+        ui.editor_delete_word_before();
+        assert_eq!(ui.sql_editor_content, "SELECT name  ");
+        ui.editor_delete_word_before();
+        ui.editor_delete_word_before();
+        assert_eq!(ui.sql_editor_content, "");
+        assert_eq!(ui.sql_editor_cursor, 0);
+        ui.editor_delete_word_before();
+        assert_eq!(ui.sql_editor_content, "");
+    }
+
+    #[test]
+    fn test_backspace_multibyte() {
+        let mut ui = editor("SELECT 'é'");
+        ui.editor_move_left();
+        ui.editor_backspace();
+        assert_eq!(ui.sql_editor_content, "SELECT ''");
+    }
+
+    #[test]
+    fn test_selection_is_run_and_replaced_by_typing() {
+        let mut ui = editor("SELECT 1;\nSELECT 2;");
+        ui.editor_start_selection();
+        ui.editor_move_home();
+        assert_eq!(ui.editor_query(), "SELECT 2;");
+
+        ui.editor_insert_char('x');
+        assert_eq!(ui.sql_editor_content, "SELECT 1;\nx");
+        assert_eq!(ui.editor_selection(), None);
+        assert_eq!(ui.editor_query(), "SELECT 1;\nx");
+    }
+
+    #[test]
+    fn test_move_up_and_down_keep_column() {
+        let mut ui = editor("SELECT *\nFROM users");
+        ui.editor_move_up();
+        assert_eq!(ui.editor_cursor_position(), (8, 0));
+        ui.editor_move_left();
+        ui.editor_move_down();
+        assert_eq!(ui.editor_cursor_position(), (7, 1));
+        ui.editor_move_down();
+        assert_eq!(ui.editor_cursor_position(), (10, 1));
+    }
+
+    /// Presses `keys` in turn, recording each edit like the SQL
+    /// editor's key handler does.
+    fn type_keys(ui: &mut DatabaseClientUI, keys: &[KeyCode]) {
+        for &key in keys {
+            let before = ui.editor_state();
+            match key {
+                KeyCode::Char(c) => ui.editor_insert_char(c),
+                KeyCode::Backspace => ui.editor_backspace(),
+                _ => unreachable!(),
+            }
+            ui.edit_history
+                .record(before, edit_group(key, KeyModifiers::NONE));
+        }
+    }
+
+    #[test]
+    fn test_undo_takes_back_a_word_at_a_time() {
+        let mut ui = editor("");
+        let keys: Vec<_> = "SELECT 1".chars().map(KeyCode::Char).collect();
+        type_keys(&mut ui, &keys);
+        type_keys(&mut ui, &[KeyCode::Backspace, KeyCode::Backspace]);
+        assert_eq!(ui.sql_editor_content, "SELECT");
+
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "SELECT ");
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "SELECT");
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "");
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "");
+
+        ui.editor_redo();
+        ui.editor_redo();
+        assert_eq!(ui.sql_editor_content, "SELECT ");
+        assert_eq!(ui.sql_editor_cursor, 7);
+
+        // A new edit drops what was undone.
+        type_keys(&mut ui, &[KeyCode::Char('*')]);
+        ui.editor_redo();
+        assert_eq!(ui.sql_editor_content, "SELECT *");
+    }
+
+    #[test]
+    fn test_paste_is_undone_on_its_own() {
+        let mut ui = editor("");
+        type_keys(&mut ui, &[KeyCode::Char('S'), KeyCode::Char('E')]);
+        ui.editor_undo();
+        ui.editor_insert_str("SELECT");
+        // The paste dropped what was undone before it.
+        ui.editor_redo();
+        assert_eq!(ui.sql_editor_content, "SELECT");
+
+        type_keys(&mut ui, &[KeyCode::Char('1')]);
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "SELECT");
+        ui.editor_undo();
+        assert_eq!(ui.sql_editor_content, "");
+        ui.editor_redo();
+        assert_eq!(ui.sql_editor_content, "SELECT");
+    }
+
+    #[test]
+    fn test_undo_history_is_bounded() {
+        let mut ui = editor("");
+        for i in 0..UNDO_LIMIT + 10 {
+            ui.editor_checkpoint();
+            ui.sql_editor_content = i.to_string();
+        }
+        let mut undone = 0;
+        while ui.edit_history.undo.pop_back().is_some() {
+            undone += 1;
+        }
+        assert_eq!(undone, UNDO_LIMIT);
+    }
+
+    #[test]
+    fn test_editor_command_keeps_its_arguments() {
+        let command = editor_command("code --wait", Path::new("/tmp/q.sql"));
+        assert_eq!(command.get_program(), "code");
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["--wait", "/tmp/q.sql"]);
+    }
+
+    #[test]
+    fn test_home_end_on_second_line() {
+        let mut ui = editor("SELECT *\nFROM users");
+        ui.editor_move_home();
+        assert_eq!(ui.editor_cursor_position(), (0, 1));
+        ui.editor_move_left();
+        ui.editor_move_home();
+        assert_eq!(ui.editor_cursor_position(), (0, 0));
+        ui.editor_move_end();
+        assert_eq!(ui.editor_cursor_position(), (8, 0));
+    }
+}
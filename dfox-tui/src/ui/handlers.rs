@@ -1,287 +1,423 @@
 use std::{
-    io::{self, stdout},
-    process,
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
-use crossterm::{
-    event::{KeyCode, KeyModifiers},
-    execute, terminal,
+use crossterm::event::{
+    self, Event, KeyCode, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
 };
-use ratatui::{prelude::CrosstermBackend, Terminal};
+use dfox_core::{errors::DbError, export, sql};
+use ratatui::{layout::Position, prelude::CrosstermBackend, Terminal};
+use serde_json::Value;
+use tokio::sync::mpsc;
 
-use crate::db::{MySQLUI, PostgresUI};
+use crate::db::{list_databases, StatementOutcome, LOST_WRITE_MESSAGE};
 
 use super::{
-    components::{FocusedWidget, InputField, ScreenState},
-    DatabaseClientUI, UIHandler, UIRenderer,
+    clipboard::copy_to_clipboard,
+    components::{FocusedWidget, FooterAggregate, InputField, ScreenState},
+    connection::{retry_delay, ConnectPrompt, ConnectRetry},
+    editor::edit_group,
+    keymap::Action,
+    query_task::{run_script, PendingFetch, QueryEvent},
+    result_cache::CachedResult,
+    results::{
+        cell_detail_text, cell_text, format_elapsed, query_summary, row_matches, sort_rows,
+        spinner_frame, InsertExport, InsertTarget, SortOrder, SPINNER_INTERVAL,
+    },
+    saved_connections::SavedConnection,
+    tables::{fuzzy_match, preview_query, schema_view_lines, TablePreview},
+    DatabaseClientUI, DatabaseType, UIHandler, UIRenderer,
 };
 
-impl UIHandler for DatabaseClientUI {
-    async fn handle_message_popup_input(&mut self) {
-        self.current_screen = ScreenState::DbTypeSelection
-    }
+/// Header of the single column an EXPLAIN plan is shown in.
+const QUERY_PLAN_COLUMN: &str = "QUERY PLAN";
 
+impl UIHandler for DatabaseClientUI {
     async fn handle_db_type_selection_input(&mut self, key: KeyCode) {
+        let key = vim_motion(key);
         match key {
             KeyCode::Up => {
-                if self.selected_db_type > 0 {
-                    self.selected_db_type -= 1;
-                }
+                self.selected_db_type = self.selected_db_type.saturating_sub(1);
             }
             KeyCode::Down => {
-                if self.selected_db_type < 2 {
-                    self.selected_db_type += 1;
-                }
+                self.selected_db_type = (self.selected_db_type + 1).min(2);
+            }
+            // Only while opening another connection, which this abandons.
+            KeyCode::Esc => {
+                self.abandon_new_session();
             }
             KeyCode::Enter => {
-                if self.selected_db_type == 2 {
-                    self.current_screen = ScreenState::MessagePopup;
+                self.db_type = DatabaseType::from_index(self.selected_db_type);
+                self.connection_input
+                    .apply_env(&self.connection_env, self.db_type);
+                self.connection_input
+                    .apply_defaults(&self.connection_defaults, self.db_type);
+                if self.saved_connections.for_type(self.db_type).is_empty() {
+                    self.current_screen = if self.db_type == DatabaseType::SQLite {
+                        ScreenState::SqliteFileInput
+                    } else {
+                        ScreenState::ConnectionInput
+                    };
                 } else {
-                    self.current_screen = ScreenState::ConnectionInput;
+                    self.selected_saved_connection = 0;
+                    self.current_screen = ScreenState::SavedConnections;
                 }
             }
-            KeyCode::Char('q') => {
-                terminal::disable_raw_mode().unwrap();
-                execute!(stdout(), terminal::LeaveAlternateScreen).unwrap();
-                process::exit(0);
-            }
             _ => {}
         }
     }
 
-    async fn handle_input_event(&mut self, key: KeyCode) -> io::Result<()> {
-        if let Some(_error_message) = &self.connection_error_message {
-            match key {
-                KeyCode::Enter | KeyCode::Esc => {
-                    self.connection_error_message = None;
+    async fn handle_saved_connections_input(&mut self, key: KeyCode) {
+        let key = vim_motion(key);
+        let saved = self.saved_connections.for_type(self.db_type);
+        // The extra last entry is "New connection".
+        let entry_count = saved.len() + 1;
+        let selected = saved.get(self.selected_saved_connection).cloned().cloned();
+
+        match key {
+            KeyCode::Up if self.selected_saved_connection > 0 => {
+                self.selected_saved_connection -= 1;
+            }
+            KeyCode::Down if self.selected_saved_connection + 1 < entry_count => {
+                self.selected_saved_connection += 1;
+            }
+            KeyCode::Enter if self.db_type == DatabaseType::SQLite => match selected {
+                Some(connection) => {
+                    self.connection_input.file_path = connection.file_path;
+                    self.current_screen = ScreenState::SqliteFileInput;
+                    self.connect_from_input().await;
                 }
-                _ => {}
+                None => self.current_screen = ScreenState::SqliteFileInput,
+            },
+            KeyCode::Enter => {
+                let input = &mut self.connection_input;
+                if let Some(connection) = selected {
+                    input.username = connection.username;
+                    input.hostname = connection.hostname;
+                    input.port = connection.port;
+                    input.database = connection.database;
+                    input.ssl_mode = connection.ssl_mode;
+                    input.ssl_root_cert = connection.ssl_root_cert;
+                    input.current_field = InputField::Username;
+                    input.current_field = input.next_field();
+                }
+                self.current_screen = ScreenState::ConnectionInput;
             }
-        } else {
-            match key {
-                KeyCode::Esc => {
-                    self.current_screen = ScreenState::DbTypeSelection;
+            KeyCode::Delete => {
+                if let Some(connection) = selected {
+                    self.saved_connections.remove(&connection);
+                    let _ = self.saved_connections.save();
+                    self.selected_saved_connection = self
+                        .selected_saved_connection
+                        .min(self.saved_connections.for_type(self.db_type).len());
                 }
-                KeyCode::Up => {
-                    self.connection_input.current_field = match self.connection_input.current_field
-                    {
-                        InputField::Port => InputField::Hostname,
-                        InputField::Hostname => InputField::Password,
-                        InputField::Password => InputField::Username,
-                        InputField::Username => InputField::Username,
-                    };
+            }
+            KeyCode::Esc => {
+                self.current_screen = ScreenState::DbTypeSelection;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_sqlite_file_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Esc => {
+                self.current_screen = ScreenState::DbTypeSelection;
+            }
+            KeyCode::Tab => {
+                self.connection_input.file_path = complete_path(&self.connection_input.file_path);
+            }
+            KeyCode::Char(c) => self.connection_input.file_path.push(c),
+            KeyCode::Backspace => {
+                self.connection_input.file_path.pop();
+            }
+            KeyCode::Enter if !self.connection_input.file_path.is_empty() => {
+                self.connect_from_input().await;
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_input_event(
+        &mut self,
+        key: KeyCode,
+        modifiers: KeyModifiers,
+    ) -> io::Result<()> {
+        if self.connect_prompt.is_some() {
+            match key {
+                KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    self.connect_prompt = Some(ConnectPrompt::Connecting);
                 }
-                KeyCode::Down => {
-                    self.connection_input.current_field = match self.connection_input.current_field
-                    {
-                        InputField::Username => InputField::Password,
-                        InputField::Password => InputField::Hostname,
-                        InputField::Hostname => InputField::Port,
-                        InputField::Port => InputField::Port,
-                    };
+                KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('N') => {
+                    self.connect_prompt = None;
                 }
-                _ => match self.connection_input.current_field {
-                    InputField::Username => match key {
-                        KeyCode::Char(c) => self.connection_input.username.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.username.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Password;
-                        }
-                        _ => {}
-                    },
-                    InputField::Password => match key {
-                        KeyCode::Char(c) => self.connection_input.password.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.password.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Hostname;
-                        }
-                        _ => {}
-                    },
-                    InputField::Hostname => match key {
-                        KeyCode::Char(c) => self.connection_input.hostname.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.hostname.pop();
-                        }
-                        KeyCode::Enter => {
-                            self.connection_input.current_field = InputField::Port;
-                        }
-                        _ => {}
-                    },
-                    InputField::Port => match key {
-                        KeyCode::Char(c) => self.connection_input.port.push(c),
-                        KeyCode::Backspace => {
-                            self.connection_input.port.pop();
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match key {
+            KeyCode::Char('r') if modifiers.contains(KeyModifiers::CONTROL) => {
+                self.connection_input.show_password = !self.connection_input.show_password;
+            }
+            KeyCode::Esc => {
+                self.connection_input.show_password = false;
+                self.current_screen = ScreenState::DbTypeSelection;
+            }
+            KeyCode::Up => {
+                self.connection_input.current_field = self.connection_input.previous_field();
+            }
+            KeyCode::Down => {
+                self.connection_input.current_field = self.connection_input.next_field();
+            }
+            _ => match self.connection_input.current_field {
+                InputField::Url => match key {
+                    KeyCode::Char(c) => {
+                        self.connection_input.url.push(c);
+                        self.connection_input.fill_from_url();
+                    }
+                    KeyCode::Backspace => {
+                        self.connection_input.url.pop();
+                        self.connection_input.fill_from_url();
+                    }
+                    KeyCode::Enter if !self.connection_input.url.is_empty() => {
+                        self.connect_prompt = Some(ConnectPrompt::Confirm)
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = self.connection_input.next_field();
+                    }
+                    _ => {}
+                },
+                InputField::Username => match key {
+                    KeyCode::Char(c) => self.connection_input.username.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.username.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = self.connection_input.next_field();
+                    }
+                    _ => {}
+                },
+                InputField::Password => match key {
+                    KeyCode::Char(c) => self.connection_input.password.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.password.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = InputField::Hostname;
+                    }
+                    _ => {}
+                },
+                InputField::Hostname => match key {
+                    KeyCode::Char(c) => self.connection_input.hostname.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.hostname.pop();
+                    }
+                    KeyCode::Enter => {
+                        self.connection_input.current_field = InputField::Port;
+                    }
+                    _ => {}
+                },
+                InputField::Port => match key {
+                    KeyCode::Char(c) => self.connection_input.port.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.port.pop();
+                    }
+                    KeyCode::Tab if self.connection_input.port.is_empty() => {
+                        self.connection_input.port = self.db_type.default_port().to_string();
+                    }
+                    KeyCode::Enter => {
+                        if self.connection_input.port.is_empty() {
+                            self.connection_input.port = self.db_type.default_port().to_string();
                         }
-                        KeyCode::Enter => match self.selected_db_type {
-                            0 => {
-                                let result = PostgresUI::connect_to_default_db(self).await;
-                                if result.is_ok() {
-                                    self.current_screen = ScreenState::DatabaseSelection;
-                                }
-                            }
-                            1 => {
-                                let result = MySQLUI::connect_to_default_db(self).await;
-                                if result.is_ok() {
-                                    self.current_screen = ScreenState::DatabaseSelection;
-                                }
-                            }
-                            _ => {}
-                        },
-                        _ => {}
-                    },
+                        self.connect_prompt = Some(ConnectPrompt::Confirm)
+                    }
+                    _ => {}
                 },
-            }
+                InputField::Database => match key {
+                    KeyCode::Char(c) => self.connection_input.database.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.database.pop();
+                    }
+                    KeyCode::Enter => self.connect_prompt = Some(ConnectPrompt::Confirm),
+                    _ => {}
+                },
+                InputField::SslMode => match key {
+                    KeyCode::Left => {
+                        self.connection_input.ssl_mode = self.connection_input.ssl_mode.previous();
+                    }
+                    KeyCode::Right | KeyCode::Char(' ') => {
+                        self.connection_input.ssl_mode = self.connection_input.ssl_mode.next();
+                    }
+                    KeyCode::Enter => self.connect_prompt = Some(ConnectPrompt::Confirm),
+                    _ => {}
+                },
+                InputField::SslRootCert => match key {
+                    KeyCode::Char(c) => self.connection_input.ssl_root_cert.push(c),
+                    KeyCode::Backspace => {
+                        self.connection_input.ssl_root_cert.pop();
+                    }
+                    KeyCode::Tab => {
+                        self.connection_input.ssl_root_cert =
+                            complete_path(&self.connection_input.ssl_root_cert);
+                    }
+                    KeyCode::Enter => self.connect_prompt = Some(ConnectPrompt::Confirm),
+                    _ => {}
+                },
+            },
         }
         Ok(())
     }
 
     async fn handle_database_selection_input(&mut self, key: KeyCode) -> io::Result<()> {
+        if self.editing_database_filter {
+            UIHandler::handle_database_filter_input(self, key).await;
+            return Ok(());
+        }
+
+        let key = vim_motion(key);
         match key {
             KeyCode::Up => {
-                if self.selected_database > 0 {
-                    self.selected_database -= 1;
-                }
+                self.selected_database = self.selected_database.saturating_sub(1);
             }
             KeyCode::Down => {
-                if !self.databases.is_empty() && self.selected_database < self.databases.len() - 1 {
-                    self.selected_database += 1;
-                }
+                self.selected_database = (self.selected_database + 1)
+                    .min(self.filtered_databases().len().saturating_sub(1));
+            }
+            KeyCode::Char('/') => self.editing_database_filter = true,
+            KeyCode::Esc if !self.database_filter.is_empty() => {
+                self.set_database_filter(String::new());
             }
             KeyCode::Enter => {
-                let cloned = self.databases.clone();
-                if let Some(db_name) = cloned.get(self.selected_database) {
-                    match self.selected_db_type {
-                        0 => {
-                            if let Err(err) =
-                                PostgresUI::connect_to_selected_db(self, db_name).await
-                            {
-                                eprintln!("Error connecting to PostgreSQL database: {}", err);
-                            } else {
-                                self.current_screen = ScreenState::TableView;
-                            }
-                        }
-                        1 => {
-                            if let Err(err) = MySQLUI::connect_to_selected_db(self, db_name).await {
-                                eprintln!("Error connecting to MySQL database: {}", err);
-                            } else {
-                                self.current_screen = ScreenState::TableView;
-                            }
-                        }
-                        _ => {
-                            eprintln!("Unsupported database type");
-                        }
+                let selected = self
+                    .filtered_databases()
+                    .get(self.selected_database)
+                    .map(|db| db.to_string());
+                if let Some(db_name) = &selected {
+                    if let Err(err) = self.connect_to_selected_db(db_name).await {
+                        self.connection_error_message = Some(format!(
+                            "Error connecting to {} database: {}",
+                            self.db_type.as_str(),
+                            err
+                        ));
+                    } else {
+                        self.current_database = Some(db_name.clone());
+                        self.update_tables().await;
+                        self.current_screen = ScreenState::TableView;
+                        self.restore_last_query();
                     }
                 }
             }
-            KeyCode::Char('q') => {
-                terminal::disable_raw_mode().unwrap();
-                execute!(stdout(), terminal::LeaveAlternateScreen).unwrap();
-                process::exit(0);
-            }
             _ => {}
         }
-        match self.selected_db_type {
-            0 => PostgresUI::update_tables(self).await,
-            1 => MySQLUI::update_tables(self).await,
-            _ => (),
-        }
 
         Ok(())
     }
 
-    async fn handle_table_view_input(
-        &mut self,
-        key: KeyCode,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-    ) {
-        match key {
-            KeyCode::F(1) => {
-                self.current_screen = ScreenState::DatabaseSelection;
-                self.sql_editor_content.clear();
-                self.sql_query_result.clear();
-                if let Err(err) = UIRenderer::render_database_selection_screen(self, terminal).await
-                {
-                    eprintln!("Error rendering database selection screen: {}", err);
+    async fn handle_session_switcher_input(&mut self, key: KeyCode) {
+        let Some(selected) = self.session_switcher else {
+            return;
+        };
+        // The extra last entry is "New connection".
+        let entry_count = self.sessions.len() + 1;
+
+        match vim_motion(key) {
+            KeyCode::Esc | KeyCode::F(4) => self.session_switcher = None,
+            KeyCode::Up if selected > 0 => self.session_switcher = Some(selected - 1),
+            KeyCode::Down if selected + 1 < entry_count => {
+                self.session_switcher = Some(selected + 1)
+            }
+            KeyCode::Delete => {
+                self.close_session(selected);
+                self.session_switcher = Some(selected.min(self.sessions.len()));
+            }
+            KeyCode::Enter => {
+                self.session_switcher = None;
+                if selected == self.sessions.len() {
+                    self.open_new_session();
+                } else {
+                    self.switch_session(selected);
                 }
             }
-            KeyCode::Tab => self.cycle_focus(),
-            KeyCode::Up => {
-                if let FocusedWidget::TablesList = self.current_focus {
-                    self.move_selection_up();
+            _ => {}
+        }
+    }
+
+    async fn handle_server_sessions_input(&mut self, key: KeyCode) {
+        let Some(list) = &mut self.server_sessions else {
+            return;
+        };
+
+        if list.confirming {
+            match key {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    list.confirming = false;
+                    self.terminate_selected_server_session().await;
                 }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    list.confirming = false;
+                }
+                _ => {}
             }
-            KeyCode::Down => {
-                if let FocusedWidget::TablesList = self.current_focus {
-                    self.move_selection_down();
+            return;
+        }
+
+        match vim_motion(key) {
+            KeyCode::Esc => self.server_sessions = None,
+            KeyCode::Up => list.selected = list.selected.saturating_sub(1),
+            KeyCode::Down if list.selected + 1 < list.sessions.len() => list.selected += 1,
+            KeyCode::Char('r') => self.open_server_sessions().await,
+            KeyCode::Char('x') | KeyCode::Delete => match list.selected_session() {
+                Some(session) if session.own => {
+                    self.status_message = Some(
+                        "That session is the one listing these; it is not terminated.".to_string(),
+                    );
                 }
+                Some(_) => list.confirming = true,
+                None => {}
+            },
+            _ => {}
+        }
+    }
+
+    async fn handle_database_switcher_input(&mut self, key: KeyCode) {
+        let Some(selected) = self.database_switcher else {
+            return;
+        };
+        let key = vim_motion(key);
+
+        match key {
+            KeyCode::Esc | KeyCode::F(2) => {
+                self.database_switcher = None;
+            }
+            KeyCode::Up if selected > 0 => {
+                self.database_switcher = Some(selected - 1);
+            }
+            KeyCode::Down if selected + 1 < self.databases.len() => {
+                self.database_switcher = Some(selected + 1);
             }
             KeyCode::Enter => {
-                if let FocusedWidget::TablesList = self.current_focus {
-                    if self.tables.is_empty() {
-                        println!("No tables available.");
-                        return;
-                    }
+                self.database_switcher = None;
+                let Some(db_name) = self.databases.get(selected).cloned() else {
+                    return;
+                };
 
-                    if self.selected_table < self.tables.len() {
-                        let selected_table = self.tables[self.selected_table].clone();
-
-                        if Some(self.selected_table) == self.expanded_table {
-                            self.expanded_table = None;
-                        } else {
-                            match self.selected_db_type {
-                                0 => {
-                                    match PostgresUI::describe_table(self, &selected_table).await {
-                                        Ok(table_schema) => {
-                                            self.table_schemas.insert(
-                                                selected_table.clone(),
-                                                table_schema.clone(),
-                                            );
-                                            self.expanded_table = Some(self.selected_table);
-
-                                            if let Err(err) = UIRenderer::render_table_schema(
-                                                self,
-                                                terminal,
-                                                &table_schema,
-                                            )
-                                            .await
-                                            {
-                                                eprintln!("Error rendering table schema: {}", err);
-                                            }
-                                        }
-                                        Err(err) => {
-                                            eprintln!("Error describing table: {}", err);
-                                        }
-                                    }
-                                }
-                                1 => match MySQLUI::describe_table(self, &selected_table).await {
-                                    Ok(table_schema) => {
-                                        self.table_schemas
-                                            .insert(selected_table.clone(), table_schema.clone());
-                                        self.expanded_table = Some(self.selected_table);
-
-                                        if let Err(err) = UIRenderer::render_table_schema(
-                                            self,
-                                            terminal,
-                                            &table_schema,
-                                        )
-                                        .await
-                                        {
-                                            eprintln!("Error rendering table schema: {}", err);
-                                        }
-                                    }
-                                    Err(err) => {
-                                        eprintln!("Error describing table: {}", err);
-                                    }
-                                },
-                                _ => (),
-                            }
-                        }
-                    } else {
-                        eprintln!("Selected table index out of bounds.");
+                match self.switch_database(&db_name).await {
+                    Ok(()) => {
+                        self.current_database = Some(db_name);
+                        self.selected_database = selected;
+                        self.table_schemas.clear();
+                        self.expanded_tables.clear();
+                        self.clear_query_result();
+                        self.update_tables().await;
+                        self.restore_last_query();
+                    }
+                    Err(err) => {
+                        self.sql_query_error =
+                            Some(format!("Error switching to database {}: {}", db_name, err));
                     }
                 }
             }
@@ -289,93 +425,1853 @@ impl UIHandler for DatabaseClientUI {
         }
     }
 
-    async fn handle_sql_editor_input(
+    async fn handle_confirmation_input(
         &mut self,
         key: KeyCode,
-        modifiers: KeyModifiers,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) {
-        match (key, modifiers) {
-            (KeyCode::Tab, _) => self.cycle_focus(),
-            (KeyCode::F(5), _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
-                if !self.sql_editor_content.is_empty() {
-                    self.sql_query_error = None;
-                    let sql_content = self.sql_editor_content.clone();
-                    match self.selected_db_type {
-                        0 => match PostgresUI::execute_sql_query(self, &sql_content).await {
-                            Ok((result, success_message)) => {
-                                self.sql_query_result = result;
-                                self.sql_query_success_message = success_message;
-                                self.sql_query_error = None;
-                            }
-                            Err(err) => {
-                                self.sql_query_error = Some(err.to_string());
-                                self.sql_query_result.clear();
-                            }
-                        },
-                        1 => match MySQLUI::execute_sql_query(self, &sql_content).await {
-                            Ok((result, success_message)) => {
-                                self.sql_query_result = result;
-                                self.sql_query_success_message = success_message;
-                                self.sql_query_error = None;
-                            }
-                            Err(err) => {
-                                self.sql_query_error = Some(err.to_string());
-                                self.sql_query_result.clear();
-                            }
-                        },
-                        _ => (),
-                    }
-                    self.sql_editor_content.clear();
+        match key {
+            KeyCode::Char('y') | KeyCode::Char('Y') => {
+                self.pending_destructive_query = None;
+                if std::mem::take(&mut self.pending_destructive_explain) {
+                    self.explain_editor_query().await;
+                } else {
+                    self.execute_editor_query(terminal, false).await;
                 }
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                self.pending_destructive_query = None;
+                self.pending_destructive_explain = false;
+                self.status_message = Some("Query not executed.".to_string());
+            }
+            _ => {}
+        }
+    }
 
-                PostgresUI::update_tables(self).await;
+    async fn handle_database_filter_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char(c) => {
+                let mut filter = self.database_filter.clone();
+                filter.push(c);
+                self.set_database_filter(filter);
             }
-            (KeyCode::Enter, _) => {
-                self.sql_editor_content.push('\n');
+            KeyCode::Backspace => {
+                let mut filter = self.database_filter.clone();
+                filter.pop();
+                self.set_database_filter(filter);
             }
-            (KeyCode::Char(c), _) => {
-                self.sql_editor_content.push(c);
+            KeyCode::Up if self.selected_database > 0 => self.selected_database -= 1,
+            KeyCode::Down if self.selected_database + 1 < self.filtered_databases().len() => {
+                self.selected_database += 1;
             }
-            (KeyCode::Backspace, _) => {
-                self.sql_editor_content.pop();
+            KeyCode::Enter => self.editing_database_filter = false,
+            KeyCode::Esc => {
+                self.editing_database_filter = false;
+                self.set_database_filter(String::new());
             }
-            (KeyCode::F(1), _) => {
-                self.current_screen = ScreenState::DatabaseSelection;
-                self.sql_editor_content.clear();
-                self.sql_query_result.clear();
-                if let Err(err) = UIRenderer::render_database_selection_screen(self, terminal).await
-                {
-                    eprintln!("Error rendering database selection screen: {}", err);
+            _ => {}
+        }
+    }
+
+    async fn handle_table_filter_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char(c) => {
+                let mut filter = self.table_filter.clone();
+                filter.push(c);
+                self.set_table_filter(filter);
+            }
+            KeyCode::Backspace => {
+                let mut filter = self.table_filter.clone();
+                filter.pop();
+                self.set_table_filter(filter);
+            }
+            KeyCode::Enter => self.editing_table_filter = false,
+            KeyCode::Esc => {
+                self.editing_table_filter = false;
+                self.set_table_filter(String::new());
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_insert_export_input(&mut self, key: KeyCode) {
+        let Some(prompt) = &mut self.insert_export else {
+            return;
+        };
+        match key {
+            KeyCode::Char(c) => prompt.table.push(c),
+            KeyCode::Backspace => {
+                prompt.table.pop();
+            }
+            KeyCode::Tab => {
+                prompt.target = match prompt.target {
+                    InsertTarget::File => InsertTarget::Editor,
+                    InsertTarget::Editor => InsertTarget::File,
+                };
+            }
+            KeyCode::Enter if !prompt.table.is_empty() => {
+                let table = std::mem::take(&mut prompt.table);
+                let target = prompt.target;
+                self.insert_export = None;
+                self.write_insert_statements(&table, target);
+            }
+            KeyCode::Esc => self.insert_export = None,
+            _ => {}
+        }
+    }
+
+    async fn handle_routine_call_input(&mut self, key: KeyCode) {
+        let Some(call) = &mut self.routine_call else {
+            return;
+        };
+        let fields = call.values.len();
+        match key {
+            KeyCode::Char(c) => call.values[call.field].push(c),
+            KeyCode::Backspace => {
+                call.values[call.field].pop();
+            }
+            KeyCode::Tab | KeyCode::Down => call.field = (call.field + 1) % fields,
+            KeyCode::BackTab | KeyCode::Up => call.field = (call.field + fields - 1) % fields,
+            KeyCode::Enter => {
+                if let Some(call) = self.routine_call.take() {
+                    self.run_routine_call(call).await;
                 }
-                return;
             }
+            KeyCode::Esc => self.routine_call = None,
             _ => {}
         }
-        if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
-            eprintln!("Error rendering UI: {}", err);
+    }
+
+    async fn handle_result_filter_input(&mut self, key: KeyCode) {
+        match key {
+            KeyCode::Char(c) => {
+                let mut filter = self.result_filter.clone();
+                filter.push(c);
+                self.set_result_filter(filter);
+            }
+            KeyCode::Backspace => {
+                let mut filter = self.result_filter.clone();
+                filter.pop();
+                self.set_result_filter(filter);
+            }
+            KeyCode::Enter => self.editing_result_filter = false,
+            KeyCode::Esc => {
+                self.editing_result_filter = false;
+                self.set_result_filter(String::new());
+            }
+            _ => {}
         }
     }
-}
 
-impl DatabaseClientUI {
-    pub fn cycle_focus(&mut self) {
-        self.current_focus = match self.current_focus {
-            FocusedWidget::TablesList => FocusedWidget::SqlEditor,
-            FocusedWidget::SqlEditor => FocusedWidget::TablesList,
-            FocusedWidget::_QueryResult => FocusedWidget::TablesList,
+    async fn handle_completion_input(&mut self, key: KeyCode) -> bool {
+        let Some(completion) = self.completion.as_mut() else {
+            return false;
         };
+        match key {
+            KeyCode::Up => completion.selected = completion.selected.saturating_sub(1),
+            KeyCode::Down if completion.selected + 1 < completion.candidates.len() => {
+                completion.selected += 1
+            }
+            KeyCode::Down => {}
+            KeyCode::Enter | KeyCode::Tab => self.accept_completion(),
+            KeyCode::Esc => self.completion = None,
+            _ => {
+                self.completion = None;
+                return false;
+            }
+        }
+        true
     }
 
-    pub fn move_selection_up(&mut self) {
-        if self.selected_table > 0 {
-            self.selected_table -= 1;
+    async fn handle_mouse_input(&mut self, mouse: MouseEvent) {
+        let position = Position::new(mouse.column, mouse.row);
+        match mouse.kind {
+            MouseEventKind::Down(MouseButton::Left) => {
+                let areas = &self.panel_areas;
+                if areas.tables.contains(position) {
+                    self.current_focus = FocusedWidget::TablesList;
+                    // The first list line sits just inside the top border.
+                    let line = mouse.row.saturating_sub(areas.tables.y + 1);
+                    if mouse.row > areas.tables.y {
+                        let line = usize::from(line) + self.tables_list_offset;
+                        if let Some(index) = self.table_at_line(line) {
+                            self.selected_table = index;
+                        }
+                    }
+                } else if areas.editor.contains(position) {
+                    self.current_focus = FocusedWidget::SqlEditor;
+                } else if areas.results.contains(position) {
+                    self.current_focus = FocusedWidget::QueryResult;
+                    // Body rows start below the top border and the header row.
+                    let first_body_row = areas.results.y + 2;
+                    if mouse.row >= first_body_row {
+                        let offset = usize::from(mouse.row - first_body_row);
+                        let row = self.result_scroll + offset;
+                        if offset < self.result_viewport_rows && row < self.sql_query_result.len() {
+                            self.selected_result_row = row;
+                        }
+                    }
+                }
+            }
+            MouseEventKind::ScrollUp => match self.current_focus {
+                FocusedWidget::TablesList => self.move_selection_up(),
+                FocusedWidget::QueryResult => self.move_result_selection_up(1),
+                FocusedWidget::SqlEditor => {}
+            },
+            MouseEventKind::ScrollDown => match self.current_focus {
+                FocusedWidget::TablesList => self.move_selection_down(),
+                FocusedWidget::QueryResult => self.move_result_selection_down(1),
+                FocusedWidget::SqlEditor => {}
+            },
+            _ => {}
         }
     }
 
-    pub fn move_selection_down(&mut self) {
-        if self.selected_table < self.databases.len().saturating_sub(1) {
-            self.selected_table += 1;
+    async fn handle_cell_detail_input(&mut self, key: KeyCode) {
+        // The renderer clamps both scrolls to the end of the value.
+        match key {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                self.cell_detail = None;
+                self.cell_detail_table = None;
+            }
+            KeyCode::Up => self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(1),
+            KeyCode::Down => self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(1),
+            KeyCode::PageUp => self.cell_detail_scroll = self.cell_detail_scroll.saturating_sub(10),
+            KeyCode::PageDown => {
+                self.cell_detail_scroll = self.cell_detail_scroll.saturating_add(10)
+            }
+            KeyCode::Home => self.cell_detail_scroll = 0,
+            KeyCode::End => self.cell_detail_scroll = usize::MAX,
+            KeyCode::Left => self.cell_detail_hscroll = self.cell_detail_hscroll.saturating_sub(4),
+            KeyCode::Right => self.cell_detail_hscroll = self.cell_detail_hscroll.saturating_add(4),
+            KeyCode::Char('w') => {
+                self.cell_detail_wrap = !self.cell_detail_wrap;
+                self.cell_detail_scroll = 0;
+                self.cell_detail_hscroll = 0;
+            }
+            KeyCode::Char('n') => self.cell_detail_line_numbers = !self.cell_detail_line_numbers,
+            KeyCode::Char('y') => self.copy_cell_detail(),
+            _ => {}
+        }
+    }
+
+    async fn handle_schema_view_input(&mut self, key: KeyCode) {
+        let Some(view) = &mut self.schema_view else {
+            return;
+        };
+        let last_line = self.table_schemas.get(&view.table).map_or(0, |schema| {
+            schema_view_lines(schema).len().saturating_sub(1)
+        });
+        match vim_motion(key) {
+            KeyCode::Esc => self.schema_view = None,
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(last_line),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(last_line),
+            KeyCode::Home => view.scroll = 0,
+            KeyCode::End => view.scroll = last_line,
+            _ => {}
+        }
+    }
+
+    async fn handle_connection_info_input(&mut self, key: KeyCode) {
+        let Some(view) = &mut self.connection_info else {
+            return;
+        };
+        let last_line = view.lines.len().saturating_sub(1);
+        match vim_motion(key) {
+            KeyCode::Esc => self.connection_info = None,
+            KeyCode::Up => view.scroll = view.scroll.saturating_sub(1),
+            KeyCode::Down => view.scroll = (view.scroll + 1).min(last_line),
+            KeyCode::PageUp => view.scroll = view.scroll.saturating_sub(10),
+            KeyCode::PageDown => view.scroll = (view.scroll + 10).min(last_line),
+            KeyCode::Home => view.scroll = 0,
+            KeyCode::End => view.scroll = last_line,
+            _ => {}
+        }
+    }
+
+    async fn handle_help_input(&mut self, key: KeyCode) {
+        let last_line = self.help_entries().len().saturating_sub(1);
+        match vim_motion(key) {
+            KeyCode::Esc | KeyCode::Char('?') => self.help_scroll = None,
+            KeyCode::Up => self.help_scroll = self.help_scroll.map(|line| line.saturating_sub(1)),
+            KeyCode::Down => {
+                self.help_scroll = self.help_scroll.map(|line| (line + 1).min(last_line))
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_table_view_input(&mut self, key: KeyCode) {
+        let key = vim_motion(key);
+        let pending_g = std::mem::take(&mut self.pending_g);
+        match key {
+            KeyCode::Char('g') if pending_g => self.jump_to_top(),
+            KeyCode::Char('g') => self.pending_g = true,
+            KeyCode::Char('G') => self.jump_to_bottom(),
+            KeyCode::Up => match self.current_focus {
+                FocusedWidget::TablesList => self.move_selection_up(),
+                FocusedWidget::QueryResult => self.move_result_selection_up(1),
+                FocusedWidget::SqlEditor => {}
+            },
+            KeyCode::Down => match self.current_focus {
+                FocusedWidget::TablesList => self.move_selection_down(),
+                FocusedWidget::QueryResult => self.move_result_selection_down(1),
+                FocusedWidget::SqlEditor => {}
+            },
+            KeyCode::Left if self.current_focus == FocusedWidget::QueryResult => {
+                self.selected_result_column = self.selected_result_column.saturating_sub(1);
+                self.result_column_offset =
+                    self.result_column_offset.min(self.selected_result_column);
+            }
+            KeyCode::Right
+                if self.current_focus == FocusedWidget::QueryResult
+                    && self.selected_result_column + 1 < self.sql_query_columns.len() =>
+            {
+                // The renderer scrolls the columns to keep this one in view.
+                self.selected_result_column += 1;
+            }
+            KeyCode::PageUp if self.current_focus == FocusedWidget::QueryResult => {
+                self.move_result_selection_up(self.result_viewport_rows);
+            }
+            KeyCode::PageDown if self.current_focus == FocusedWidget::QueryResult => {
+                self.move_result_selection_down(self.result_viewport_rows);
+            }
+            KeyCode::Enter if self.current_focus == FocusedWidget::QueryResult => {
+                self.open_cell_detail();
+            }
+            KeyCode::Enter if self.current_focus == FocusedWidget::TablesList => {
+                self.toggle_selected_table().await;
+            }
+            KeyCode::Right if self.current_focus == FocusedWidget::TablesList => {
+                self.expand_selected_table().await;
+            }
+            KeyCode::Left if self.current_focus == FocusedWidget::TablesList => {
+                self.collapse_selected_table();
+            }
+            _ => {}
+        }
+    }
+
+    async fn handle_sql_editor_input(
+        &mut self,
+        key: KeyCode,
+        modifiers: KeyModifiers,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) {
+        let is_motion = matches!(
+            key,
+            KeyCode::Left
+                | KeyCode::Right
+                | KeyCode::Up
+                | KeyCode::Down
+                | KeyCode::Home
+                | KeyCode::End
+        );
+        if is_motion && modifiers.contains(KeyModifiers::SHIFT) {
+            self.editor_start_selection();
+        } else if is_motion {
+            self.sql_editor_selection = None;
         }
+
+        let before = self.editor_state();
+        let is_undo = modifiers == KeyModifiers::CONTROL
+            && matches!(key, KeyCode::Char('z') | KeyCode::Char('y'));
+        match (key, modifiers) {
+            (KeyCode::Char('z'), KeyModifiers::CONTROL) => self.editor_undo(),
+            (KeyCode::Char('y'), KeyModifiers::CONTROL) => self.editor_redo(),
+            (KeyCode::Enter, _) => self.editor_insert_char('\n'),
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => self.editor_delete_to_line_start(),
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => self.editor_delete_word_before(),
+            (KeyCode::Char('l'), KeyModifiers::CONTROL) => self.clear_sql_editor(),
+            (KeyCode::Char(c), _) => self.editor_insert_char(c),
+            (KeyCode::Backspace, _) => self.editor_backspace(),
+            (KeyCode::Delete, _) => self.editor_delete(),
+            (KeyCode::Left, _) => self.editor_move_left(),
+            (KeyCode::Right, _) => self.editor_move_right(),
+            (KeyCode::Up, KeyModifiers::SHIFT) => self.editor_move_up(),
+            (KeyCode::Down, KeyModifiers::SHIFT) => self.editor_move_down(),
+            (KeyCode::Up, _) => self.editor_history_previous(),
+            (KeyCode::Down, _) => self.editor_history_next(),
+            (KeyCode::Home, _) => self.editor_move_home(),
+            (KeyCode::End, _) => self.editor_move_end(),
+            _ => {}
+        }
+        if self.sql_editor_content != before.content() && !is_undo {
+            self.edit_history.record(before, edit_group(key, modifiers));
+        } else if is_motion {
+            self.edit_history.break_group();
+        }
+        if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
+            self.connection_error_message = Some(format!("Error rendering UI: {}", err));
+        }
+    }
+}
+
+impl DatabaseClientUI {
+    /// Connects with the details entered on the connection screen, or opens
+    /// the file entered on the SQLite file screen, and remembers them for
+    /// next time.
+    pub async fn connect_from_input(&mut self) {
+        self.connect_retry = None;
+        self.try_connect(1).await;
+    }
+
+    /// Makes connection attempt number `attempt`, leaving the connection
+    /// screen if it works and scheduling another try if it fails while
+    /// retries are left.
+    async fn try_connect(&mut self, attempt: u32) {
+        if self.connect_to_default_db().await.is_err() {
+            if attempt <= self.connect_retries {
+                let delay = retry_delay(attempt);
+                let error = self.connection_error_message.take().unwrap_or_default();
+                self.connection_error_message = Some(format!(
+                    "{}\n\nAttempt {} of {} failed, retrying in {}s.",
+                    error,
+                    attempt,
+                    self.connect_retries + 1,
+                    delay.as_secs()
+                ));
+                self.connect_retry = Some(ConnectRetry {
+                    attempt,
+                    next_attempt: Instant::now() + delay,
+                });
+            }
+            return;
+        }
+
+        self.saved_connections.remember(SavedConnection::from_input(
+            self.db_type,
+            &self.connection_input,
+        ));
+        // Failing to write the config shouldn't block connecting.
+        let _ = self.saved_connections.save();
+        if self.db_type == DatabaseType::SQLite {
+            // A SQLite file is a single database, so skip the database list.
+            self.current_database = Some("main".to_string());
+            self.update_tables().await;
+            self.current_screen = ScreenState::TableView;
+            self.restore_last_query();
+        } else {
+            self.connection_input.show_password = false;
+            self.start_database_fetch();
+            self.current_screen = ScreenState::DatabaseSelection;
+        }
+    }
+
+    /// Makes the next attempt of a failed connection once its wait is over.
+    pub async fn retry_connection_if_due(&mut self) {
+        let Some(retry) = &self.connect_retry else {
+            return;
+        };
+        if Instant::now() < retry.next_attempt {
+            return;
+        }
+        let attempt = retry.attempt + 1;
+        self.connect_retry = None;
+        self.try_connect(attempt).await;
+    }
+
+    /// Stops retrying, leaving the connection screen open for corrections.
+    pub fn cancel_connect_retry(&mut self) {
+        self.connect_retry = None;
+        self.connection_error_message = None;
+    }
+
+    /// Runs a table view command bound in the keymap.
+    ///
+    /// Returns `false` when `action` doesn't apply to the focused panel, so
+    /// the key is handled as if it were unbound.
+    pub async fn run_action(
+        &mut self,
+        action: Action,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> bool {
+        let focus = self.current_focus;
+        match action {
+            Action::Quit => self.should_quit = true,
+            // In the editor, the word before the cursor is completed first.
+            Action::CycleFocus if focus == FocusedWidget::SqlEditor => {
+                if !self.editor_complete().await {
+                    self.cycle_focus();
+                }
+            }
+            Action::CycleFocus => self.cycle_focus(),
+            Action::Execute | Action::ExecuteFresh if focus == FocusedWidget::SqlEditor => {
+                if sql::is_blank(&self.db_type.db_type(), self.editor_query()) {
+                    return true;
+                }
+                if self.connection_input.read_only {
+                    if let Some(statement) =
+                        sql::find_write(&self.db_type.db_type(), self.editor_query())
+                    {
+                        self.sql_query_error = Some(format!(
+                            "Read-only mode only runs statements that read; refused: {}",
+                            statement
+                        ));
+                        return true;
+                    }
+                }
+                if let Some(statement) =
+                    sql::find_destructive(&self.db_type.db_type(), self.editor_query())
+                {
+                    self.pending_destructive_query = Some(statement.to_string());
+                    return true;
+                }
+                self.execute_editor_query(terminal, action == Action::Execute)
+                    .await;
+            }
+            Action::Explain if focus == FocusedWidget::SqlEditor => {
+                let db_type = self.db_type.db_type();
+                if sql::is_blank(&db_type, self.editor_query()) {
+                    return true;
+                }
+                // `EXPLAIN ANALYZE` runs the statement it explains, so it gets
+                // the same checks as running it would.
+                let explained = format!("EXPLAIN {}", self.editor_query());
+                if self.connection_input.read_only {
+                    if let Some(statement) = sql::find_write(&db_type, &explained) {
+                        self.sql_query_error = Some(format!(
+                            "Read-only mode only explains statements that read; refused: {}",
+                            statement
+                        ));
+                        return true;
+                    }
+                }
+                if let Some(statement) = sql::find_destructive(&db_type, &explained) {
+                    self.pending_destructive_query = Some(statement.to_string());
+                    self.pending_destructive_explain = true;
+                    return true;
+                }
+                self.explain_editor_query().await;
+            }
+            Action::OpenInEditor if focus == FocusedWidget::SqlEditor => {
+                self.edit_in_external_editor(terminal);
+            }
+            Action::DatabaseSelection => {
+                self.start_database_fetch();
+                self.current_screen = ScreenState::DatabaseSelection;
+                self.clear_sql_editor();
+                self.clear_query_result();
+            }
+            Action::SwitchDatabase => self.open_database_switcher().await,
+            Action::SwitchConnection => self.session_switcher = Some(self.active_session()),
+            Action::ServerSessions => self.open_server_sessions().await,
+            Action::ConnectionInfo => self.open_connection_info().await,
+            Action::ToggleTotals => self.result_footer = FooterAggregate::cycle(self.result_footer),
+            Action::BeginTransaction => self.begin_transaction().await,
+            Action::CommitTransaction => self.commit_transaction().await,
+            Action::RollbackTransaction => self.rollback_transaction().await,
+            Action::Refresh if focus != FocusedWidget::SqlEditor => self.refresh_tables().await,
+            Action::Preview if focus == FocusedWidget::TablesList => {
+                self.preview_selected_table().await;
+            }
+            Action::NextPage
+                if focus != FocusedWidget::SqlEditor && self.table_preview.is_some() =>
+            {
+                self.next_preview_page().await;
+            }
+            Action::PreviousPage
+                if focus != FocusedWidget::SqlEditor && self.table_preview.is_some() =>
+            {
+                self.previous_preview_page().await;
+            }
+            Action::Filter if focus == FocusedWidget::TablesList => {
+                self.editing_table_filter = true;
+            }
+            Action::Filter if focus == FocusedWidget::QueryResult => {
+                self.editing_result_filter = true;
+            }
+            Action::ExportCsv if focus == FocusedWidget::QueryResult => self.export_results_csv(),
+            Action::ExportJson if focus == FocusedWidget::QueryResult => self.export_results_json(),
+            Action::ExportInserts if focus == FocusedWidget::QueryResult => {
+                self.open_insert_export()
+            }
+            Action::ExportMarkdown if focus == FocusedWidget::QueryResult => {
+                self.export_results_markdown()
+            }
+            Action::CopyCell if focus == FocusedWidget::QueryResult => self.copy_selected_cell(),
+            Action::CopyRow if focus == FocusedWidget::QueryResult => self.copy_selected_row(),
+            Action::SortResults if focus == FocusedWidget::QueryResult => {
+                self.sort_results_by_selected_column();
+            }
+            Action::ToggleNullsOrder if focus == FocusedWidget::QueryResult => {
+                self.toggle_nulls_order();
+            }
+            Action::PinColumn if focus == FocusedWidget::QueryResult => {
+                self.toggle_pinned_column();
+            }
+            Action::ShowDdl if focus == FocusedWidget::TablesList => {
+                self.show_selected_table_ddl().await;
+            }
+            Action::DescribeTable if focus == FocusedWidget::TablesList => {
+                self.describe_selected_table().await;
+            }
+            Action::ToggleObjects if focus == FocusedWidget::TablesList => {
+                self.toggle_objects().await;
+            }
+            Action::CallRoutine if focus == FocusedWidget::TablesList => {
+                self.open_routine_call().await;
+            }
+            Action::Help => self.help_scroll = Some(0),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Runs the SQL editor content, records it in history and clears the editor.
+    /// With text selected, only the selection runs and the editor is kept.
+    ///
+    /// The statements run on a background task that reports back over a
+    /// channel, while this keeps redrawing with a spinner. Esc cancels the
+    /// query; the editor content is kept so it can be tweaked and run again.
+    pub async fn execute_editor_query(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+        use_cache: bool,
+    ) {
+        self.sql_query_error = None;
+        self.table_preview = None;
+        self.script_log.clear();
+        self.server_messages.clear();
+        let sql_content = self.editor_query().to_string();
+        let ran_selection = self.editor_selection().is_some();
+        self.query_history.push(&sql_content);
+
+        // Inside a transaction, a query may see rows that aren't committed.
+        let cache_key = self
+            .result_cache
+            .key(
+                &self.db_type.db_type(),
+                &self.connection_label(),
+                &sql_content,
+            )
+            .filter(|_| !self.transaction_active);
+        if use_cache && self.show_cached_result(cache_key.as_ref()) {
+            self.detect_timestamp_columns(&sql_content).await;
+            if !ran_selection {
+                self.last_queries
+                    .insert(self.connection_label(), sql_content);
+                self.clear_sql_editor();
+            }
+            return;
+        }
+        if sql::find_write(&self.db_type.db_type(), &sql_content).is_some() {
+            self.result_cache.clear();
+        }
+
+        let started = Instant::now();
+        let (mut outcome, connection_lost) = self
+            .run_script_in_background(sql_content.clone(), started, terminal)
+            .await;
+        if connection_lost {
+            let in_transaction = self.transaction_active;
+            self.status_message = Some("Connection lost, reconnecting…".to_string());
+            if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
+                self.connection_error_message = Some(format!("Error rendering UI: {}", err));
+            }
+            match self.reconnect().await {
+                Ok(()) if in_transaction => {
+                    outcome = Some(Err("The connection was lost, and the open transaction \
+                         with it. Reconnected; run the transaction again."
+                        .to_string()));
+                }
+                Ok(()) if sql::find_write(&self.db_type.db_type(), &sql_content).is_some() => {
+                    outcome = Some(Err(LOST_WRITE_MESSAGE.to_string()));
+                }
+                Ok(()) => {
+                    self.script_log.clear();
+                    self.server_messages.clear();
+                    (outcome, _) = self
+                        .run_script_in_background(sql_content.clone(), started, terminal)
+                        .await;
+                }
+                // The connection error dialog explains why.
+                Err(_) => {}
+            }
+        }
+
+        let elapsed = started.elapsed();
+        match outcome {
+            None => {
+                self.status_message = Some(format!(
+                    "Query cancelled after {}; the server may still finish it.",
+                    format_elapsed(elapsed)
+                ));
+                return;
+            }
+            Some(Ok(outcome)) => {
+                self.status_message = Some(match &outcome.message {
+                    Some(message) => format!("{} ({})", message, format_elapsed(elapsed)),
+                    None => query_summary(outcome.rows.len(), elapsed),
+                });
+                if let Some(key) = cache_key {
+                    self.result_cache.insert(
+                        key,
+                        CachedResult {
+                            columns: outcome.columns.clone(),
+                            rows: outcome.rows.clone(),
+                            message: outcome.message.clone(),
+                            stored: Instant::now(),
+                        },
+                    );
+                }
+                self.sql_query_columns = outcome.columns;
+                self.sql_query_result = outcome.rows;
+                self.reset_result_position();
+                self.sql_query_success_message = outcome.message;
+                self.sql_query_error = None;
+                self.detect_timestamp_columns(&sql_content).await;
+            }
+            Some(Err(err)) => {
+                self.status_message =
+                    Some(format!("Query failed after {}", format_elapsed(elapsed)));
+                self.sql_query_error = Some(err);
+                self.clear_query_result();
+            }
+        }
+        if !ran_selection {
+            self.last_queries
+                .insert(self.connection_label(), sql_content);
+            self.clear_sql_editor();
+        }
+
+        self.update_tables().await;
+    }
+
+    /// Shows the result cached under `key`, if there is a fresh one,
+    /// returning whether it did.
+    fn show_cached_result(&mut self, key: Option<&(String, String)>) -> bool {
+        let Some(cached) = key.and_then(|key| self.result_cache.get(key)) else {
+            return false;
+        };
+        let columns = cached.columns.clone();
+        let rows = cached.rows.clone();
+        let message = cached.message.clone();
+        let age = cached.stored.elapsed();
+
+        let count = if rows.len() == 1 { "row" } else { "rows" };
+        self.status_message = Some(format!(
+            "{} {} from the cache, {}s old ({} reruns it)",
+            rows.len(),
+            count,
+            age.as_secs(),
+            self.keymap.label(Action::ExecuteFresh)
+        ));
+        self.sql_query_columns = columns;
+        self.sql_query_result = rows;
+        self.reset_result_position();
+        self.result_from_cache = true;
+        self.sql_query_success_message = message;
+        self.sql_query_error = None;
+        true
+    }
+
+    /// What was asked for since the last check while waiting on the
+    /// database. Doesn't block, so the UI keeps drawing; other input
+    /// meanwhile is discarded.
+    pub fn wait_input(&self) -> Option<WaitInput> {
+        let typing = self.current_focus == FocusedWidget::SqlEditor;
+        let mut resized = false;
+        while let Ok(true) = event::poll(Duration::ZERO) {
+            match event::read() {
+                // Esc cancels even when it is also the quit key.
+                Ok(Event::Key(key)) if key.code == KeyCode::Esc => return Some(WaitInput::Cancel),
+                Ok(Event::Key(key))
+                    if self.keymap.action(key.code, key.modifiers, typing)
+                        == Some(Action::Quit) =>
+                {
+                    return Some(WaitInput::Quit)
+                }
+                Ok(Event::Resize(..)) => resized = true,
+                _ => {}
+            }
+        }
+        resized.then_some(WaitInput::Resized)
+    }
+
+    /// Runs `script` on a background task, redrawing with a spinner until it
+    /// finishes. The outcome is `None` if Esc or the quit key cancelled it;
+    /// the flag says whether the connection was found gone before anything
+    /// ran.
+    async fn run_script_in_background(
+        &mut self,
+        script: String,
+        started: Instant,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> (Option<Result<StatementOutcome, String>>, bool) {
+        let (events, mut received) = mpsc::unbounded_channel();
+        let db_manager = self.db_manager.clone();
+        let db_type = self.db_type.db_type();
+        let query_timeout = self.query_timeout;
+        let task = tokio::spawn(async move {
+            let run = run_script(&db_manager, &db_type, &script, &events);
+            match query_timeout {
+                Some(limit) => {
+                    if tokio::time::timeout(limit, run).await.is_err() {
+                        let message = format!(
+                            "Query timed out after {}s. dfox stopped waiting, but the \
+                             server may still be running it.",
+                            limit.as_secs()
+                        );
+                        let _ = events.send(QueryEvent::Finished(Err(message)));
+                    }
+                }
+                None => run.await,
+            }
+        });
+
+        let mut connection_lost = false;
+        let mut ticker = tokio::time::interval(SPINNER_INTERVAL);
+        let outcome = loop {
+            tokio::select! {
+                event = received.recv() => match event {
+                    Some(QueryEvent::ConnectionLost) => connection_lost = true,
+                    Some(event) => {
+                        if let Some(result) = self.apply_query_event(event) {
+                            break Some(result);
+                        }
+                    }
+                    None => break Some(Err("The query stopped unexpectedly.".to_string())),
+                },
+                _ = ticker.tick() => {
+                    match self.wait_input() {
+                        // Aborting drops the query future, which releases the
+                        // connection; the server isn't told to stop.
+                        Some(WaitInput::Cancel) => {
+                            task.abort();
+                            break None;
+                        }
+                        Some(WaitInput::Quit) => {
+                            task.abort();
+                            self.should_quit = true;
+                            break None;
+                        }
+                        Some(WaitInput::Resized) => {
+                            let _ = terminal.clear();
+                        }
+                        None => {}
+                    }
+                    self.status_message = Some(format!(
+                        "{} Running… {} (Esc to cancel)",
+                        spinner_frame(started.elapsed()),
+                        format_elapsed(started.elapsed())
+                    ));
+                    if let Err(err) = UIRenderer::render_table_view_screen(self, terminal).await {
+                        self.connection_error_message = Some(format!("Error rendering UI: {}", err));
+                    }
+                }
+            }
+        };
+        (outcome, connection_lost)
+    }
+
+    /// Applies progress from the background query task, returning the
+    /// script's outcome once it has finished.
+    fn apply_query_event(&mut self, event: QueryEvent) -> Option<Result<StatementOutcome, String>> {
+        match event {
+            QueryEvent::Ran(statement) => self.invalidate_schemas(&statement),
+            QueryEvent::Logged(line) => self.script_log.push(line),
+            QueryEvent::Notice(notice) => self.server_messages.push(notice),
+            QueryEvent::ConnectionLost => {}
+            QueryEvent::Finished(result) => return Some(result),
+        }
+        None
+    }
+
+    /// Runs `script` like the editor does, but in place rather than on a
+    /// background task.
+    #[cfg(test)]
+    async fn execute_script(
+        &mut self,
+        script: &str,
+    ) -> Result<(Vec<HashMap<String, Value>>, Option<String>), String> {
+        let (events, mut received) = mpsc::unbounded_channel();
+        run_script(
+            &self.db_manager.clone(),
+            &self.db_type.db_type(),
+            script,
+            &events,
+        )
+        .await;
+        drop(events);
+
+        let mut outcome = Err("The query stopped unexpectedly.".to_string());
+        while let Some(event) = received.recv().await {
+            if let Some(result) = self.apply_query_event(event) {
+                outcome = result;
+            }
+        }
+        let outcome = outcome?;
+        self.sql_query_columns = outcome.columns;
+        Ok((outcome.rows, outcome.message))
+    }
+
+    /// Shows the plan for the SQL editor content in the Query Result panel.
+    ///
+    /// The editor is left as is, so the query can be run or tuned next.
+    pub async fn explain_editor_query(&mut self) {
+        let sql_content = self.editor_query().to_string();
+        self.script_log.clear();
+        self.server_messages.clear();
+        match self.explain_query(&sql_content).await {
+            Ok(plan) => {
+                self.sql_query_columns = vec![QUERY_PLAN_COLUMN.to_string()];
+                self.sql_query_result = plan
+                    .into_iter()
+                    .map(|line| {
+                        HashMap::from([(QUERY_PLAN_COLUMN.to_string(), Value::String(line))])
+                    })
+                    .collect();
+                self.table_preview = None;
+                self.reset_result_position();
+                self.sql_query_success_message = None;
+                self.sql_query_error = None;
+                self.status_message = Some("Query plan".to_string());
+            }
+            Err(err) => {
+                self.sql_query_error = Some(format!("Error explaining query: {}", err));
+                self.clear_query_result();
+            }
+        }
+    }
+
+    /// Starts loading the database list in the background; the selection
+    /// screen shows a spinner until [`Self::finish_database_fetch`] stores it.
+    pub fn start_database_fetch(&mut self) {
+        let db_manager = self.db_manager.clone();
+        self.databases.clear();
+        // Keep the cursor; finish_database_fetch clamps it to the new list.
+        self.database_filter.clear();
+        self.editing_database_filter = false;
+        self.database_fetch = Some(PendingFetch::spawn(async move {
+            list_databases(&db_manager)
+                .await
+                .map_err(|err| err.to_string())
+        }));
+    }
+
+    /// Stores the database list once its background fetch is done, or shows
+    /// why it failed.
+    pub async fn finish_database_fetch(&mut self) {
+        match self.database_fetch.take() {
+            Some(fetch) if fetch.is_finished() => match fetch.result().await {
+                Ok(databases) => {
+                    self.databases = databases;
+                    // The list may have shrunk since the cursor was last moved.
+                    self.selected_database = self
+                        .selected_database
+                        .min(self.filtered_databases().len().saturating_sub(1));
+                }
+                Err(err) => {
+                    self.connection_error_message =
+                        Some(format!("Error fetching databases: {}", err));
+                }
+            },
+            pending => self.database_fetch = pending,
+        }
+    }
+
+    /// Databases fuzzy-matching `database_filter`, in server order; all of
+    /// them when it is empty.
+    pub fn filtered_databases(&self) -> Vec<&String> {
+        self.databases
+            .iter()
+            .filter(|db| fuzzy_match(db, &self.database_filter).is_some())
+            .collect()
+    }
+
+    pub fn set_database_filter(&mut self, filter: String) {
+        self.database_filter = filter;
+        self.selected_database = 0;
+    }
+
+    pub async fn open_database_switcher(&mut self) {
+        match self.fetch_databases().await {
+            Ok(databases) => {
+                let current = self
+                    .current_database
+                    .as_ref()
+                    .and_then(|name| databases.iter().position(|db| db == name))
+                    .unwrap_or(0);
+                self.databases = databases;
+                self.database_switcher = Some(current);
+            }
+            Err(err) => {
+                self.sql_query_error = Some(format!("Error fetching databases: {}", err));
+            }
+        }
+    }
+
+    pub fn cycle_focus(&mut self) {
+        self.current_focus = match self.current_focus {
+            FocusedWidget::TablesList => FocusedWidget::SqlEditor,
+            FocusedWidget::SqlEditor => FocusedWidget::QueryResult,
+            FocusedWidget::QueryResult => FocusedWidget::TablesList,
+        };
+    }
+
+    pub fn clear_query_result(&mut self) {
+        self.sql_query_result.clear();
+        self.sql_query_columns.clear();
+        self.table_preview = None;
+        self.reset_result_position();
+    }
+
+    pub fn reset_result_position(&mut self) {
+        self.result_scroll = 0;
+        self.result_column_offset = 0;
+        self.selected_result_row = 0;
+        self.selected_result_column = 0;
+        self.result_sort = None;
+        self.result_from_cache = false;
+        self.result_filter.clear();
+        self.editing_result_filter = false;
+        self.unfiltered_result = None;
+    }
+
+    /// Narrows `sql_query_result` to the fetched rows with a cell containing
+    /// `filter`, ignoring case, keeping them sorted if they were.
+    pub fn set_result_filter(&mut self, filter: String) {
+        let rows = self
+            .unfiltered_result
+            .take()
+            .unwrap_or_else(|| std::mem::take(&mut self.sql_query_result));
+        if filter.is_empty() {
+            self.sql_query_result = rows;
+        } else {
+            self.sql_query_result = rows
+                .iter()
+                .filter(|row| row_matches(row, &filter))
+                .cloned()
+                .collect();
+            self.unfiltered_result = Some(rows);
+        }
+        if let Some((column, order)) = &self.result_sort {
+            sort_rows(
+                &mut self.sql_query_result,
+                column,
+                *order,
+                self.result_nulls_last,
+            );
+        }
+        self.result_filter = filter;
+        self.result_scroll = 0;
+        self.selected_result_row = 0;
+    }
+
+    /// Sorts the fetched rows by the focused column, ascending first and
+    /// reversing when sorted by the same column again.
+    pub fn sort_results_by_selected_column(&mut self) {
+        let Some(header) = self
+            .sql_query_columns
+            .get(self.selected_result_column)
+            .cloned()
+        else {
+            return;
+        };
+        let order = match &self.result_sort {
+            Some((column, SortOrder::Ascending)) if *column == header => SortOrder::Descending,
+            _ => SortOrder::Ascending,
+        };
+        sort_rows(
+            &mut self.sql_query_result,
+            &header,
+            order,
+            self.result_nulls_last,
+        );
+        self.result_sort = Some((header, order));
+        self.result_scroll = 0;
+        self.selected_result_row = 0;
+    }
+
+    /// Pins the focused result column so it stays on the left while the
+    /// others scroll, or unpins it.
+    pub fn toggle_pinned_column(&mut self) {
+        let Some(header) = self
+            .sql_query_columns
+            .get(self.selected_result_column)
+            .cloned()
+        else {
+            return;
+        };
+        self.status_message = Some(if self.pinned_columns.remove(&header) {
+            format!("Unpinned {}.", header)
+        } else {
+            let message = format!("Pinned {}.", header);
+            self.pinned_columns.insert(header);
+            message
+        });
+    }
+
+    /// Switches sorting between NULLs first and NULLs last, re-sorting the
+    /// rows if they are sorted.
+    pub fn toggle_nulls_order(&mut self) {
+        self.result_nulls_last = !self.result_nulls_last;
+        if let Some((column, order)) = &self.result_sort {
+            sort_rows(
+                &mut self.sql_query_result,
+                column,
+                *order,
+                self.result_nulls_last,
+            );
+            self.result_scroll = 0;
+            self.selected_result_row = 0;
+        }
+        self.status_message = Some(
+            if self.result_nulls_last {
+                "NULLs sort last."
+            } else {
+                "NULLs sort first."
+            }
+            .to_string(),
+        );
+    }
+
+    /// Copies the focused result cell to the clipboard.
+    pub fn copy_selected_cell(&mut self) {
+        let row = self.sql_query_result.get(self.selected_result_row);
+        let header = self.sql_query_columns.get(self.selected_result_column);
+        if let (Some(row), Some(header)) = (row, header) {
+            let text = cell_text(row, header);
+            self.copy_text(&text, "cell");
+        }
+    }
+
+    /// Copies the focused result row to the clipboard as tab-separated values.
+    pub fn copy_selected_row(&mut self) {
+        if let Some(row) = self.sql_query_result.get(self.selected_result_row) {
+            let text = self
+                .sql_query_columns
+                .iter()
+                .map(|header| cell_text(row, header))
+                .collect::<Vec<_>>()
+                .join("\t");
+            self.copy_text(&text, "row");
+        }
+    }
+
+    /// Copies the whole value shown in the cell detail popup.
+    fn copy_cell_detail(&mut self) {
+        if let Some(text) = self.cell_detail.clone() {
+            let what = if self.cell_detail_table.is_some() {
+                "DDL"
+            } else {
+                "cell"
+            };
+            self.copy_text(&text, what);
+        }
+    }
+
+    fn copy_text(&mut self, text: &str, what: &str) {
+        self.status_message = Some(match copy_to_clipboard(text) {
+            Ok(()) => format!("Copied {} to clipboard.", what),
+            Err(err) => format!("Copy failed: {}", err),
+        });
+    }
+
+    /// Shows the full value of the focused result cell in a popup.
+    pub fn open_cell_detail(&mut self) {
+        let row = self.sql_query_result.get(self.selected_result_row);
+        let header = self.sql_query_columns.get(self.selected_result_column);
+        if let (Some(row), Some(header)) = (row, header) {
+            self.cell_detail = Some(cell_detail_text(row, header));
+            self.cell_detail_table = None;
+            self.cell_detail_scroll = 0;
+            self.cell_detail_hscroll = 0;
+        }
+    }
+
+    pub fn export_results_csv(&mut self) {
+        self.export_results("csv", export::export_csv);
+    }
+
+    pub fn export_results_json(&mut self) {
+        self.export_results("json", export::export_json);
+    }
+
+    /// Exports the results as a Markdown table and copies the table as well,
+    /// ready to paste into docs or a pull request.
+    pub fn export_results_markdown(&mut self) {
+        self.export_results("md", export::export_markdown);
+        if self.sql_query_result.is_empty() {
+            return;
+        }
+        let table = export::markdown_table(&self.sql_query_columns, &self.sql_query_result);
+        if let (Ok(()), Some(status)) = (copy_to_clipboard(&table), &mut self.status_message) {
+            status.push_str(" and copied the table");
+        }
+    }
+
+    fn export_results(&mut self, extension: &str, write: ExportFn) {
+        if self.sql_query_result.is_empty() {
+            self.status_message = Some("No query results to export.".to_string());
+            return;
+        }
+
+        let path = export_path(extension);
+        self.status_message = Some(
+            match write(&path, &self.sql_query_columns, &self.sql_query_result) {
+                Ok(()) => format!(
+                    "Exported {} rows to {}",
+                    self.sql_query_result.len(),
+                    path.display()
+                ),
+                Err(err) => err.to_string(),
+            },
+        );
+    }
+
+    /// Asks which table the INSERT statements for the fetched rows should
+    /// target, suggesting the table being previewed.
+    pub fn open_insert_export(&mut self) {
+        if self.sql_query_result.is_empty() {
+            self.status_message = Some("No query results to export.".to_string());
+            return;
+        }
+        self.insert_export = Some(InsertExport {
+            table: self
+                .table_preview
+                .as_ref()
+                .map(|preview| preview.table.clone())
+                .unwrap_or_default(),
+            target: InsertTarget::File,
+        });
+    }
+
+    /// Turns the shown rows into INSERT statements into `table`, quoted for
+    /// the connected backend.
+    pub fn write_insert_statements(&mut self, table: &str, target: InsertTarget) {
+        let db_type = self.db_type.db_type();
+        let row_count = self.sql_query_result.len();
+        match target {
+            InsertTarget::File => {
+                let path = export_path("sql");
+                self.status_message = Some(
+                    match export::export_inserts(
+                        &path,
+                        &db_type,
+                        table,
+                        &self.sql_query_columns,
+                        &self.sql_query_result,
+                    ) {
+                        Ok(()) => format!(
+                            "Wrote {} INSERT statements to {}",
+                            row_count,
+                            path.display()
+                        ),
+                        Err(err) => err.to_string(),
+                    },
+                );
+            }
+            InsertTarget::Editor => {
+                self.editor_checkpoint();
+                self.sql_editor_content = export::insert_statements(
+                    &db_type,
+                    table,
+                    &self.sql_query_columns,
+                    &self.sql_query_result,
+                );
+                self.sql_editor_cursor = self.sql_editor_content.len();
+                self.sql_editor_selection = None;
+                self.current_focus = FocusedWidget::SqlEditor;
+                self.status_message = Some(format!(
+                    "Wrote {} INSERT statements to the editor",
+                    row_count
+                ));
+            }
+        }
+    }
+
+    /// Starts a transaction; editor statements run inside it until it is
+    /// committed or rolled back.
+    pub async fn begin_transaction(&mut self) {
+        match self.db_manager.begin_transaction().await {
+            Ok(()) => self.status_message = Some("Transaction started.".to_string()),
+            Err(err) => self.sql_query_error = Some(err.to_string()),
+        }
+        self.transaction_active = self.db_manager.in_transaction().await;
+    }
+
+    pub async fn commit_transaction(&mut self) {
+        match self.db_manager.commit_transaction().await {
+            Ok(()) => {
+                self.status_message = Some("Transaction committed.".to_string());
+                self.update_tables().await;
+            }
+            Err(err) => self.sql_query_error = Some(err.to_string()),
+        }
+        self.transaction_active = self.db_manager.in_transaction().await;
+    }
+
+    pub async fn rollback_transaction(&mut self) {
+        match self.db_manager.rollback_transaction().await {
+            Ok(()) => {
+                self.status_message = Some("Transaction rolled back.".to_string());
+                self.update_tables().await;
+            }
+            Err(err) => self.sql_query_error = Some(err.to_string()),
+        }
+        self.transaction_active = self.db_manager.in_transaction().await;
+    }
+
+    /// Reloads the tables list and row counts and drops cached schemas, so DDL
+    /// run from the editor shows up.
+    pub async fn refresh_tables(&mut self) {
+        self.table_schemas.clear();
+        self.expanded_tables.clear();
+        self.update_tables().await;
+        self.selected_table = self
+            .selected_table
+            .min(self.visible_tables().len().saturating_sub(1));
+        if self.connection_error_message.is_none() {
+            self.status_message = Some("Tables refreshed.".to_string());
+        }
+    }
+
+    /// Shows the first page of the selected table's rows in the Query Result panel.
+    pub async fn preview_selected_table(&mut self) {
+        if self.selected_routine().is_some() {
+            self.status_message = Some(format!(
+                "Functions and procedures are called with {}.",
+                self.keymap.label(Action::CallRoutine)
+            ));
+            return;
+        }
+        let Some(table) = self
+            .visible_tables()
+            .get(self.selected_table)
+            .map(|t| t.to_string())
+        else {
+            self.status_message = Some("No tables available.".to_string());
+            return;
+        };
+        self.table_preview = Some(TablePreview {
+            table,
+            page: 0,
+            is_last_page: false,
+        });
+        self.current_focus = FocusedWidget::QueryResult;
+        self.load_preview_page().await;
+    }
+
+    pub async fn next_preview_page(&mut self) {
+        match self.table_preview.as_mut() {
+            Some(preview) if preview.is_last_page => {
+                self.status_message = Some("Already on the last page.".to_string());
+            }
+            Some(preview) => {
+                preview.page += 1;
+                self.load_preview_page().await;
+            }
+            None => {}
+        }
+    }
+
+    pub async fn previous_preview_page(&mut self) {
+        if let Some(preview) = self.table_preview.as_mut() {
+            if preview.page > 0 {
+                preview.page -= 1;
+                preview.is_last_page = false;
+                self.load_preview_page().await;
+            }
+        }
+    }
+
+    /// Queries the current preview page with `LIMIT`/`OFFSET`.
+    async fn load_preview_page(&mut self) {
+        let Some(preview) = &self.table_preview else {
+            return;
+        };
+        let query = preview_query(self.db_type, &preview.table, self.page_size, preview.page);
+        self.script_log.clear();
+        self.server_messages.clear();
+
+        match self.execute_sql_query(&query).await {
+            Ok((rows, _)) => {
+                let first_row = self
+                    .table_preview
+                    .as_ref()
+                    .map_or(0, |preview| preview.page * self.page_size);
+                let row_count = rows.len();
+                if let Some(preview) = self.table_preview.as_mut() {
+                    preview.is_last_page = row_count < self.page_size;
+                    self.status_message = Some(if row_count == 0 {
+                        format!("{}: page {} (no rows)", preview.table, preview.page + 1)
+                    } else {
+                        format!(
+                            "{}: page {} (rows {}–{})",
+                            preview.table,
+                            preview.page + 1,
+                            first_row + 1,
+                            first_row + row_count
+                        )
+                    });
+                }
+                self.sql_query_result = rows;
+                self.sql_query_success_message = None;
+                self.sql_query_error = None;
+                self.reset_result_position();
+                self.detect_timestamp_columns(&query).await;
+            }
+            Err(err) => {
+                self.sql_query_error = Some(err.to_string());
+                self.clear_query_result();
+            }
+        }
+    }
+
+    /// `gg`: selects the first table or result row, depending on focus.
+    fn jump_to_top(&mut self) {
+        match self.current_focus {
+            FocusedWidget::TablesList => self.selected_table = 0,
+            FocusedWidget::QueryResult => self.move_result_selection_up(self.selected_result_row),
+            FocusedWidget::SqlEditor => {}
+        }
+    }
+
+    /// `G`: selects the last table or result row, depending on focus.
+    fn jump_to_bottom(&mut self) {
+        match self.current_focus {
+            FocusedWidget::TablesList => {
+                self.selected_table = self.visible_tables().len().saturating_sub(1)
+            }
+            FocusedWidget::QueryResult => {
+                self.move_result_selection_down(self.sql_query_result.len())
+            }
+            FocusedWidget::SqlEditor => {}
+        }
+    }
+
+    /// Moves the focused result row up, scrolling to keep it in view.
+    pub fn move_result_selection_up(&mut self, rows: usize) {
+        self.selected_result_row = self.selected_result_row.saturating_sub(rows);
+        self.result_scroll = self.result_scroll.min(self.selected_result_row);
+    }
+
+    /// Moves the focused result row down, scrolling to keep it in view.
+    pub fn move_result_selection_down(&mut self, rows: usize) {
+        let last_row = self.sql_query_result.len().saturating_sub(1);
+        self.selected_result_row = (self.selected_result_row + rows).min(last_row);
+        let first_visible =
+            (self.selected_result_row + 1).saturating_sub(self.result_viewport_rows);
+        self.result_scroll = self.result_scroll.max(first_visible);
+    }
+
+    pub fn move_selection_up(&mut self) {
+        if self.selected_table > 0 {
+            self.selected_table -= 1;
+        }
+    }
+
+    pub fn move_selection_down(&mut self) {
+        if self.selected_table < self.visible_tables().len().saturating_sub(1) {
+            self.selected_table += 1;
+        }
+    }
+}
+
+type ExportFn = fn(&Path, &[String], &[HashMap<String, Value>]) -> Result<(), DbError>;
+
+/// Timestamped file in the working directory for exporting the current result.
+fn export_path(extension: &str) -> PathBuf {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let file_name = format!("query_result_{}.{}", timestamp, extension);
+    env::current_dir()
+        .map(|dir| dir.join(&file_name))
+        .unwrap_or_else(|_| PathBuf::from(file_name))
+}
+
+/// Completes the last path segment of `input` against the filesystem.
+///
+/// A single match is completed in full (with a trailing `/` for directories);
+/// several matches are completed up to their longest common prefix.
+fn complete_path(input: &str) -> String {
+    let (dir_part, prefix) = match input.rfind('/') {
+        Some(idx) => (&input[..=idx], &input[idx + 1..]),
+        None => ("", input),
+    };
+
+    let search_dir = if dir_part.is_empty() {
+        PathBuf::from(".")
+    } else if let Some(rest) = dir_part.strip_prefix("~/") {
+        env::var_os("HOME")
+            .map(|home| PathBuf::from(home).join(rest))
+            .unwrap_or_else(|| PathBuf::from(dir_part))
+    } else {
+        PathBuf::from(dir_part)
+    };
+
+    let Ok(entries) = fs::read_dir(search_dir) else {
+        return input.to_string();
+    };
+
+    let mut matches: Vec<(String, bool)> = entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            name.starts_with(prefix).then_some((name, is_dir))
+        })
+        .collect();
+    matches.sort();
+
+    match matches.as_slice() {
+        [] => input.to_string(),
+        [(name, is_dir)] => {
+            let suffix = if *is_dir { "/" } else { "" };
+            format!("{}{}{}", dir_part, name, suffix)
+        }
+        [(first, _), rest @ ..] => {
+            let common = rest.iter().fold(first.as_str(), |common, (name, _)| {
+                let len = common
+                    .char_indices()
+                    .zip(name.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map_or(0, |((i, a), _)| i + a.len_utf8());
+                &common[..len]
+            });
+            format!("{}{}", dir_part, common)
+        }
+    }
+}
+
+/// Input that matters while the UI waits on the database.
+#[derive(Debug, PartialEq)]
+pub enum WaitInput {
+    /// Esc: stop waiting.
+    Cancel,
+    /// The quit key: stop waiting and leave dfox.
+    Quit,
+    /// The terminal changed size, so the screen is drawn afresh.
+    Resized,
+}
+
+/// Maps vim's h/j/k/l to the arrow keys so list and table navigation accepts both.
+fn vim_motion(key: KeyCode) -> KeyCode {
+    match key {
+        KeyCode::Char('h') => KeyCode::Left,
+        KeyCode::Char('j') => KeyCode::Down,
+        KeyCode::Char('k') => KeyCode::Up,
+        KeyCode::Char('l') => KeyCode::Right,
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use dfox_core::DbManager;
+
+    use super::*;
+    use crate::ui::sqlite_ui;
+
+    #[tokio::test]
+    async fn test_sqlite_file_is_offered_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saved.db");
+        let mut ui = sqlite_ui(&path).await;
+        ui.execute_sql_query("CREATE TABLE kept (id INTEGER)")
+            .await
+            .unwrap();
+
+        ui.current_screen = ScreenState::DbTypeSelection;
+        ui.connection_input.file_path.clear();
+        ui.selected_db_type = 2;
+        ui.handle_db_type_selection_input(KeyCode::Enter).await;
+        assert!(matches!(ui.current_screen, ScreenState::SavedConnections));
+        let saved = ui.saved_connections.for_type(DatabaseType::SQLite);
+        assert_eq!(saved.len(), 1);
+        assert!(saved[0].label().contains("saved.db"));
+
+        ui.handle_saved_connections_input(KeyCode::Enter).await;
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert_eq!(ui.tables, vec!["kept"]);
+    }
+
+    #[tokio::test]
+    async fn test_database_fetch_error_opens_dialog() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.databases = vec!["stale".to_string()];
+        ui.database_filter = "st".to_string();
+        ui.selected_database = 3;
+        ui.start_database_fetch();
+        assert!(ui.databases.is_empty());
+        assert!(ui.database_filter.is_empty());
+        assert_eq!(ui.selected_database, 3);
+
+        while ui
+            .database_fetch
+            .as_ref()
+            .is_some_and(|fetch| !fetch.is_finished())
+        {
+            tokio::task::yield_now().await;
+        }
+        ui.finish_database_fetch().await;
+        assert!(ui.database_fetch.is_none());
+        assert!(ui.databases.is_empty());
+        assert_eq!(
+            ui.connection_error_message.as_deref(),
+            Some("Error fetching databases: No database connection available.")
+        );
+    }
+
+    #[test]
+    fn test_result_filter_keeps_sort_and_restores_rows() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.sql_query_columns = vec!["name".to_string()];
+        ui.sql_query_result = ["pear", "apple", "Grape"]
+            .into_iter()
+            .map(|name| HashMap::from([("name".to_string(), Value::from(name))]))
+            .collect();
+        let names = |ui: &DatabaseClientUI| -> Vec<String> {
+            ui.sql_query_result
+                .iter()
+                .map(|row| cell_text(row, "name"))
+                .collect()
+        };
+
+        ui.sort_results_by_selected_column();
+        ui.set_result_filter("P".to_string());
+        assert_eq!(names(&ui), vec!["Grape", "apple", "pear"]);
+        ui.set_result_filter("pe".to_string());
+        assert_eq!(names(&ui), vec!["Grape", "pear"]);
+        assert_eq!(ui.unfiltered_result.as_ref().map(Vec::len), Some(3));
+
+        ui.set_result_filter(String::new());
+        assert_eq!(names(&ui), vec!["Grape", "apple", "pear"]);
+        assert!(ui.unfiltered_result.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_insert_statements_round_trip_through_the_editor() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+        ui.execute_sql_query("CREATE TABLE src (id INTEGER, note TEXT)")
+            .await
+            .unwrap();
+        ui.execute_sql_query("INSERT INTO src VALUES (1, 'it''s'), (2, NULL)")
+            .await
+            .unwrap();
+        ui.execute_sql_query("CREATE TABLE \"copy of src\" (id INTEGER, note TEXT)")
+            .await
+            .unwrap();
+        let (rows, _) = ui.execute_sql_query("SELECT * FROM src").await.unwrap();
+        ui.sql_query_result = rows;
+
+        ui.open_insert_export();
+        for c in "copy of src".chars() {
+            UIHandler::handle_insert_export_input(&mut ui, KeyCode::Char(c)).await;
+        }
+        UIHandler::handle_insert_export_input(&mut ui, KeyCode::Tab).await;
+        UIHandler::handle_insert_export_input(&mut ui, KeyCode::Enter).await;
+        assert!(ui.insert_export.is_none());
+        assert_eq!(
+            ui.sql_editor_content,
+            "INSERT INTO \"copy of src\" (\"id\", \"note\") VALUES (1, 'it''s');\n\
+             INSERT INTO \"copy of src\" (\"id\", \"note\") VALUES (2, NULL);\n"
+        );
+
+        let script = ui.sql_editor_content.clone();
+        for statement in sql::split_statements(&ui.db_type.db_type(), &script) {
+            ui.execute_sql_query(statement).await.unwrap();
+        }
+        let (copied, _) = ui
+            .execute_sql_query("SELECT * FROM \"copy of src\" WHERE note = 'it''s'")
+            .await
+            .unwrap();
+        assert_eq!(copied.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_script_runs_statements_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+
+        let (rows, message) = ui
+            .execute_script(
+                "CREATE TABLE t (name TEXT); INSERT INTO t VALUES ('a;b'); SELECT name FROM t;",
+            )
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(ui.sql_query_columns, vec!["name"]);
+        assert_eq!(message.as_deref(), Some("Ran 3 statements."));
+        assert_eq!(
+            ui.script_log[1],
+            "2. INSERT INTO t VALUES ('a;b') — Query OK, 1 row affected."
+        );
+
+        ui.script_log.clear();
+        let err = ui
+            .execute_script("INSERT INTO missing VALUES (1); SELECT 1")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().starts_with("Statement 1 of 2 failed"));
+        assert_eq!(ui.script_log[1], "2. SELECT 1 — skipped");
+    }
+
+    #[tokio::test]
+    async fn test_transaction_badge_follows_begin_and_rollback() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+
+        ui.begin_transaction().await;
+        assert!(ui.transaction_active);
+        ui.rollback_transaction().await;
+        assert!(!ui.transaction_active);
+
+        // A failed commit leaves nothing open either.
+        ui.commit_transaction().await;
+        assert!(ui.sql_query_error.is_some());
+        assert!(!ui.transaction_active);
+    }
+
+    #[tokio::test]
+    async fn test_explain_runs_in_the_open_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("x.db")).await;
+        ui.begin_transaction().await;
+        // Temporary tables only exist on the connection that made them.
+        ui.execute_sql_query("CREATE TEMP TABLE scratch (id INTEGER)")
+            .await
+            .unwrap();
+
+        ui.sql_editor_content = "SELECT * FROM scratch".to_string();
+        ui.explain_editor_query().await;
+        assert_eq!(ui.sql_query_error, None);
+        assert!(!ui.sql_query_result.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_replaces_the_connection_and_drops_the_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("r.db")).await;
+        ui.execute_sql_query("CREATE TABLE kept (id INTEGER)")
+            .await
+            .unwrap();
+        ui.begin_transaction().await;
+        ui.execute_sql_query("INSERT INTO kept VALUES (1)")
+            .await
+            .unwrap();
+
+        ui.reconnect().await.unwrap();
+        assert!(!ui.transaction_active);
+        assert!(!ui.db_manager.in_transaction().await);
+        assert_eq!(ui.db_manager.connections.lock().await.len(), 1);
+        let (rows, _) = ui.execute_sql_query("SELECT * FROM kept").await.unwrap();
+        assert!(rows.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_alter_is_shown_after_reexpanding() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+        ui.execute_script("CREATE TABLE t (id INTEGER)")
+            .await
+            .unwrap();
+        ui.tables = vec!["t".to_string()];
+
+        ui.expand_selected_table().await;
+        assert_eq!(ui.table_schemas["t"].columns.len(), 1);
+
+        ui.execute_script("ALTER TABLE t ADD COLUMN name TEXT")
+            .await
+            .unwrap();
+        assert!(!ui.expanded_tables.contains("t"));
+
+        ui.expand_selected_table().await;
+        let columns: Vec<&str> = ui.table_schemas["t"]
+            .columns
+            .iter()
+            .map(|column| column.name.as_str())
+            .collect();
+        assert_eq!(columns, vec!["id", "name"]);
+    }
+
+    #[test]
+    fn test_q_quits_only_screens_without_text_entry() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        let q = KeyCode::Char('q');
+        assert!(ui.is_quit_key(q, KeyModifiers::NONE));
+        assert!(!ui.is_quit_key(q, KeyModifiers::CONTROL));
+
+        ui.current_screen = ScreenState::DatabaseSelection;
+        assert!(ui.is_quit_key(q, KeyModifiers::NONE));
+        ui.editing_database_filter = true;
+        assert!(!ui.is_quit_key(q, KeyModifiers::NONE));
+        ui.editing_database_filter = false;
+
+        for screen in [
+            ScreenState::ConnectionInput,
+            ScreenState::SqliteFileInput,
+            ScreenState::TableView,
+        ] {
+            ui.current_screen = screen;
+            assert!(!ui.is_quit_key(q, KeyModifiers::NONE));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_database_filter_narrows_the_selection() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::DatabaseSelection;
+        ui.databases = ["analytics", "orders_db", "orders_archive", "users"]
+            .map(String::from)
+            .to_vec();
+
+        for key in [KeyCode::Char('/'), KeyCode::Char('o'), KeyCode::Char('d')] {
+            UIHandler::handle_database_selection_input(&mut ui, key)
+                .await
+                .unwrap();
+        }
+        assert_eq!(ui.filtered_databases(), vec!["orders_db", "orders_archive"]);
+
+        // Up/Down move within the matches only.
+        for _ in 0..3 {
+            UIHandler::handle_database_selection_input(&mut ui, KeyCode::Down)
+                .await
+                .unwrap();
+        }
+        assert_eq!(ui.selected_database, 1);
+
+        UIHandler::handle_database_selection_input(&mut ui, KeyCode::Char('b'))
+            .await
+            .unwrap();
+        assert_eq!(ui.filtered_databases(), vec!["orders_db"]);
+        assert_eq!(ui.selected_database, 0);
+
+        UIHandler::handle_database_selection_input(&mut ui, KeyCode::Esc)
+            .await
+            .unwrap();
+        assert!(!ui.editing_database_filter);
+        assert_eq!(ui.filtered_databases().len(), 4);
+    }
+
+    #[test]
+    fn test_table_selection_stays_within_tables() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.databases = (0..5).map(|i| format!("db{}", i)).collect();
+        ui.tables = vec!["orders".to_string(), "users".to_string()];
+
+        for _ in 0..4 {
+            ui.move_selection_down();
+        }
+        assert_eq!(ui.selected_table, 1);
+
+        for _ in 0..4 {
+            ui.move_selection_up();
+        }
+        assert_eq!(ui.selected_table, 0);
+
+        // Only the filtered tables are reachable.
+        ui.set_table_filter("user".to_string());
+        ui.move_selection_down();
+        assert_eq!(ui.selected_table, 0);
     }
 }
@@ -0,0 +1,90 @@
+use super::{components::ScreenState, keymap::Action, DatabaseClientUI};
+
+/// Keys of the table view that the keymap doesn't cover.
+const TABLE_VIEW_NAVIGATION: &[(&str, &str)] = &[
+    ("↑/↓, k/j", "Move through tables or result rows"),
+    (
+        "→/←, l/h",
+        "Expand / collapse a table, or move between result columns",
+    ),
+    ("Enter", "Toggle a table, or show the focused cell in full"),
+    ("gg / G", "Jump to the first / last table or result row"),
+    ("PgUp/PgDn", "Scroll the results a page"),
+    ("Shift+arrows", "Select text in the editor"),
+    (
+        "Ctrl+U / Ctrl+W",
+        "Delete to the line start / the previous word",
+    ),
+    ("Ctrl+L", "Clear the editor"),
+    ("Ctrl+Z / Ctrl+Y", "Undo / redo an edit in the editor"),
+    ("↑/↓ in the editor", "Recall earlier queries"),
+];
+
+impl DatabaseClientUI {
+    /// Keys listed in the `?` help overlay for the current screen, as
+    /// `(keys, description)` pairs.
+    pub fn help_entries(&self) -> Vec<(String, &'static str)> {
+        let fixed = |entries: &[(&str, &'static str)]| -> Vec<(String, &'static str)> {
+            entries
+                .iter()
+                .map(|(keys, description)| (keys.to_string(), *description))
+                .collect()
+        };
+
+        match self.current_screen {
+            ScreenState::DbTypeSelection => fixed(&[
+                ("↑/↓, k/j", "Choose a database type"),
+                ("Enter", "Continue"),
+                ("Esc", "Back to the open connection, when adding another"),
+                ("?", "Show this help"),
+                ("q", "Quit"),
+            ]),
+            ScreenState::SavedConnections => fixed(&[
+                ("↑/↓, k/j", "Choose a saved connection"),
+                ("Enter", "Connect, or enter new details"),
+                ("Delete", "Forget the selected connection"),
+                ("Esc", "Back to database types"),
+                ("?", "Show this help"),
+                ("q", "Quit"),
+            ]),
+            ScreenState::DatabaseSelection => fixed(&[
+                ("↑/↓, k/j", "Choose a database"),
+                ("Enter", "Open the database"),
+                ("/", "Filter the databases by typed characters"),
+                ("?", "Show this help"),
+                ("q", "Quit"),
+            ]),
+            ScreenState::TableView => {
+                let mut entries = fixed(TABLE_VIEW_NAVIGATION);
+                entries.extend(
+                    Action::ALL.map(|action| (self.keymap.label(action), action.description())),
+                );
+                entries
+            }
+            // `?` is typed into these screens' fields, so they have no overlay.
+            ScreenState::ConnectionInput | ScreenState::SqliteFileInput => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::Arc};
+
+    use dfox_core::DbManager;
+
+    use super::*;
+    use crate::ui::keymap::{KeyBindings, Keymap};
+
+    #[test]
+    fn test_table_view_help_follows_the_keymap() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.current_screen = ScreenState::TableView;
+        let overrides: HashMap<Action, KeyBindings> = toml::from_str("quit = \"ctrl+q\"").unwrap();
+        ui.keymap = Keymap::new(overrides);
+
+        let entries = ui.help_entries();
+        assert!(entries.contains(&("Ctrl+Q".to_string(), "Quit")));
+        assert!(entries.contains(&("F5/Ctrl+E".to_string(), Action::Execute.description())));
+    }
+}
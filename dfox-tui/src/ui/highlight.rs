@@ -0,0 +1,268 @@
+use std::ops::Range;
+
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+use super::theme::Theme;
+
+/// Words colored as keywords in the SQL editor, matched case-insensitively.
+const KEYWORDS: &[&str] = &[
+    "ADD",
+    "ALL",
+    "ALTER",
+    "AND",
+    "AS",
+    "ASC",
+    "BEGIN",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "COLUMN",
+    "COMMIT",
+    "CONSTRAINT",
+    "CREATE",
+    "CROSS",
+    "DATABASE",
+    "DEFAULT",
+    "DELETE",
+    "DESC",
+    "DISTINCT",
+    "DROP",
+    "ELSE",
+    "END",
+    "EXISTS",
+    "EXPLAIN",
+    "FALSE",
+    "FOREIGN",
+    "FROM",
+    "FULL",
+    "GROUP",
+    "HAVING",
+    "IN",
+    "INDEX",
+    "INNER",
+    "INSERT",
+    "INTO",
+    "IS",
+    "JOIN",
+    "KEY",
+    "LEFT",
+    "LIKE",
+    "LIMIT",
+    "NOT",
+    "NULL",
+    "OFFSET",
+    "ON",
+    "OR",
+    "ORDER",
+    "OUTER",
+    "PRIMARY",
+    "REFERENCES",
+    "RETURNING",
+    "RIGHT",
+    "ROLLBACK",
+    "SELECT",
+    "SET",
+    "TABLE",
+    "THEN",
+    "TRUE",
+    "TRUNCATE",
+    "UNION",
+    "UNIQUE",
+    "UPDATE",
+    "USE",
+    "USING",
+    "VALUES",
+    "VIEW",
+    "WHEN",
+    "WHERE",
+    "WITH",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TokenKind {
+    Keyword,
+    String,
+    Number,
+    Comment,
+    Other,
+}
+
+impl TokenKind {
+    fn style(self, theme: &Theme) -> Style {
+        match self {
+            TokenKind::Keyword => Style::default()
+                .fg(theme.keyword)
+                .add_modifier(Modifier::BOLD),
+            TokenKind::String => Style::default().fg(theme.string),
+            TokenKind::Number => Style::default().fg(theme.number),
+            TokenKind::Comment => Style::default()
+                .fg(theme.muted)
+                .add_modifier(Modifier::ITALIC),
+            TokenKind::Other => Style::default().fg(theme.text),
+        }
+    }
+}
+
+/// Splits `sql` into styled lines for the SQL editor.
+pub fn highlight_sql(sql: &str, theme: &Theme) -> Vec<Line<'static>> {
+    let mut lines = vec![Line::default()];
+    for (kind, text) in tokenize(sql) {
+        // Strings and block comments may span several lines.
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Line::default());
+            }
+            if !part.is_empty() {
+                if let Some(line) = lines.last_mut() {
+                    line.push_span(Span::styled(part.to_string(), kind.style(theme)));
+                }
+            }
+        }
+    }
+    lines
+}
+
+/// Shows the `selection` byte range of the highlighted text in reverse video.
+pub fn highlight_selection(
+    lines: Vec<Line<'static>>,
+    selection: Range<usize>,
+) -> Vec<Line<'static>> {
+    let mut offset = 0;
+    lines
+        .into_iter()
+        .map(|line| {
+            let mut spans = Vec::new();
+            for span in line.spans {
+                let text = span.content.as_ref();
+                let start = selection.start.clamp(offset, offset + text.len()) - offset;
+                let end = selection.end.clamp(offset, offset + text.len()) - offset;
+                offset += text.len();
+
+                for (range, selected) in [
+                    (0..start, false),
+                    (start..end, true),
+                    (end..text.len(), false),
+                ] {
+                    if range.is_empty() {
+                        continue;
+                    }
+                    let style = if selected {
+                        span.style.add_modifier(Modifier::REVERSED)
+                    } else {
+                        span.style
+                    };
+                    spans.push(Span::styled(text[range].to_string(), style));
+                }
+            }
+            // The line break between lines.
+            offset += 1;
+            Line::from(spans)
+        })
+        .collect()
+}
+
+fn tokenize(sql: &str) -> Vec<(TokenKind, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+
+    while let Some(c) = sql[start..].chars().next() {
+        let rest = &sql[start..];
+        let (kind, len) = if rest.starts_with("--") {
+            (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()))
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let len = body.find("*/").map_or(rest.len(), |end| end + 4);
+            (TokenKind::Comment, len)
+        } else if let '\'' | '"' | '`' = c {
+            let len = rest[1..].find(c).map_or(rest.len(), |end| end + 2);
+            // Only single quotes are string literals; the others quote identifiers.
+            let kind = if c == '\'' {
+                TokenKind::String
+            } else {
+                TokenKind::Other
+            };
+            (kind, len)
+        } else if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '.'))
+                .unwrap_or(rest.len());
+            (TokenKind::Number, len)
+        } else if c.is_alphabetic() || c == '_' {
+            let len = rest
+                .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .unwrap_or(rest.len());
+            let word = rest[..len].to_ascii_uppercase();
+            let kind = if KEYWORDS.contains(&word.as_str()) {
+                TokenKind::Keyword
+            } else {
+                TokenKind::Other
+            };
+            (kind, len)
+        } else {
+            (TokenKind::Other, c.len_utf8())
+        };
+
+        tokens.push((kind, &rest[..len]));
+        start += len;
+    }
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn highlighted(sql: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(sql)
+            .into_iter()
+            .filter(|(kind, _)| *kind != TokenKind::Other)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_query() {
+        assert_eq!(
+            highlighted("select name from users where id = 42 and note = 'a -- b' -- done"),
+            vec![
+                (TokenKind::Keyword, "select"),
+                (TokenKind::Keyword, "from"),
+                (TokenKind::Keyword, "where"),
+                (TokenKind::Number, "42"),
+                (TokenKind::Keyword, "and"),
+                (TokenKind::String, "'a -- b'"),
+                (TokenKind::Comment, "-- done"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quoted_identifiers_and_words_are_not_keywords() {
+        assert!(highlighted(r#"SELECT "from", select_count, t1 FROM t"#)
+            .iter()
+            .all(|(_, text)| matches!(*text, "SELECT" | "FROM")));
+    }
+
+    #[test]
+    fn test_selection_splits_spans() {
+        let lines = highlight_selection(highlight_sql("SELECT 1\nFROM t", &Theme::DARK), 4..11);
+        let selected: Vec<&str> = lines
+            .iter()
+            .flat_map(|line| &line.spans)
+            .filter(|span| span.style.add_modifier.contains(Modifier::REVERSED))
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(selected, vec!["CT", " ", "1", "FR"]);
+    }
+
+    #[test]
+    fn test_multiline_tokens_split_into_lines() {
+        let lines = highlight_sql("/* a\nb */ SELECT\n1", &Theme::DARK);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].spans[0].content, "b */");
+        assert_eq!(lines[2].spans[0].content, "1");
+    }
+}
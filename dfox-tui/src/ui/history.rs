@@ -0,0 +1,151 @@
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Oldest entries are dropped once the history grows past this many queries.
+const MAX_HISTORY: usize = 500;
+
+/// Executed queries, oldest first, with a cursor for Up/Down recall.
+#[derive(Default)]
+pub struct QueryHistory {
+    entries: Vec<String>,
+    /// Entry currently recalled into the editor, if browsing.
+    position: Option<usize>,
+    /// Editor content from before browsing started, restored past the newest entry.
+    draft: String,
+}
+
+impl QueryHistory {
+    /// Loads the history saved by previous sessions, starting empty if there is none.
+    pub fn load() -> Self {
+        let entries = history_file()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| parse_entries(&content))
+            .unwrap_or_default();
+
+        Self {
+            entries,
+            ..Self::default()
+        }
+    }
+
+    /// Records an executed query and stops browsing.
+    ///
+    /// Repeating the most recent query does not add a duplicate entry.
+    pub fn push(&mut self, query: &str) {
+        self.position = None;
+        self.draft.clear();
+
+        let query = query.trim();
+        if self.entries.last().map(String::as_str) == Some(query) {
+            return;
+        }
+
+        self.entries.push(query.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+    }
+
+    /// Steps to the previous (older) query, remembering `current` as the draft.
+    pub fn previous(&mut self, current: &str) -> Option<&str> {
+        let index = match self.position {
+            None if self.entries.is_empty() => return None,
+            None => {
+                self.draft = current.to_string();
+                self.entries.len() - 1
+            }
+            Some(0) => 0,
+            Some(index) => index - 1,
+        };
+        self.position = Some(index);
+        Some(&self.entries[index])
+    }
+
+    /// Steps to the next (newer) query, or back to the draft past the newest one.
+    pub fn next(&mut self) -> Option<&str> {
+        let index = self.position?;
+        if index + 1 < self.entries.len() {
+            self.position = Some(index + 1);
+            Some(&self.entries[index + 1])
+        } else {
+            self.position = None;
+            Some(&self.draft)
+        }
+    }
+
+    /// Writes the history so the next session can recall it.
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = history_file() else {
+            return Ok(());
+        };
+        write_entries(&path, &self.entries)
+    }
+}
+
+/// `<data dir>/dfox/history`, e.g. `~/.local/share/dfox/history` on Linux.
+fn history_file() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("dfox").join("history"))
+}
+
+/// Each line holds one query as a JSON string, so multiline queries survive.
+fn parse_entries(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+fn write_entries(path: &Path, entries: &[String]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = fs::File::create(path)?;
+    for entry in entries {
+        writeln!(file, "{}", serde_json::Value::String(entry.clone()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browse_back_and_forward_restores_draft() {
+        let mut history = QueryHistory::default();
+        history.push("SELECT 1");
+        history.push("SELECT 2");
+
+        assert_eq!(history.previous("SEL"), Some("SELECT 2"));
+        assert_eq!(history.previous("SELECT 2"), Some("SELECT 1"));
+        assert_eq!(history.previous("SELECT 1"), Some("SELECT 1"));
+        assert_eq!(history.next(), Some("SELECT 2"));
+        assert_eq!(history.next(), Some("SEL"));
+        assert_eq!(history.next(), None);
+    }
+
+    #[test]
+    fn test_repeated_query_is_not_duplicated() {
+        let mut history = QueryHistory::default();
+        history.push("SELECT 1");
+        history.push("SELECT 1\n");
+        assert_eq!(history.entries, vec!["SELECT 1"]);
+    }
+
+    #[test]
+    fn test_multiline_entries_round_trip() {
+        let dir = std::env::temp_dir().join(format!("dfox-history-{}", std::process::id()));
+        let path = dir.join("history");
+        let entries = vec!["SELECT *\nFROM users".to_string(), "SELECT 2".to_string()];
+
+        write_entries(&path, &entries).unwrap();
+        let loaded = parse_entries(&fs::read_to_string(&path).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded, entries);
+    }
+}
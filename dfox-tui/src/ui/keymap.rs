@@ -0,0 +1,402 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+
+/// Table view commands whose keys can be changed in the config file.
+///
+/// Navigation (arrow keys, h/j/k/l, gg/G, Enter) stays fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Action {
+    Quit,
+    CycleFocus,
+    Execute,
+    ExecuteFresh,
+    Explain,
+    OpenInEditor,
+    DatabaseSelection,
+    SwitchDatabase,
+    SwitchConnection,
+    ServerSessions,
+    ConnectionInfo,
+    ToggleTotals,
+    BeginTransaction,
+    CommitTransaction,
+    RollbackTransaction,
+    Refresh,
+    Preview,
+    NextPage,
+    PreviousPage,
+    /// Was `filter-tables` before it applied to the results too.
+    #[serde(alias = "filter-tables")]
+    Filter,
+    ExportCsv,
+    ExportJson,
+    ExportInserts,
+    ExportMarkdown,
+    CopyCell,
+    CopyRow,
+    SortResults,
+    ToggleNullsOrder,
+    PinColumn,
+    ShowDdl,
+    DescribeTable,
+    ToggleObjects,
+    CallRoutine,
+    Help,
+}
+
+impl Action {
+    pub const ALL: [Action; 34] = [
+        Action::Quit,
+        Action::CycleFocus,
+        Action::Execute,
+        Action::ExecuteFresh,
+        Action::Explain,
+        Action::OpenInEditor,
+        Action::DatabaseSelection,
+        Action::SwitchDatabase,
+        Action::SwitchConnection,
+        Action::ServerSessions,
+        Action::ConnectionInfo,
+        Action::ToggleTotals,
+        Action::BeginTransaction,
+        Action::CommitTransaction,
+        Action::RollbackTransaction,
+        Action::Refresh,
+        Action::Preview,
+        Action::NextPage,
+        Action::PreviousPage,
+        Action::Filter,
+        Action::ExportCsv,
+        Action::ExportJson,
+        Action::ExportInserts,
+        Action::ExportMarkdown,
+        Action::CopyCell,
+        Action::CopyRow,
+        Action::SortResults,
+        Action::ToggleNullsOrder,
+        Action::PinColumn,
+        Action::ShowDdl,
+        Action::DescribeTable,
+        Action::ToggleObjects,
+        Action::CallRoutine,
+        Action::Help,
+    ];
+
+    fn default_keys(self) -> &'static [&'static str] {
+        match self {
+            Action::Quit => &["esc"],
+            Action::CycleFocus => &["tab"],
+            Action::Execute => &["f5", "ctrl+e"],
+            Action::ExecuteFresh => &["ctrl+f5"],
+            Action::Explain => &["f6"],
+            Action::OpenInEditor => &["ctrl+o"],
+            Action::DatabaseSelection => &["f1"],
+            Action::SwitchDatabase => &["f2"],
+            Action::SwitchConnection => &["f4"],
+            Action::ServerSessions => &["f10"],
+            Action::ConnectionInfo => &["f12"],
+            Action::ToggleTotals => &["f3"],
+            Action::BeginTransaction => &["f7"],
+            Action::CommitTransaction => &["f8"],
+            Action::RollbackTransaction => &["f9"],
+            Action::Refresh => &["r"],
+            Action::Preview => &["p"],
+            Action::NextPage => &["]"],
+            Action::PreviousPage => &["["],
+            Action::Filter => &["/"],
+            Action::ExportCsv => &["c"],
+            Action::ExportJson => &["J"],
+            Action::ExportInserts => &["I"],
+            Action::ExportMarkdown => &["M"],
+            Action::CopyCell => &["y"],
+            Action::CopyRow => &["Y"],
+            Action::SortResults => &["s"],
+            Action::ToggleNullsOrder => &["n"],
+            Action::PinColumn => &["P"],
+            Action::ShowDdl => &["d"],
+            Action::DescribeTable => &["i"],
+            Action::ToggleObjects => &["o"],
+            Action::CallRoutine => &["e"],
+            Action::Help => &["?"],
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Action::Quit => "Quit",
+            Action::CycleFocus => "Focus the next panel (in the editor, complete a name first)",
+            Action::Execute => "Run the editor query, or just the selected text",
+            Action::ExecuteFresh => {
+                "Run the editor query on the server even if its result is cached"
+            }
+            Action::Explain => "Show the query plan",
+            Action::OpenInEditor => "Edit the query in $VISUAL / $EDITOR",
+            Action::DatabaseSelection => "Return to database selection",
+            Action::SwitchDatabase => "Switch database",
+            Action::SwitchConnection => "Switch to another open connection, or open one",
+            Action::ServerSessions => "List the server's sessions, to terminate one",
+            Action::ConnectionInfo => "Show the server version, user, encoding and settings",
+            Action::ToggleTotals => "Toggle result totals",
+            Action::BeginTransaction => "Begin a transaction",
+            Action::CommitTransaction => "Commit the transaction",
+            Action::RollbackTransaction => "Roll back the transaction",
+            Action::Refresh => "Reload the tables list and schemas",
+            Action::Preview => "Preview the selected table's rows",
+            Action::NextPage => "Next preview page",
+            Action::PreviousPage => "Previous preview page",
+            Action::Filter => "Filter the tables, or the result rows",
+            Action::ExportCsv => "Export results as CSV",
+            Action::ExportJson => "Export results as JSON",
+            Action::ExportInserts => "Write results as INSERT statements, to a file or the editor",
+            Action::ExportMarkdown => "Export results as a Markdown table, also copied",
+            Action::CopyCell => "Copy the focused cell",
+            Action::CopyRow => "Copy the focused row",
+            Action::SortResults => "Sort the results by the focused column, again to reverse",
+            Action::ToggleNullsOrder => "Sort NULLs last instead of first, or back",
+            Action::PinColumn => "Pin the focused column on the left, or unpin it",
+            Action::ShowDdl => "Show the selected table's CREATE TABLE statement",
+            Action::DescribeTable => "Show the selected table's schema full screen",
+            Action::ToggleObjects => {
+                "Show or hide views, materialized views, sequences and routines"
+            }
+            Action::CallRoutine => "Call the selected function or procedure, asking for arguments",
+            Action::Help => "Show this help",
+        }
+    }
+}
+
+/// A key with its Ctrl/Alt modifiers, written like `f5`, `ctrl+e` or `J`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// A character without Ctrl or Alt, which the SQL editor takes as typing.
+    fn is_plain_char(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+
+    /// Shift is left out, since it is already part of the character or key
+    /// (`J`, `BackTab`) crossterm reports.
+    fn matches(&self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        self.code == code
+            && self.modifiers == modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT)
+    }
+}
+
+impl FromStr for KeyBinding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (modifier_names, key) = match s.rsplit_once('+') {
+            Some((modifier_names, key)) if !key.is_empty() => (modifier_names, key),
+            _ => ("", s),
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in modifier_names.split('+').filter(|name| !name.is_empty()) {
+            match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
+                "alt" => modifiers |= KeyModifiers::ALT,
+                _ => return Err(format!("Unknown modifier in key '{}'", s)),
+            }
+        }
+
+        let lower = key.to_ascii_lowercase();
+        let code = match lower.as_str() {
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "space" => KeyCode::Char(' '),
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            _ => match (lower.strip_prefix('f'), key.chars().count()) {
+                (Some(number), _) if !number.is_empty() => number
+                    .parse()
+                    .ok()
+                    .filter(|n| (1..=24).contains(n))
+                    .map(KeyCode::F)
+                    .ok_or_else(|| format!("Unknown key '{}'", s))?,
+                (_, 1) if modifiers.is_empty() => KeyCode::Char(key.chars().next().unwrap_or(' ')),
+                // Terminals report Ctrl+E as a lowercase `e`.
+                (_, 1) => KeyCode::Char(lower.chars().next().unwrap_or(' ')),
+                _ => return Err(format!("Unknown key '{}'", s)),
+            },
+        };
+
+        Ok(KeyBinding { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeyBinding {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            write!(f, "Alt+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) if self.modifiers.is_empty() => write!(f, "{}", c),
+            KeyCode::Char(c) => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// One key or a list of keys for an action in the config file.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBindings {
+    One(KeyBinding),
+    Many(Vec<KeyBinding>),
+}
+
+/// Keys bound to each table view [`Action`].
+#[derive(Debug)]
+pub struct Keymap {
+    bindings: HashMap<Action, Vec<KeyBinding>>,
+}
+
+impl Keymap {
+    /// The default keys, with those of the actions in `overrides` replaced.
+    pub fn new(overrides: HashMap<Action, KeyBindings>) -> Self {
+        let mut bindings: HashMap<Action, Vec<KeyBinding>> = Action::ALL
+            .iter()
+            .map(|&action| {
+                let keys = action
+                    .default_keys()
+                    .iter()
+                    .filter_map(|key| key.parse().ok())
+                    .collect();
+                (action, keys)
+            })
+            .collect();
+        for (action, keys) in overrides {
+            let keys = match keys {
+                KeyBindings::One(key) => vec![key],
+                KeyBindings::Many(keys) => keys,
+            };
+            bindings.insert(action, keys);
+        }
+        Keymap { bindings }
+    }
+
+    /// The action bound to a key press, if any.
+    ///
+    /// While `typing` (the SQL editor has focus), plain characters are left for
+    /// the editor, so binding `q` to quit doesn't stop it being typed.
+    pub fn action(&self, code: KeyCode, modifiers: KeyModifiers, typing: bool) -> Option<Action> {
+        Action::ALL.into_iter().find(|action| {
+            self.keys(*action)
+                .iter()
+                .any(|key| key.matches(code, modifiers) && !(typing && key.is_plain_char()))
+        })
+    }
+
+    fn keys(&self, action: Action) -> &[KeyBinding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// The keys bound to `action` for help lines, e.g. `F5/Ctrl+E`.
+    pub fn label(&self, action: Action) -> String {
+        let keys: Vec<String> = self
+            .keys(action)
+            .iter()
+            .map(KeyBinding::to_string)
+            .collect();
+        keys.join("/")
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Keymap::new(HashMap::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_display_keys() {
+        let key: KeyBinding = "ctrl+E".parse().unwrap();
+        assert_eq!(key.code, KeyCode::Char('e'));
+        assert_eq!(key.modifiers, KeyModifiers::CONTROL);
+        assert_eq!(key.to_string(), "Ctrl+E");
+
+        let key: KeyBinding = "F12".parse().unwrap();
+        assert_eq!(key.code, KeyCode::F(12));
+        assert_eq!(key.to_string(), "F12");
+        assert_eq!("J".parse::<KeyBinding>().unwrap().to_string(), "J");
+        assert_eq!("+".parse::<KeyBinding>().unwrap().code, KeyCode::Char('+'));
+        assert!("hyper+x".parse::<KeyBinding>().is_err());
+        assert!("f99".parse::<KeyBinding>().is_err());
+    }
+
+    #[test]
+    fn test_overrides_replace_default_keys() {
+        let overrides: HashMap<Action, KeyBindings> =
+            toml::from_str("quit = \"q\"\nexecute = [\"ctrl+r\"]").unwrap();
+        let keymap = Keymap::new(overrides);
+
+        assert_eq!(
+            keymap.action(KeyCode::Char('q'), KeyModifiers::NONE, false),
+            Some(Action::Quit)
+        );
+        assert_eq!(keymap.action(KeyCode::Esc, KeyModifiers::NONE, false), None);
+        assert_eq!(keymap.label(Action::Execute), "Ctrl+R");
+        assert_eq!(keymap.label(Action::Refresh), "r");
+
+        let overrides: HashMap<Action, KeyBindings> =
+            toml::from_str("filter-tables = \"f\"").unwrap();
+        assert_eq!(Keymap::new(overrides).label(Action::Filter), "f");
+    }
+
+    #[test]
+    fn test_typing_leaves_plain_characters_to_the_editor() {
+        let keymap = Keymap::default();
+        assert_eq!(
+            keymap.action(KeyCode::Char('r'), KeyModifiers::NONE, true),
+            None
+        );
+        assert_eq!(
+            keymap.action(KeyCode::Char('e'), KeyModifiers::CONTROL, true),
+            Some(Action::Execute)
+        );
+        assert_eq!(
+            keymap.action(KeyCode::Char('Y'), KeyModifiers::SHIFT, false),
+            Some(Action::CopyRow)
+        );
+    }
+}
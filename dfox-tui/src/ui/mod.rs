@@ -1,24 +1,64 @@
+mod batch;
+mod clipboard;
+mod completion;
 mod components;
+mod config;
+mod connection;
+mod diagnostics;
+mod editor;
 mod handlers;
+mod help;
+mod highlight;
+mod history;
+mod keymap;
+mod query_task;
+mod result_cache;
+mod results;
+mod routines;
+mod saved_connections;
 mod screens;
+mod server_sessions;
+mod sessions;
+mod tables;
+mod theme;
+mod timestamps;
 
 use std::io;
 
-pub use components::DatabaseClientUI;
-use crossterm::event::{KeyCode, KeyModifiers};
-use dfox_core::models::schema::TableSchema;
+pub use components::{DatabaseClientUI, DatabaseType};
+pub use config::Config;
+use crossterm::event::{KeyCode, KeyModifiers, MouseEvent};
+pub use handlers::WaitInput;
 use ratatui::{prelude::CrosstermBackend, Terminal};
 
 pub trait UIHandler {
-    async fn handle_message_popup_input(&mut self);
     async fn handle_db_type_selection_input(&mut self, key: KeyCode);
-    async fn handle_input_event(&mut self, key: KeyCode) -> io::Result<()>;
+    async fn handle_saved_connections_input(&mut self, key: KeyCode);
+    async fn handle_sqlite_file_input(&mut self, key: KeyCode);
+    async fn handle_input_event(&mut self, key: KeyCode, modifiers: KeyModifiers)
+        -> io::Result<()>;
     async fn handle_database_selection_input(&mut self, key: KeyCode) -> io::Result<()>;
-    async fn handle_table_view_input(
+    async fn handle_database_filter_input(&mut self, key: KeyCode);
+    async fn handle_database_switcher_input(&mut self, key: KeyCode);
+    async fn handle_session_switcher_input(&mut self, key: KeyCode);
+    async fn handle_server_sessions_input(&mut self, key: KeyCode);
+    async fn handle_confirmation_input(
         &mut self,
         key: KeyCode,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     );
+    async fn handle_table_filter_input(&mut self, key: KeyCode);
+    async fn handle_result_filter_input(&mut self, key: KeyCode);
+    async fn handle_insert_export_input(&mut self, key: KeyCode);
+    async fn handle_routine_call_input(&mut self, key: KeyCode);
+    async fn handle_cell_detail_input(&mut self, key: KeyCode);
+    async fn handle_schema_view_input(&mut self, key: KeyCode);
+    async fn handle_connection_info_input(&mut self, key: KeyCode);
+    async fn handle_help_input(&mut self, key: KeyCode);
+    async fn handle_mouse_input(&mut self, mouse: MouseEvent);
+    /// Returns `false` when the key should also reach the SQL editor.
+    async fn handle_completion_input(&mut self, key: KeyCode) -> bool;
+    async fn handle_table_view_input(&mut self, key: KeyCode);
     async fn handle_sql_editor_input(
         &mut self,
         key: KeyCode,
@@ -28,7 +68,11 @@ pub trait UIHandler {
 }
 
 pub trait UIRenderer {
-    async fn render_message_popup(
+    async fn render_saved_connections_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()>;
+    async fn render_sqlite_file_input_screen(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()>;
@@ -48,9 +92,20 @@ pub trait UIRenderer {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()>;
-    async fn render_table_schema(
-        &self,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-        table_schema: &TableSchema,
-    ) -> io::Result<()>;
+}
+
+/// Opens a UI on a SQLite file at `path`, creating it if needed, the way the
+/// SQLite file screen does.
+#[cfg(test)]
+pub async fn sqlite_ui(path: &std::path::Path) -> DatabaseClientUI {
+    let mut ui = DatabaseClientUI::new(std::sync::Arc::new(dfox_core::DbManager::new()));
+    ui.db_type = DatabaseType::SQLite;
+    ui.connection_input.file_path = format!("{}?mode=rwc", path.display());
+    ui.connect_from_input().await;
+    assert!(
+        ui.connection_error_message.is_none(),
+        "{:?}",
+        ui.connection_error_message
+    );
+    ui
 }
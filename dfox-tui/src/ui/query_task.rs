@@ -0,0 +1,146 @@
+use std::{future::Future, time::Instant};
+
+use dfox_core::{models::connections::DbType, notices, sql, DbManager};
+use tokio::{sync::mpsc::UnboundedSender, task::JoinHandle};
+
+use crate::db::{is_connection_lost, run_statement, StatementOutcome};
+
+/// Progress reported by a script running on the background query task.
+pub enum QueryEvent {
+    /// A statement succeeded, so any DDL in it has taken effect.
+    Ran(String),
+    /// A `script_log` line for one statement of a multi-statement script.
+    Logged(String),
+    /// A NOTICE or WARNING the server sent while a statement ran.
+    Notice(String),
+    /// The connection was gone before any statement ran, so the script can
+    /// be run again as a whole once reconnected. Sent before `Finished`.
+    ConnectionLost,
+    /// What the script returned, or why it stopped. Always the last event.
+    Finished(Result<StatementOutcome, String>),
+}
+
+/// A catalog lookup running on its own task, checked on by the UI loop
+/// between redraws so the screen can show a spinner meanwhile.
+pub struct PendingFetch<T> {
+    task: JoinHandle<Result<T, String>>,
+    pub started: Instant,
+}
+
+impl<T: Send + 'static> PendingFetch<T> {
+    pub fn spawn(fetch: impl Future<Output = Result<T, String>> + Send + 'static) -> Self {
+        PendingFetch {
+            task: tokio::spawn(fetch),
+            started: Instant::now(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// Waits for the task; immediate once `is_finished` returned true.
+    pub async fn result(self) -> Result<T, String> {
+        self.task.await.unwrap_or_else(|err| Err(err.to_string()))
+    }
+}
+
+/// Runs the `;`-separated statements of `script` in order, stopping at the
+/// first error, and reports each outcome on `events`.
+///
+/// Finishes with the rows of the last statement that produced any, so a
+/// script ending in a SELECT shows its result. A single statement runs as is.
+/// Nothing is borrowed from the UI, so this can run on its own task.
+pub async fn run_script(
+    db_manager: &DbManager,
+    db_type: &DbType,
+    script: &str,
+    events: &UnboundedSender<QueryEvent>,
+) {
+    let finished = |result| {
+        let _ = events.send(QueryEvent::Finished(result));
+    };
+    let send_notices = || {
+        for notice in notices::take_notices() {
+            let _ = events.send(QueryEvent::Notice(notice));
+        }
+    };
+    // Anything left over came from an earlier statement.
+    notices::take_notices();
+
+    let statements = sql::split_statements(db_type, script);
+    if statements.len() <= 1 {
+        let result = run_statement(db_manager, db_type, script).await;
+        send_notices();
+        match result {
+            Ok(outcome) => {
+                let _ = events.send(QueryEvent::Ran(script.to_string()));
+                finished(Ok(outcome));
+            }
+            Err(err) => {
+                if is_connection_lost(err.as_ref()) {
+                    let _ = events.send(QueryEvent::ConnectionLost);
+                }
+                finished(Err(err.to_string()))
+            }
+        }
+        return;
+    }
+
+    let mut last_result = None;
+    for (i, statement) in statements.iter().enumerate() {
+        let result = run_statement(db_manager, db_type, statement).await;
+        send_notices();
+        match result {
+            Ok(outcome) => {
+                let _ = events.send(QueryEvent::Ran(statement.to_string()));
+                let logged = if outcome.columns.is_empty() {
+                    outcome.message.unwrap_or_else(|| "OK".to_string())
+                } else {
+                    let logged = format!("{} rows", outcome.rows.len());
+                    last_result = Some((outcome.rows, outcome.columns));
+                    logged
+                };
+                let _ = events.send(QueryEvent::Logged(script_log_line(i, statement, &logged)));
+            }
+            Err(err) => {
+                if i == 0 && is_connection_lost(err.as_ref()) {
+                    let _ = events.send(QueryEvent::ConnectionLost);
+                }
+                let _ = events.send(QueryEvent::Logged(script_log_line(
+                    i,
+                    statement,
+                    &format!("Error: {}", err),
+                )));
+                for (j, skipped) in statements.iter().enumerate().skip(i + 1) {
+                    let _ = events.send(QueryEvent::Logged(script_log_line(j, skipped, "skipped")));
+                }
+                finished(Err(format!(
+                    "Statement {} of {} failed: {}",
+                    i + 1,
+                    statements.len(),
+                    err
+                )));
+                return;
+            }
+        }
+    }
+
+    let (rows, columns) = last_result.unwrap_or_default();
+    finished(Ok(StatementOutcome {
+        rows,
+        columns,
+        message: Some(format!("Ran {} statements.", statements.len())),
+    }));
+}
+
+/// One `script_log` entry: the statement number, its first line and what happened.
+fn script_log_line(index: usize, statement: &str, outcome: &str) -> String {
+    const PREVIEW_CHARS: usize = 40;
+    let first_line = statement.lines().next().unwrap_or_default();
+    let mut preview: String = first_line.chars().take(PREVIEW_CHARS).collect();
+    if preview.len() < statement.len() {
+        preview.push('…');
+    }
+    format!("{}. {} — {}", index + 1, preview, outcome)
+}
@@ -0,0 +1,122 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use dfox_core::{models::connections::DbType, sql};
+use serde_json::Value;
+
+/// A query result kept by [`ResultCache`].
+pub struct CachedResult {
+    pub columns: Vec<String>,
+    pub rows: Vec<HashMap<String, Value>>,
+    pub message: Option<String>,
+    pub stored: Instant,
+}
+
+/// Results of read-only queries from the SQL editor, reused when the same
+/// query runs on the same connection again within `ttl`.
+#[derive(Default)]
+pub struct ResultCache {
+    /// How long a result is reused, from `result_cache_seconds`; `None`
+    /// turns the cache off.
+    pub ttl: Option<Duration>,
+    /// Results by connection label and [`sql::normalize`]d query.
+    entries: HashMap<(String, String), CachedResult>,
+}
+
+impl ResultCache {
+    /// Key under which the result of `script`, run on `connection`, is kept.
+    /// `None` when the cache is off or the script isn't a single statement
+    /// that only reads, whose result could go stale by running it. `db_type`
+    /// is the backend `connection` is to, which decides how `script` is read.
+    pub fn key(
+        &self,
+        db_type: &DbType,
+        connection: &str,
+        script: &str,
+    ) -> Option<(String, String)> {
+        self.ttl?;
+        match sql::split_statements(db_type, script)[..] {
+            [statement] if sql::is_read_only(db_type, statement) => {
+                Some((connection.to_string(), sql::normalize(db_type, script)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The result kept under `key`, unless it is older than `ttl`.
+    pub fn get(&mut self, key: &(String, String)) -> Option<&CachedResult> {
+        let ttl = self.ttl?;
+        self.entries
+            .retain(|_, cached| cached.stored.elapsed() < ttl);
+        self.entries.get(key)
+    }
+
+    pub fn insert(&mut self, key: (String, String), result: CachedResult) {
+        self.entries.insert(key, result);
+    }
+
+    /// Forgets every result, e.g. after a statement that may have changed
+    /// the data they were read from.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache() -> ResultCache {
+        ResultCache {
+            ttl: Some(Duration::from_secs(60)),
+            ..ResultCache::default()
+        }
+    }
+
+    fn result(stored: Instant) -> CachedResult {
+        CachedResult {
+            columns: vec!["n".to_string()],
+            rows: vec![HashMap::from([("n".to_string(), Value::from(1))])],
+            message: None,
+            stored,
+        }
+    }
+
+    #[test]
+    fn test_only_single_reads_are_cached() {
+        let cache = cache();
+        assert_eq!(
+            cache.key(&DbType::Postgres, "db", "SELECT  1;\n"),
+            Some(("db".to_string(), "SELECT 1".to_string()))
+        );
+        assert!(cache
+            .key(&DbType::Postgres, "db", "SELECT 1; SELECT 2")
+            .is_none());
+        assert!(cache
+            .key(&DbType::Postgres, "db", "DELETE FROM users WHERE id = 1")
+            .is_none());
+        assert!(ResultCache::default()
+            .key(&DbType::Postgres, "db", "SELECT 1")
+            .is_none());
+    }
+
+    #[test]
+    fn test_results_expire_after_the_ttl() {
+        let mut cache = cache();
+        let key = cache.key(&DbType::Postgres, "db", "SELECT 1").unwrap();
+        assert_eq!(
+            cache.key(&DbType::Postgres, "other", "SELECT 1").unwrap().1,
+            key.1
+        );
+
+        cache.insert(key.clone(), result(Instant::now()));
+        assert!(cache.get(&key).is_some());
+        assert!(cache.get(&("other".to_string(), key.1.clone())).is_none());
+
+        let stale = Instant::now() - Duration::from_secs(61);
+        cache.insert(key.clone(), result(stale));
+        assert!(cache.get(&key).is_none());
+    }
+}
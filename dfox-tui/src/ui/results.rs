@@ -0,0 +1,679 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    ops::Range,
+    time::Duration,
+};
+
+use dfox_core::models::value::{binary_bytes, binary_len};
+use ratatui::{
+    style::{Modifier, Style},
+    text::Span,
+    widgets::Cell,
+};
+use serde_json::Value;
+
+use super::{components::FooterAggregate, theme::Theme, timestamps::TimestampColumns};
+
+/// Widest a result column is allowed to grow before its values are truncated.
+pub const MAX_COLUMN_WIDTH: u16 = 40;
+/// Spacing ratatui's `Table` puts between adjacent columns.
+const COLUMN_SPACING: u16 = 1;
+
+/// Text shown for `header` in a result row.
+///
+/// SQL NULL (and a missing column) reads "NULL"; strings are shown without
+/// JSON quotes so an empty string stays visibly blank. JSON and array values
+/// are kept on one line, and binary values are summarized by their size.
+pub fn cell_text(row: &HashMap<String, Value>, header: &str) -> String {
+    match row.get(header) {
+        None | Some(Value::Null) => "NULL".to_string(),
+        Some(value) => match (binary_len(value), value) {
+            (Some(len), _) => binary_placeholder(len),
+            (None, Value::String(text)) => text.clone(),
+            (None, value) => compact_json(value),
+        },
+    }
+}
+
+/// Full text of `header` in a result row for the cell detail popup, with JSON
+/// and array values pretty-printed and binary values hex-dumped.
+pub fn cell_detail_text(row: &HashMap<String, Value>, header: &str) -> String {
+    match row.get(header) {
+        Some(value) => match (binary_bytes(value), value) {
+            (Some(bytes), _) => hex_dump(&bytes),
+            (None, Value::Object(_) | Value::Array(_)) => {
+                serde_json::to_string_pretty(value).unwrap_or_else(|_| compact_json(value))
+            }
+            (None, _) => cell_text(row, header),
+        },
+        None => cell_text(row, header),
+    }
+}
+
+/// Most bytes of a binary value the cell detail popup dumps.
+const HEX_DUMP_BYTES: usize = 4096;
+
+/// Stands in for a binary value of `len` bytes, e.g. `<binary 1.2 KiB>`.
+fn binary_placeholder(len: usize) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+    if len < 1024 {
+        return format!("<binary {} B>", len);
+    }
+    let mut size = len as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("<binary {:.1} {}>", size, UNITS[unit])
+}
+
+/// The size of `bytes`, then the first `HEX_DUMP_BYTES` of them sixteen to a
+/// line: offset, hex and the printable ASCII characters.
+fn hex_dump(bytes: &[u8]) -> String {
+    let mut lines = vec![binary_placeholder(bytes.len())];
+    for (i, chunk) in bytes.chunks(16).take(HEX_DUMP_BYTES / 16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        lines.push(format!("{:08x}  {:<47}  {}", i * 16, hex.join(" "), ascii));
+    }
+    if bytes.len() > HEX_DUMP_BYTES {
+        lines.push(format!("… {} more bytes", bytes.len() - HEX_DUMP_BYTES));
+    }
+    lines.join("\n")
+}
+
+/// Whether any cell of `row` contains `filter`, ignoring case. NULLs match
+/// as the text "NULL" they are shown as.
+pub fn row_matches(row: &HashMap<String, Value>, filter: &str) -> bool {
+    let filter = filter.to_lowercase();
+    row.keys()
+        .any(|header| cell_text(row, header).to_lowercase().contains(&filter))
+}
+
+/// Direction the results are sorted in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    /// Arrow shown after the sorted column's header.
+    pub fn arrow(self) -> &'static str {
+        match self {
+            SortOrder::Ascending => "▲",
+            SortOrder::Descending => "▼",
+        }
+    }
+}
+
+/// Where generated INSERT statements are written.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InsertTarget {
+    File,
+    Editor,
+}
+
+/// The prompt for turning the fetched rows into INSERT statements.
+pub struct InsertExport {
+    /// Table the statements insert into, as typed so far.
+    pub table: String,
+    pub target: InsertTarget,
+}
+
+/// Orders cell values by their JSON type first (NULL, booleans, numbers,
+/// strings, then arrays and objects) and by value within a type, so numbers
+/// compare numerically rather than as text.
+pub fn compare_values(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    fn rank(value: Option<&Value>) -> u8 {
+        match value {
+            None | Some(Value::Null) => 0,
+            Some(Value::Bool(_)) => 1,
+            Some(Value::Number(_)) => 2,
+            Some(Value::String(_)) => 3,
+            Some(Value::Array(_) | Value::Object(_)) => 4,
+        }
+    }
+
+    match (a, b) {
+        (Some(Value::Bool(a)), Some(Value::Bool(b))) => a.cmp(b),
+        (Some(Value::Number(a)), Some(Value::Number(b))) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => a
+                .as_f64()
+                .unwrap_or(f64::NAN)
+                .total_cmp(&b.as_f64().unwrap_or(f64::NAN)),
+        },
+        (Some(Value::String(a)), Some(Value::String(b))) => a.cmp(b),
+        (Some(a @ (Value::Array(_) | Value::Object(_))), Some(b)) if rank(Some(b)) == 4 => {
+            compact_json(a).cmp(&compact_json(b))
+        }
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+/// Sorts result rows by `header`, keeping the fetched order among equal values.
+///
+/// NULLs come first, or last with `nulls_last`, in either `order`.
+pub fn sort_rows(
+    rows: &mut [HashMap<String, Value>],
+    header: &str,
+    order: SortOrder,
+    nulls_last: bool,
+) {
+    let is_null = |row: &HashMap<String, Value>| row.get(header).is_none_or(Value::is_null);
+    rows.sort_by(|a, b| match (is_null(a), is_null(b)) {
+        (true, true) => Ordering::Equal,
+        (true, false) if nulls_last => Ordering::Greater,
+        (true, false) => Ordering::Less,
+        (false, true) if nulls_last => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => {
+            let ordering = compare_values(a.get(header), b.get(header));
+            match order {
+                SortOrder::Ascending => ordering,
+                SortOrder::Descending => ordering.reverse(),
+            }
+        }
+    });
+}
+
+/// Shown after the sorted column's header: the [`SortOrder::arrow`], with a
+/// `∅` on the side the NULLs are sorted to.
+pub fn sort_indicator(order: SortOrder, nulls_last: bool) -> String {
+    if nulls_last {
+        format!("{}∅", order.arrow())
+    } else {
+        format!("∅{}", order.arrow())
+    }
+}
+
+/// Splits `text` into rows of at most `width` columns so the cell detail popup
+/// knows exactly how far it can scroll.
+pub fn wrap_text(text: &str, width: u16) -> Vec<String> {
+    let width = usize::from(width.max(1));
+    let mut rows = Vec::new();
+    for line in text.lines() {
+        let mut row = String::new();
+        let mut row_width = 0;
+        for (i, c) in line.char_indices() {
+            let char_width = Span::raw(&line[i..i + c.len_utf8()]).width();
+            if row_width + char_width > width && !row.is_empty() {
+                rows.push(std::mem::take(&mut row));
+                row_width = 0;
+            }
+            row.push(c);
+            row_width += char_width;
+        }
+        rows.push(row);
+    }
+    rows
+}
+
+/// Rows of the cell detail popup, each with the 1-based number of the line it
+/// starts, or `None` for the rows a long line wrapped onto.
+///
+/// With `wrap` off every line stays on a single row, to be scrolled sideways.
+pub fn detail_rows(text: &str, width: u16, wrap: bool) -> Vec<(Option<usize>, String)> {
+    let mut rows = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        let wrapped = if wrap {
+            wrap_text(line, width)
+        } else {
+            Vec::new()
+        };
+        if wrapped.is_empty() {
+            rows.push((Some(i + 1), line.to_string()));
+            continue;
+        }
+        for (j, row) in wrapped.into_iter().enumerate() {
+            rows.push(((j == 0).then_some(i + 1), row));
+        }
+    }
+    rows
+}
+
+/// JSON on one line with a space after each separator, e.g. `{"a": [1, 2]}`.
+fn compact_json(value: &Value) -> String {
+    match value {
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(compact_json).collect();
+            format!("[{}]", items.join(", "))
+        }
+        Value::Object(map) => {
+            let entries: Vec<String> = map
+                .iter()
+                .map(|(key, value)| {
+                    format!("{}: {}", Value::from(key.as_str()), compact_json(value))
+                })
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+        other => other.to_string(),
+    }
+}
+
+/// Table cell for `header` in a result row, with NULL dimmed so it can't be
+/// mistaken for the string "NULL".
+pub fn result_cell(
+    row: &HashMap<String, Value>,
+    header: &str,
+    theme: &Theme,
+    timestamps: &TimestampColumns,
+) -> Cell<'static> {
+    let cell = Cell::from(timestamps.cell_text(row, header));
+    match row.get(header) {
+        None | Some(Value::Null) => cell.style(
+            Style::default()
+                .fg(theme.muted)
+                .add_modifier(Modifier::ITALIC),
+        ),
+        Some(_) => cell,
+    }
+}
+
+/// Width of each column: the widest of its header and values, clamped to `max_width`.
+pub fn column_widths(
+    rows: &[HashMap<String, Value>],
+    headers: &[String],
+    max_width: u16,
+    timestamps: &TimestampColumns,
+) -> Vec<u16> {
+    headers
+        .iter()
+        .map(|header| {
+            let widest_value = rows
+                .iter()
+                .map(|row| Span::raw(timestamps.cell_text(row, header)).width())
+                .max()
+                .unwrap_or(0);
+            let width = Span::raw(header.as_str()).width().max(widest_value);
+            (width as u16).clamp(1, max_width)
+        })
+        .collect()
+}
+
+/// Columns starting at `offset` that fit into `available_width`; always at least one.
+pub fn visible_column_range(widths: &[u16], offset: usize, available_width: u16) -> Range<usize> {
+    if offset >= widths.len() {
+        return offset..offset;
+    }
+
+    let mut used = 0u16;
+    let mut end = offset;
+    for width in &widths[offset..] {
+        let needed = if end == offset {
+            *width
+        } else {
+            width.saturating_add(COLUMN_SPACING)
+        };
+        if end > offset && used.saturating_add(needed) > available_width {
+            break;
+        }
+        used = used.saturating_add(needed);
+        end += 1;
+    }
+
+    offset..end
+}
+
+/// `widths` with the `pinned` columns' set to zero, for picking the scrolled
+/// columns beside them, and the width the pinned columns take up front.
+pub fn unpinned_widths(widths: &[u16], pinned: &BTreeSet<usize>) -> (Vec<u16>, u16) {
+    let mut unpinned = widths.to_vec();
+    let mut pinned_width = 0u16;
+    for &index in pinned {
+        if let Some(width) = unpinned.get_mut(index) {
+            pinned_width = pinned_width.saturating_add(*width + COLUMN_SPACING);
+            *width = 0;
+        }
+    }
+    (unpinned, pinned_width)
+}
+
+/// Indices of the columns to draw: the `pinned` ones, then the rest of the
+/// `scrolled` ones.
+pub fn shown_columns(pinned: &BTreeSet<usize>, scrolled: Range<usize>) -> Vec<usize> {
+    pinned
+        .iter()
+        .copied()
+        .chain(scrolled.filter(|index| !pinned.contains(index)))
+        .collect()
+}
+
+/// Computes the footer cell for `header` over `rows`.
+///
+/// `Count` applies to any column and counts non-NULL values. `Sum` and `Avg`
+/// only apply to columns whose non-NULL values are all numeric; other columns
+/// yield `None` and are left blank in the footer.
+pub fn aggregate_column(
+    rows: &[HashMap<String, Value>],
+    header: &str,
+    aggregate: FooterAggregate,
+) -> Option<String> {
+    let values: Vec<&Value> = rows
+        .iter()
+        .filter_map(|row| row.get(header))
+        .filter(|value| !value.is_null())
+        .collect();
+
+    let numeric = !values.is_empty() && values.iter().all(|value| value.is_number());
+    let sum = || -> f64 { values.iter().filter_map(|value| value.as_f64()).sum() };
+
+    match aggregate {
+        FooterAggregate::Count => Some(values.len().to_string()),
+        _ if !numeric => None,
+        // Integers add up exactly; no number of 64-bit values a query can
+        // return overflows an i128.
+        FooterAggregate::Sum => Some(
+            values
+                .iter()
+                .map(|value| {
+                    value
+                        .as_i64()
+                        .map(i128::from)
+                        .or_else(|| value.as_u64().map(i128::from))
+                })
+                .sum::<Option<i128>>()
+                .map_or_else(|| format_float(sum()), |sum| sum.to_string()),
+        ),
+        FooterAggregate::Avg => Some(format_float(sum() / values.len() as f64)),
+    }
+}
+
+/// Summary shown in the status line after a query, e.g. "42 rows in 18ms".
+pub fn query_summary(row_count: usize, elapsed: Duration) -> String {
+    let rows = if row_count == 1 { "row" } else { "rows" };
+    format!("{} {} in {}", row_count, rows, format_elapsed(elapsed))
+}
+
+pub fn format_elapsed(elapsed: Duration) -> String {
+    if elapsed.as_millis() < 1000 {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+/// How often a spinner advances, and so how often a screen waiting on the
+/// database redraws.
+pub const SPINNER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Spinner frame to draw `elapsed` into a wait.
+pub fn spinner_frame(elapsed: Duration) -> &'static str {
+    const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let tick = elapsed.as_millis() / SPINNER_INTERVAL.as_millis();
+    FRAMES[tick as usize % FRAMES.len()]
+}
+
+fn format_float(value: f64) -> String {
+    let formatted = format!("{:.4}", value);
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dfox_core::models::value::{binary_value, number_value};
+    use serde_json::json;
+
+    fn rows() -> Vec<HashMap<String, Value>> {
+        vec![
+            HashMap::from([
+                ("id".to_string(), json!(1)),
+                ("price".to_string(), json!(2.5)),
+                ("name".to_string(), json!("a")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), json!(2)),
+                ("price".to_string(), Value::Null),
+                ("name".to_string(), json!("b")),
+            ]),
+            HashMap::from([
+                ("id".to_string(), json!(3)),
+                ("price".to_string(), json!(1.0)),
+                ("name".to_string(), Value::Null),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn test_sum_of_integer_column() {
+        let result = aggregate_column(&rows(), "id", FooterAggregate::Sum);
+        assert_eq!(result.as_deref(), Some("6"));
+    }
+
+    #[test]
+    fn test_sum_of_large_integers_is_exact() {
+        let rows = vec![
+            HashMap::from([("n".to_string(), json!(i64::MAX))]),
+            HashMap::from([("n".to_string(), json!(u64::MAX))]),
+            HashMap::from([("n".to_string(), json!(1))]),
+        ];
+        let result = aggregate_column(&rows, "n", FooterAggregate::Sum);
+        assert_eq!(result.as_deref(), Some("27670116110564327423"));
+    }
+
+    #[test]
+    fn test_avg_skips_nulls() {
+        let result = aggregate_column(&rows(), "price", FooterAggregate::Avg);
+        assert_eq!(result.as_deref(), Some("1.75"));
+    }
+
+    #[test]
+    fn test_count_non_null_values() {
+        let result = aggregate_column(&rows(), "name", FooterAggregate::Count);
+        assert_eq!(result.as_deref(), Some("2"));
+    }
+
+    #[test]
+    fn test_cell_text_distinguishes_null_from_empty_string() {
+        let row = HashMap::from([
+            ("missing_value".to_string(), Value::Null),
+            ("blank".to_string(), json!("")),
+            ("name".to_string(), json!("a")),
+            ("id".to_string(), json!(7)),
+        ]);
+        assert_eq!(cell_text(&row, "missing_value"), "NULL");
+        assert_eq!(cell_text(&row, "not_selected"), "NULL");
+        assert_eq!(cell_text(&row, "blank"), "");
+        assert_eq!(cell_text(&row, "name"), "a");
+        assert_eq!(cell_text(&row, "id"), "7");
+    }
+
+    #[test]
+    fn test_large_numbers_keep_their_digits() {
+        let row = HashMap::from([
+            (
+                "total".to_string(),
+                number_value("98765432109876543210.0100"),
+            ),
+            ("id".to_string(), json!(u64::MAX)),
+        ]);
+        assert_eq!(cell_text(&row, "total"), "98765432109876543210.0100");
+        assert_eq!(cell_text(&row, "id"), "18446744073709551615");
+    }
+
+    #[test]
+    fn test_binary_values_show_their_size_and_dump_in_detail() {
+        let row = HashMap::from([
+            ("small".to_string(), binary_value(b"Hi\x00")),
+            ("large".to_string(), binary_value(&[0xab; 5000])),
+            ("text".to_string(), Value::from("\\x41")),
+        ]);
+        assert_eq!(cell_text(&row, "small"), "<binary 3 B>");
+        assert_eq!(cell_text(&row, "text"), "\\x41");
+        assert_eq!(cell_detail_text(&row, "text"), "\\x41");
+        assert_eq!(cell_text(&row, "large"), "<binary 4.9 KiB>");
+
+        assert_eq!(
+            cell_detail_text(&row, "small"),
+            "<binary 3 B>\n00000000  48 69 00                                         Hi."
+        );
+        let dump = cell_detail_text(&row, "large");
+        assert_eq!(dump.lines().count(), 1 + 256 + 1);
+        assert!(dump.ends_with("… 904 more bytes"));
+    }
+
+    #[test]
+    fn test_json_values_are_compact_in_cells_and_pretty_in_detail() {
+        let row = HashMap::from([
+            ("tags".to_string(), json!(["a", "b"])),
+            ("meta".to_string(), json!({"id": 1, "ok": true})),
+        ]);
+        assert_eq!(cell_text(&row, "tags"), r#"["a", "b"]"#);
+        assert_eq!(cell_text(&row, "meta"), r#"{"id": 1, "ok": true}"#);
+        assert_eq!(
+            cell_detail_text(&row, "meta"),
+            "{\n  \"id\": 1,\n  \"ok\": true\n}"
+        );
+    }
+
+    #[test]
+    fn test_detail_rows_number_wrapped_lines_once() {
+        assert_eq!(
+            detail_rows("abcde\n\nf", 3, true),
+            vec![
+                (Some(1), "abc".to_string()),
+                (None, "de".to_string()),
+                (Some(2), String::new()),
+                (Some(3), "f".to_string()),
+            ]
+        );
+        assert_eq!(
+            detail_rows("abcde\nf", 3, false),
+            vec![(Some(1), "abcde".to_string()), (Some(2), "f".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_row_matches_any_cell_ignoring_case() {
+        let row = &rows()[0];
+        assert!(row_matches(row, "A"));
+        assert!(row_matches(row, "2.5"));
+        assert!(!row_matches(row, "zebra"));
+    }
+
+    #[test]
+    fn test_sort_rows_compares_by_type() {
+        let mut rows: Vec<HashMap<String, Value>> = [json!(10), json!(9), json!(null), json!("a")]
+            .into_iter()
+            .map(|value| HashMap::from([("v".to_string(), value)]))
+            .collect();
+        let values = |rows: &[HashMap<String, Value>]| -> Vec<String> {
+            rows.iter().map(|row| cell_text(row, "v")).collect()
+        };
+
+        sort_rows(&mut rows, "v", SortOrder::Ascending, false);
+        assert_eq!(values(&rows), vec!["NULL", "9", "10", "a"]);
+        sort_rows(&mut rows, "v", SortOrder::Descending, false);
+        assert_eq!(values(&rows), vec!["NULL", "a", "10", "9"]);
+        assert_eq!(
+            compare_values(Some(&json!(1.5)), Some(&json!(2))),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_nulls_last_holds_in_either_order() {
+        let mut rows: Vec<HashMap<String, Value>> = [json!(null), json!(2), json!(1)]
+            .into_iter()
+            .map(|value| HashMap::from([("v".to_string(), value)]))
+            .collect();
+        rows.push(HashMap::new());
+        let values = |rows: &[HashMap<String, Value>]| -> Vec<String> {
+            rows.iter().map(|row| cell_text(row, "v")).collect()
+        };
+
+        sort_rows(&mut rows, "v", SortOrder::Ascending, true);
+        assert_eq!(values(&rows), vec!["1", "2", "NULL", "NULL"]);
+        sort_rows(&mut rows, "v", SortOrder::Descending, true);
+        assert_eq!(values(&rows), vec!["2", "1", "NULL", "NULL"]);
+        assert_eq!(sort_indicator(SortOrder::Descending, true), "▼∅");
+        assert_eq!(sort_indicator(SortOrder::Ascending, false), "∅▲");
+    }
+
+    #[test]
+    fn test_wrap_text() {
+        assert_eq!(wrap_text("abcdefg\nhi", 3), vec!["abc", "def", "g", "hi"]);
+        assert_eq!(wrap_text("a\n\nb", 10), vec!["a", "", "b"]);
+        assert!(wrap_text("", 10).is_empty());
+    }
+
+    #[test]
+    fn test_column_widths_fit_content_and_clamp() {
+        let rows = vec![HashMap::from([
+            ("id".to_string(), json!(12345)),
+            ("bio".to_string(), json!("x".repeat(100))),
+        ])];
+        let headers = vec!["id".to_string(), "bio".to_string()];
+        assert_eq!(
+            column_widths(&rows, &headers, 40, &TimestampColumns::default()),
+            vec![5, 40]
+        );
+    }
+
+    #[test]
+    fn test_visible_column_range() {
+        let widths = [10, 10, 10, 10];
+        assert_eq!(visible_column_range(&widths, 0, 32), 0..3);
+        assert_eq!(visible_column_range(&widths, 2, 100), 2..4);
+        // A single column wider than the panel is still shown.
+        assert_eq!(visible_column_range(&[50], 0, 20), 0..1);
+    }
+
+    #[test]
+    fn test_pinned_columns_stay_in_front_of_the_scrolled_ones() {
+        let widths = [4, 10, 10, 10, 10];
+        let pinned = BTreeSet::from([0]);
+        let (unpinned, pinned_width) = unpinned_widths(&widths, &pinned);
+        assert_eq!(unpinned, vec![0, 10, 10, 10, 10]);
+        assert_eq!(pinned_width, 5);
+
+        let scrolled = visible_column_range(&unpinned, 3, 32 - pinned_width);
+        assert_eq!(scrolled, 3..5);
+        assert_eq!(shown_columns(&pinned, scrolled), vec![0, 3, 4]);
+        let scrolled = visible_column_range(&unpinned, 0, 32 - pinned_width);
+        assert_eq!(shown_columns(&pinned, scrolled), vec![0, 1, 2]);
+        assert_eq!(shown_columns(&BTreeSet::new(), 1..3), vec![1, 2]);
+    }
+
+    #[test]
+    fn test_query_summary() {
+        assert_eq!(
+            query_summary(42, Duration::from_millis(18)),
+            "42 rows in 18ms"
+        );
+        assert_eq!(
+            query_summary(1, Duration::from_millis(1240)),
+            "1 row in 1.24s"
+        );
+    }
+
+    #[test]
+    fn test_sum_of_text_column_is_blank() {
+        let result = aggregate_column(&rows(), "name", FooterAggregate::Sum);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_spinner_advances_each_interval() {
+        assert_eq!(spinner_frame(Duration::ZERO), "⠋");
+        assert_eq!(spinner_frame(Duration::from_millis(250)), "⠹");
+        assert_eq!(spinner_frame(Duration::from_millis(1000)), "⠋");
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+
+use dfox_core::{models::schema::RoutineSchema, sql};
+use serde_json::Value;
+
+use super::{components::FocusedWidget, DatabaseClientUI};
+use crate::db::run_statements_together;
+
+/// The prompt for the arguments of a function or procedure about to be
+/// called.
+pub struct RoutineCall {
+    pub routine: RoutineSchema,
+    /// Values typed for the routine's input arguments, in order; empty ones
+    /// pass NULL.
+    pub values: Vec<String>,
+    /// Index into `values` of the argument being typed.
+    pub field: usize,
+}
+
+/// Text of the routine popup: its signature as a comment above its source,
+/// so a copy of it still runs.
+pub fn routine_detail(routine: &RoutineSchema) -> String {
+    let mut detail = format!("-- {}", routine.signature());
+    if let Some(source) = &routine.source {
+        detail.push_str("\n\n");
+        detail.push_str(source.trim_end());
+    }
+    detail
+}
+
+impl DatabaseClientUI {
+    /// Name of the selected entry of the Tables panel, if it is a function
+    /// or procedure.
+    pub fn selected_routine(&self) -> Option<String> {
+        let name = self.visible_tables().get(self.selected_table)?.to_string();
+        self.object_kind(&name)
+            .filter(|kind| kind.is_routine())
+            .map(|_| name)
+    }
+
+    async fn describe_selected_routine(&mut self) -> Option<RoutineSchema> {
+        let name = self.selected_routine()?;
+        match self.describe_routine(&name).await {
+            Ok(routine) => Some(routine),
+            Err(err) => {
+                self.connection_error_message = Some(format!("Error describing routine: {}", err));
+                None
+            }
+        }
+    }
+
+    /// Shows the selected routine's signature and source in the cell detail
+    /// popup, where `y` copies them.
+    pub async fn show_selected_routine(&mut self) {
+        if let Some(routine) = self.describe_selected_routine().await {
+            self.cell_detail = Some(routine_detail(&routine));
+            self.cell_detail_table = Some(routine.name);
+            self.cell_detail_scroll = 0;
+            self.cell_detail_hscroll = 0;
+        }
+    }
+
+    /// Prompts for the selected routine's input arguments, or calls it right
+    /// away when it takes none.
+    pub async fn open_routine_call(&mut self) {
+        if self.selected_routine().is_none() {
+            self.status_message = Some("Select a function or procedure to call.".to_string());
+            return;
+        }
+        let Some(routine) = self.describe_selected_routine().await else {
+            return;
+        };
+        let call = RoutineCall {
+            values: vec![String::new(); routine.inputs().len()],
+            routine,
+            field: 0,
+        };
+        if call.values.is_empty() {
+            self.run_routine_call(call).await;
+        } else {
+            self.routine_call = Some(call);
+        }
+    }
+
+    /// Calls the routine with the typed values and shows what it returns in
+    /// the Query Result panel: the values of a MySQL procedure's output
+    /// arguments if it has any, or else the rows of the call itself.
+    pub async fn run_routine_call(&mut self, call: RoutineCall) {
+        let db_type = self.db_type.db_type();
+        let statement = sql::call_statement(&db_type, &call.routine, &call.values);
+        if self.connection_input.read_only && sql::find_write(&db_type, &statement).is_some() {
+            self.sql_query_error = Some(format!(
+                "Read-only mode only runs statements that read; refused: {}",
+                statement
+            ));
+            return;
+        }
+
+        // A routine may write, so cached results could be stale after it.
+        self.result_cache.clear();
+        self.table_preview = None;
+        self.script_log.clear();
+        self.server_messages.clear();
+        let result = match sql::call_outputs_query(&db_type, &call.routine) {
+            Some(outputs) => self.call_with_outputs(&statement, outputs).await,
+            None => self.execute_sql_query(&statement).await,
+        };
+        match result {
+            Ok((rows, message)) => {
+                self.sql_query_result = rows;
+                self.sql_query_success_message = message;
+                self.sql_query_error = None;
+                self.reset_result_position();
+                self.current_focus = FocusedWidget::QueryResult;
+                self.status_message = Some(format!("Ran {}", statement));
+            }
+            Err(err) => {
+                self.sql_query_error = Some(err.to_string());
+                self.clear_query_result();
+            }
+        }
+    }
+
+    /// Runs a MySQL procedure `call` and then `outputs`, which reads back its
+    /// output arguments, on the same connection, since `@` variables belong
+    /// to the session.
+    async fn call_with_outputs(
+        &mut self,
+        call: &str,
+        outputs: String,
+    ) -> Result<(Vec<HashMap<String, Value>>, Option<String>), Box<dyn std::error::Error>> {
+        let outcome = run_statements_together(
+            &self.db_manager,
+            &self.db_type.db_type(),
+            &[call.to_string(), outputs],
+        )
+        .await
+        .map_err(|err| err.to_string())?
+        .pop()
+        .ok_or("The procedure call returned nothing.")?;
+        self.sql_query_columns = outcome.columns;
+        Ok((outcome.rows, outcome.message))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crossterm::event::KeyCode;
+    use dfox_core::{
+        models::schema::{DbObject, ObjectKind, RoutineArgument},
+        DbManager,
+    };
+
+    use super::*;
+    use crate::ui::{DatabaseType, UIHandler};
+
+    fn routine() -> RoutineSchema {
+        let argument = |name: &str| RoutineArgument {
+            name: name.to_string(),
+            data_type: "integer".to_string(),
+            is_input: true,
+        };
+        RoutineSchema {
+            name: "public.add".to_string(),
+            kind: ObjectKind::Function,
+            arguments: vec![argument("a"), argument("b")],
+            return_type: Some("integer".to_string()),
+            source: Some("CREATE FUNCTION public.add(a integer, b integer)\n".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_routine_detail_puts_the_signature_above_the_source() {
+        assert_eq!(
+            routine_detail(&routine()),
+            "-- public.add(a integer, b integer) RETURNS integer\n\n\
+             CREATE FUNCTION public.add(a integer, b integer)"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_call_prompt_takes_a_value_per_argument() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.db_type = DatabaseType::Postgres;
+        ui.objects = vec![DbObject {
+            name: "public.add(integer, integer)".to_string(),
+            kind: ObjectKind::Function,
+        }];
+        ui.selected_table = 0;
+        assert_eq!(
+            ui.selected_routine().as_deref(),
+            Some("public.add(integer, integer)")
+        );
+
+        ui.routine_call = Some(RoutineCall {
+            values: vec![String::new(); 2],
+            routine: routine(),
+            field: 0,
+        });
+        for key in [
+            KeyCode::Char('4'),
+            KeyCode::Char('2'),
+            KeyCode::Backspace,
+            KeyCode::Tab,
+            KeyCode::Char('7'),
+        ] {
+            UIHandler::handle_routine_call_input(&mut ui, key).await;
+        }
+        let call = ui.routine_call.as_ref().unwrap();
+        assert_eq!(call.values, vec!["4", "7"]);
+        assert_eq!(call.field, 1);
+
+        UIHandler::handle_routine_call_input(&mut ui, KeyCode::Esc).await;
+        assert!(ui.routine_call.is_none());
+    }
+}
@@ -0,0 +1,183 @@
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{components::ConnectionInput, connection::SslMode, DatabaseType};
+
+/// Most recently used connections kept in the config file.
+const MAX_SAVED_CONNECTIONS: usize = 10;
+
+/// Connection parameters remembered after a successful connect.
+///
+/// Passwords are deliberately not stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedConnection {
+    pub db_type: DatabaseType,
+    pub username: String,
+    pub hostname: String,
+    pub port: String,
+    #[serde(default)]
+    pub database: String,
+    #[serde(default)]
+    pub ssl_mode: SslMode,
+    #[serde(default)]
+    pub ssl_root_cert: String,
+    /// The database file, for SQLite.
+    #[serde(default)]
+    pub file_path: String,
+}
+
+impl SavedConnection {
+    pub fn from_input(db_type: DatabaseType, input: &ConnectionInput) -> Self {
+        if db_type == DatabaseType::SQLite {
+            // The file is all there is to a SQLite connection.
+            return Self {
+                db_type,
+                username: String::new(),
+                hostname: String::new(),
+                port: String::new(),
+                database: String::new(),
+                ssl_mode: SslMode::default(),
+                ssl_root_cert: String::new(),
+                file_path: input.file_path.clone(),
+            };
+        }
+        Self {
+            db_type,
+            username: input.username.clone(),
+            hostname: input.hostname.clone(),
+            port: input.port.clone(),
+            database: input.database.clone(),
+            ssl_mode: input.ssl_mode,
+            ssl_root_cert: input.ssl_root_cert.clone(),
+            file_path: String::new(),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        if self.db_type == DatabaseType::SQLite {
+            return self.file_path.clone();
+        }
+        format!("{}@{}:{}", self.username, self.hostname, self.port)
+    }
+}
+
+/// Saved connections, most recently used first.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SavedConnections {
+    #[serde(default)]
+    connections: Vec<SavedConnection>,
+    /// The file `load` read, which `save` writes back; without one the list
+    /// is only kept in memory.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl SavedConnections {
+    /// Reads the config file, starting empty if it is missing or unreadable.
+    pub fn load() -> Self {
+        let path = config_file();
+        let mut saved: Self = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+        saved.path = path;
+        saved
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let content = toml::to_string(self).map_err(io::Error::other)?;
+        fs::write(path, content)
+    }
+
+    pub fn for_type(&self, db_type: DatabaseType) -> Vec<&SavedConnection> {
+        self.connections
+            .iter()
+            .filter(|connection| connection.db_type == db_type)
+            .collect()
+    }
+
+    /// Moves `connection` to the front, adding it if it is new.
+    pub fn remember(&mut self, connection: SavedConnection) {
+        self.remove(&connection);
+        self.connections.insert(0, connection);
+        self.connections.truncate(MAX_SAVED_CONNECTIONS);
+    }
+
+    pub fn remove(&mut self, connection: &SavedConnection) {
+        self.connections.retain(|saved| saved != connection);
+    }
+}
+
+/// `<config dir>/dfox/connections.toml`, e.g. `~/.config/dfox/connections.toml` on Linux.
+fn config_file() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("dfox").join("connections.toml"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(db_type: DatabaseType, hostname: &str) -> SavedConnection {
+        SavedConnection {
+            db_type,
+            username: "admin".to_string(),
+            hostname: hostname.to_string(),
+            port: "5432".to_string(),
+            database: String::new(),
+            ssl_mode: SslMode::Require,
+            ssl_root_cert: String::new(),
+            file_path: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_remember_moves_connection_to_front() {
+        let mut saved = SavedConnections::default();
+        saved.remember(connection(DatabaseType::Postgres, "a"));
+        saved.remember(connection(DatabaseType::Postgres, "b"));
+        saved.remember(connection(DatabaseType::Postgres, "a"));
+
+        let hosts: Vec<&str> = saved
+            .for_type(DatabaseType::Postgres)
+            .iter()
+            .map(|c| c.hostname.as_str())
+            .collect();
+        assert_eq!(hosts, vec!["a", "b"]);
+        assert!(saved.for_type(DatabaseType::MySQL).is_empty());
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let mut saved = SavedConnections::default();
+        saved.remember(connection(DatabaseType::MySQL, "db.local"));
+
+        let content = toml::to_string(&saved).unwrap();
+        let loaded: SavedConnections = toml::from_str(&content).unwrap();
+
+        assert_eq!(loaded.connections, saved.connections);
+        assert!(!content.contains("password"));
+    }
+
+    #[test]
+    fn test_sqlite_connection_is_its_file() {
+        let mut input = ConnectionInput::new();
+        input.username = "admin".to_string();
+        input.file_path = "/tmp/app.db".to_string();
+        let saved = SavedConnection::from_input(DatabaseType::SQLite, &input);
+
+        assert_eq!(saved.label(), "/tmp/app.db");
+        assert_eq!(saved.username, "");
+        assert_eq!(
+            SavedConnection::from_input(DatabaseType::Postgres, &input).file_path,
+            ""
+        );
+    }
+}
@@ -1,22 +1,40 @@
-use dfox_core::models::schema::TableSchema;
+use dfox_core::{models::schema::TableSchema, sql};
 use ratatui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Row, Table, Wrap};
-use ratatui::{backend::CrosstermBackend, Terminal};
-use std::io;
-
-use crate::db::{MySQLUI, PostgresUI};
-
-use super::components::{DatabaseType, FocusedWidget};
+use ratatui::widgets::{
+    Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, Wrap,
+};
+use ratatui::{backend::CrosstermBackend, Frame, Terminal};
+use std::{collections::BTreeSet, io};
+
+use super::completion::Completion;
+use super::components::{DatabaseType, FocusedWidget, PanelAreas};
+use super::connection::ConnectPrompt;
+use super::diagnostics::ConnectionInfoView;
+use super::editor::follow_cursor;
+use super::highlight::{highlight_selection, highlight_sql};
+use super::keymap::Action;
+use super::results::{
+    aggregate_column, column_widths, detail_rows, result_cell, shown_columns, sort_indicator,
+    spinner_frame, unpinned_widths, visible_column_range, InsertTarget, MAX_COLUMN_WIDTH,
+};
+use super::tables::{
+    column_summary, constraint_lines, filter_match, format_count, fuzzy_match, schema_view_lines,
+};
+use super::theme::Theme;
 use super::{DatabaseClientUI, UIRenderer};
 
 impl UIRenderer for DatabaseClientUI {
-    async fn render_message_popup(
+    async fn render_sqlite_file_input_screen(
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
         terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
             let size = f.area();
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
@@ -31,31 +49,56 @@ impl UIRenderer for DatabaseClientUI {
                 )
                 .split(size);
 
-            let popup_area = centered_rect(50, chunks[1]);
+            let input_area = centered_rect(50, chunks[1]);
 
             let block = Block::default()
-                .title("Message")
+                .title("Enter SQLite Database File")
                 .borders(Borders::ALL)
                 .title_alignment(Alignment::Center);
 
-            let message = Paragraph::new("SQLite is not implemented yet.")
-                .block(block)
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true });
+            let input_paragraph =
+                Paragraph::new(format!("Path: {}", self.connection_input.file_path))
+                    .block(block)
+                    .style(Style::default().fg(self.theme.text))
+                    .alignment(Alignment::Left)
+                    .wrap(Wrap { trim: false });
 
-            f.render_widget(message, popup_area);
+            f.render_widget(input_paragraph, input_area);
 
-            let help_message = vec![Line::from(vec![Span::styled(
-                "Press any key to return.",
-                Style::default().fg(Color::White),
-            )])];
+            if let Some(error_message) = &self.connection_error_message {
+                render_error_dialog(f, &self.theme, error_message);
+            } else {
+                let help_message = vec![Line::from(vec![
+                    Span::styled(
+                        "Enter",
+                        Style::default()
+                            .fg(self.theme.action)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" to connect, "),
+                    Span::styled(
+                        "Tab",
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" to complete path, "),
+                    Span::styled(
+                        "Esc",
+                        Style::default()
+                            .fg(self.theme.danger)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" to go back"),
+                ])];
 
-            let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
-                .alignment(Alignment::Center)
-                .wrap(Wrap { trim: true });
+                let help_paragraph = Paragraph::new(help_message)
+                    .style(Style::default().fg(self.theme.text))
+                    .alignment(Alignment::Center)
+                    .wrap(Wrap { trim: true });
 
-            f.render_widget(help_paragraph, chunks[2]);
+                f.render_widget(help_paragraph, chunks[2]);
+            }
         })?;
 
         Ok(())
@@ -65,12 +108,7 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
-        let db_types = [
-            DatabaseType::Postgres,
-            DatabaseType::MySQL,
-            DatabaseType::SQLite,
-        ];
-        let db_type_list: Vec<ListItem> = db_types
+        let db_type_list: Vec<ListItem> = DatabaseType::ALL
             .iter()
             .enumerate()
             .map(|(i, db_type)| {
@@ -79,17 +117,21 @@ impl UIRenderer for DatabaseClientUI {
                 if i == self.selected_db_type {
                     ListItem::new(db).style(
                         Style::default()
-                            .bg(Color::Yellow)
-                            .fg(Color::Black)
+                            .bg(self.theme.selection)
+                            .fg(self.theme.selection_text)
                             .add_modifier(Modifier::BOLD),
                     )
                 } else {
-                    ListItem::new(db).style(Style::default().fg(Color::White))
+                    ListItem::new(db).style(Style::default().fg(self.theme.text))
                 }
             })
             .collect();
 
         terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
             let size = f.area();
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
@@ -113,8 +155,8 @@ impl UIRenderer for DatabaseClientUI {
 
             let db_type_widget = List::new(db_type_list).block(block).highlight_style(
                 Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
+                    .bg(self.theme.selection)
+                    .fg(self.theme.selection_text)
                     .add_modifier(Modifier::BOLD),
             );
 
@@ -124,37 +166,157 @@ impl UIRenderer for DatabaseClientUI {
                 Span::styled(
                     "Up",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(self.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("/"),
                 Span::styled(
                     "Down",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(self.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to navigate, "),
                 Span::styled(
                     "Enter",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(self.theme.action)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to select, "),
+                Span::styled(
+                    "?",
+                    Style::default()
+                        .fg(self.theme.info)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" for help, "),
                 Span::styled(
                     "q",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default()
+                        .fg(self.theme.danger)
+                        .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(self.theme.text))
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true });
+
+            f.render_widget(help_paragraph, chunks[2]);
+
+            if let Some(scroll) = self.help_scroll {
+                render_help(f, &self.theme, &self.help_entries(), scroll);
+            }
+
+            if let Some(error_message) = &self.connection_error_message {
+                render_error_dialog(f, &self.theme, error_message);
+            }
+        })?;
+
+        Ok(())
+    }
+
+    async fn render_saved_connections_screen(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    ) -> io::Result<()> {
+        let mut labels: Vec<String> = self
+            .saved_connections
+            .for_type(self.db_type)
+            .iter()
+            .map(|connection| connection.label())
+            .collect();
+        labels.push("+ New connection".to_string());
+
+        let connection_list: Vec<ListItem> = labels
+            .into_iter()
+            .enumerate()
+            .map(|(i, label)| {
+                if i == self.selected_saved_connection {
+                    ListItem::new(label).style(
+                        Style::default()
+                            .bg(self.theme.selection)
+                            .fg(self.theme.selection_text)
+                            .add_modifier(Modifier::BOLD),
+                    )
+                } else {
+                    ListItem::new(label).style(Style::default().fg(self.theme.text))
+                }
+            })
+            .collect();
+
+        terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
+            let size = f.area();
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(30),
+                        Constraint::Percentage(40),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(10),
+                    ]
+                    .as_ref(),
+                )
+                .split(size);
+
+            let horizontal_layout = centered_rect(50, chunks[1]);
+
+            let block = Block::default()
+                .title(format!("Saved {} Connections", self.db_type.as_str()))
+                .borders(Borders::ALL)
+                .title_alignment(Alignment::Center);
+
+            f.render_widget(List::new(connection_list).block(block), horizontal_layout);
+
+            let help_message = vec![Line::from(vec![
+                Span::styled(
+                    "Enter",
+                    Style::default()
+                        .fg(self.theme.action)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to select, "),
+                Span::styled(
+                    "Delete",
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to forget a connection, "),
+                Span::styled(
+                    "?",
+                    Style::default()
+                        .fg(self.theme.info)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" for help, "),
+                Span::styled(
+                    "Esc",
+                    Style::default()
+                        .fg(self.theme.danger)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to go back"),
+            ])];
+
+            let help_paragraph = Paragraph::new(help_message)
+                .style(Style::default().fg(self.theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
             f.render_widget(help_paragraph, chunks[2]);
+
+            if let Some(scroll) = self.help_scroll {
+                render_help(f, &self.theme, &self.help_entries(), scroll);
+            }
         })?;
 
         Ok(())
@@ -165,6 +327,10 @@ impl UIRenderer for DatabaseClientUI {
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
         terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
             let size = f.area();
             let vertical_chunks = Layout::default()
                 .direction(Direction::Vertical)
@@ -187,70 +353,110 @@ impl UIRenderer for DatabaseClientUI {
                 .title_alignment(Alignment::Center);
 
             let mut content = [
+                format!("URI: {}", self.connection_input.display_url()),
                 format!("Username: {}", self.connection_input.username),
-                format!(
-                    "Password: {}",
-                    "*".repeat(self.connection_input.password.len())
-                ),
+                if self.connection_input.external_password {
+                    "Password: (provided externally)".to_string()
+                } else if self.connection_input.show_password {
+                    format!("Password: {}", self.connection_input.password)
+                } else {
+                    format!(
+                        "Password: {}",
+                        "*".repeat(self.connection_input.password.len())
+                    )
+                },
                 format!("Hostname: {}", self.connection_input.hostname),
-                format!("Port: {}", self.connection_input.port),
+                if self.connection_input.port.is_empty() {
+                    format!("Port: ({})", self.db_type.default_port())
+                } else {
+                    format!("Port: {}", self.connection_input.port)
+                },
+                if self.connection_input.database.is_empty() {
+                    "Database: (server default)".to_string()
+                } else {
+                    format!("Database: {}", self.connection_input.database)
+                },
+                format!("SSL mode: {}", self.connection_input.ssl_mode.as_str()),
+                format!("CA certificate: {}", self.connection_input.ssl_root_cert),
             ];
 
             content[self.current_input_index()].push_str(" <");
 
             let input_paragraph = Paragraph::new(content.join("\n"))
                 .block(block)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(self.theme.text))
                 .alignment(Alignment::Left);
 
             f.render_widget(input_paragraph, horizontal_layout);
 
             if let Some(error_message) = &self.connection_error_message {
-                let error_block = Block::default()
-                    .title("Error")
-                    .borders(Borders::ALL)
-                    .style(Style::default().fg(Color::Red))
-                    .title_alignment(Alignment::Center);
-
-                let error_paragraph = Paragraph::new(error_message.clone())
-                    .block(error_block)
-                    .style(Style::default().fg(Color::White))
-                    .alignment(Alignment::Center)
-                    .wrap(Wrap { trim: true });
-
-                let error_area = centered_rect(50, vertical_chunks[1]);
-                f.render_widget(Clear, error_area);
-                f.render_widget(error_paragraph, error_area);
+                render_error_dialog(f, &self.theme, error_message);
             } else {
                 let help_message = vec![Line::from(vec![
                     Span::styled(
                         "Enter",
                         Style::default()
-                            .fg(Color::Green)
+                            .fg(self.theme.action)
                             .add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" to confirm input, "),
                     Span::styled(
                         "Up/Down",
                         Style::default()
-                            .fg(Color::Yellow)
+                            .fg(self.theme.accent)
                             .add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" to navigate fields, "),
+                    Span::styled(
+                        "Left/Right",
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" to change the SSL mode, "),
+                    Span::styled(
+                        "Ctrl+R",
+                        Style::default()
+                            .fg(self.theme.accent)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::raw(" to show/hide the password, "),
                     Span::styled(
                         "Esc",
-                        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                        Style::default()
+                            .fg(self.theme.danger)
+                            .add_modifier(Modifier::BOLD),
                     ),
                     Span::raw(" to go back"),
                 ])];
 
                 let help_paragraph = Paragraph::new(help_message)
-                    .style(Style::default().fg(Color::White))
+                    .style(Style::default().fg(self.theme.text))
                     .alignment(Alignment::Center)
                     .wrap(Wrap { trim: true });
 
                 f.render_widget(help_paragraph, vertical_chunks[2]);
             }
+
+            match self.connect_prompt {
+                Some(ConnectPrompt::Confirm) => {
+                    let message = format!(
+                        "{}\n\nEnter to connect, Esc to edit",
+                        self.connection_input.summary(self.db_type)
+                    );
+                    let title = format!("Connect to {}?", self.db_type.as_str());
+                    render_dialog(f, &self.theme, &title, &message, self.theme.accent);
+                }
+                Some(ConnectPrompt::Connecting) => {
+                    let message = format!(
+                        "Connecting to {}:{}…",
+                        self.connection_input.hostname,
+                        self.connection_input.port_or_default(self.db_type)
+                    );
+                    render_dialog(f, &self.theme, "Connecting", &message, self.theme.accent);
+                }
+                None => {}
+            }
         })?;
 
         Ok(())
@@ -260,46 +466,57 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
-        match self.selected_db_type {
-            0 => match PostgresUI::fetch_databases(self).await {
-                Ok(databases) => {
-                    self.databases = databases;
-                }
-                Err(_) => {
-                    self.databases = vec!["Error fetching databases".to_string()];
-                }
-            },
-            1 => match MySQLUI::fetch_databases(self).await {
-                Ok(databases) => {
-                    self.databases = databases;
-                }
-                Err(e) => {
-                    self.databases =
-                        vec!["Error fetching databases: {}".to_string(), e.to_string()];
-                }
-            },
-            _ => (),
+        let db_list: Vec<ListItem> = match &self.database_fetch {
+            Some(fetch) => vec![ListItem::new(format!(
+                "{} Loading databases…",
+                spinner_frame(fetch.started.elapsed())
+            ))
+            .style(Style::default().fg(self.theme.muted))],
+            None => self
+                .filtered_databases()
+                .into_iter()
+                .enumerate()
+                .map(|(i, db)| {
+                    let matched = fuzzy_match(db, &self.database_filter).unwrap_or_default();
+                    let name: Vec<Span> = db
+                        .char_indices()
+                        .map(|(offset, c)| {
+                            if matched.contains(&offset) {
+                                Span::styled(
+                                    c.to_string(),
+                                    Style::default()
+                                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                                )
+                            } else {
+                                Span::raw(c.to_string())
+                            }
+                        })
+                        .collect();
+                    if i == self.selected_database {
+                        ListItem::new(Line::from(name)).style(
+                            Style::default()
+                                .bg(self.theme.selection)
+                                .fg(self.theme.selection_text)
+                                .add_modifier(Modifier::BOLD),
+                        )
+                    } else {
+                        ListItem::new(Line::from(name)).style(Style::default().fg(self.theme.text))
+                    }
+                })
+                .collect(),
+        };
+        let mut title = "Select Database".to_string();
+        if self.editing_database_filter {
+            title.push_str(&format!(" /{}_", self.database_filter));
+        } else if !self.database_filter.is_empty() {
+            title.push_str(&format!(" /{}", self.database_filter));
         }
 
-        let db_list: Vec<ListItem> = self
-            .databases
-            .iter()
-            .enumerate()
-            .map(|(i, db)| {
-                if i == self.selected_database {
-                    ListItem::new(db.clone()).style(
-                        Style::default()
-                            .bg(Color::Yellow)
-                            .fg(Color::Black)
-                            .add_modifier(Modifier::BOLD),
-                    )
-                } else {
-                    ListItem::new(db.clone()).style(Style::default().fg(Color::White))
-                }
-            })
-            .collect();
-
         terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
             let size = f.area();
 
             let chunks = Layout::default()
@@ -317,14 +534,14 @@ impl UIRenderer for DatabaseClientUI {
             let horizontal_layout = centered_rect(50, chunks[1]);
 
             let block = Block::default()
-                .title("Select Database")
+                .title(title)
                 .borders(Borders::ALL)
                 .title_alignment(Alignment::Center);
 
             let db_list_widget = List::new(db_list).block(block).highlight_style(
                 Style::default()
-                    .bg(Color::Yellow)
-                    .fg(Color::Black)
+                    .bg(self.theme.selection)
+                    .fg(self.theme.selection_text)
                     .add_modifier(Modifier::BOLD),
             );
 
@@ -334,37 +551,61 @@ impl UIRenderer for DatabaseClientUI {
                 Span::styled(
                     "Up",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(self.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw("/"),
                 Span::styled(
                     "Down",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(self.theme.accent)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to navigate, "),
                 Span::styled(
                     "Enter",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(self.theme.action)
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to select, "),
+                Span::styled(
+                    "/",
+                    Style::default()
+                        .fg(self.theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" to filter, "),
+                Span::styled(
+                    "?",
+                    Style::default()
+                        .fg(self.theme.info)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(" for help, "),
                 Span::styled(
                     "q",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+                    Style::default()
+                        .fg(self.theme.danger)
+                        .add_modifier(Modifier::BOLD),
                 ),
                 Span::raw(" to quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(self.theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
             f.render_widget(help_paragraph, chunks[2]);
+
+            if let Some(scroll) = self.help_scroll {
+                render_help(f, &self.theme, &self.help_entries(), scroll);
+            }
+
+            if let Some(error_message) = &self.connection_error_message {
+                render_error_dialog(f, &self.theme, error_message);
+            }
         })?;
 
         Ok(())
@@ -374,16 +615,40 @@ impl UIRenderer for DatabaseClientUI {
         &mut self,
         terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     ) -> io::Result<()> {
-        let tables = PostgresUI::fetch_tables(self)
-            .await
-            .unwrap_or_else(|_| vec![]);
+        let mut connection_label = self.connection_label();
+        if self.sessions.len() > 1 {
+            connection_label = format!(
+                "[{}/{}] {}",
+                self.active_session() + 1,
+                self.sessions.len(),
+                connection_label
+            );
+        }
+        let mut result_viewport_rows = self.result_viewport_rows;
+        let mut result_column_offset = self.result_column_offset;
+        let mut cell_detail_scroll = (self.cell_detail_scroll, self.cell_detail_hscroll);
+        let mut result_scroll = self.result_scroll;
+        let mut panel_areas = PanelAreas::default();
+        let mut tables_list_offset = self.tables_list_offset;
+        let mut editor_scroll = self.editor_scroll;
 
         terminal.draw(|f| {
+            if render_too_small(f, &self.theme) {
+                return;
+            }
+
             let size = f.area();
 
             let chunks = Layout::default()
                 .direction(Direction::Vertical)
-                .constraints([Constraint::Percentage(95), Constraint::Percentage(5)].as_ref())
+                .constraints(
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(1),
+                        Constraint::Percentage(5),
+                    ]
+                    .as_ref(),
+                )
                 .split(size);
 
             let main_chunks = Layout::default()
@@ -396,107 +661,388 @@ impl UIRenderer for DatabaseClientUI {
                 .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
                 .split(main_chunks[1]);
 
+            // A multi-statement run logs each statement under its result,
+            // followed by any notices the server sent.
+            let log_height = |lines: &[String]| {
+                if lines.is_empty() {
+                    0
+                } else {
+                    (lines.len() as u16 + 2).min(10)
+                }
+            };
+            let result_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(log_height(&self.script_log)),
+                    Constraint::Length(log_height(&self.server_messages)),
+                ])
+                .split(right_chunks[1]);
+            let results_area = result_chunks[0];
+            if !self.script_log.is_empty() {
+                let script_log_widget = Paragraph::new(self.script_log.join("\n"))
+                    .block(Block::default().title("Script").borders(Borders::ALL))
+                    .wrap(Wrap { trim: false });
+                f.render_widget(script_log_widget, result_chunks[1]);
+            }
+            if !self.server_messages.is_empty() {
+                let messages_widget = Paragraph::new(self.server_messages.join("\n"))
+                    .block(Block::default().title("Messages").borders(Borders::ALL))
+                    .wrap(Wrap { trim: false });
+                f.render_widget(messages_widget, result_chunks[2]);
+            }
+
             let mut table_list: Vec<ListItem> = Vec::new();
+            // Line of the selected table, which shifts when a table above it is expanded.
+            let mut selected_line = None;
+
+            if let Some(db_name) = &self.current_database {
+                table_list.push(
+                    ListItem::new(format!("▾ {}", db_name)).style(
+                        Style::default()
+                            .fg(self.theme.info)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+            }
+
+            // Postgres tables are listed under a header for their schema, and
+            // views and the like under one for their kind.
+            let mut previous = None;
+            for (i, table) in self.visible_tables().into_iter().enumerate() {
+                if let Some(header) = self.list_header(previous, table) {
+                    table_list.push(
+                        ListItem::new(format!("  {}", header))
+                            .style(Style::default().fg(self.theme.info)),
+                    );
+                }
+                previous = Some(table.as_str());
+                let table_group = self.table_group(table);
+                let kind = self.object_kind(table);
+                let indent = if table_group.is_some() || kind.is_some() {
+                    "  "
+                } else {
+                    ""
+                };
+                let shown = match table_group {
+                    Some(_) => sql::split_qualified(table).1,
+                    None => table.as_str(),
+                };
 
-            for (i, table) in tables.iter().enumerate() {
                 let style = if i == self.selected_table {
-                    Style::default().bg(Color::Yellow).fg(Color::Black)
+                    self.theme.selected()
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 };
 
-                table_list.push(ListItem::new(table.to_string()).style(style));
-
-                if let Some(expanded_idx) = self.expanded_table {
-                    if expanded_idx == i {
-                        if let Some(schema) = self.table_schemas.get(table) {
-                            for column in &schema.columns {
-                                let column_info = format!(
-                                    "  ├─ {}: {} (Nullable: {}, Default: {:?})",
-                                    column.name,
-                                    column.data_type,
-                                    column.is_nullable,
-                                    column.default
-                                );
-                                table_list.push(
-                                    ListItem::new(column_info)
-                                        .style(Style::default().fg(Color::Gray)),
-                                );
-                            }
-                        }
+                let expanded = self.expanded_tables.contains(table);
+                let mut name = vec![
+                    Span::raw(indent),
+                    Span::raw(if expanded { "  ▾ " } else { "  ▸ " }),
+                ];
+                if let Some(kind) = kind {
+                    name.push(Span::styled(
+                        format!("[{}] ", kind.marker()),
+                        Style::default().fg(self.theme.muted),
+                    ));
+                }
+                name.extend(match filter_match(shown, &self.table_filter) {
+                    Some(range) if !range.is_empty() => vec![
+                        Span::raw(&shown[..range.start]),
+                        Span::styled(
+                            &shown[range.clone()],
+                            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                        ),
+                        Span::raw(&shown[range.end..]),
+                    ],
+                    _ => vec![Span::raw(shown)],
+                });
+                if let Some(count) = self.table_row_counts.get(table.as_str()) {
+                    name.push(Span::raw(format!(" ({})", format_count(*count))));
+                }
+                if i == self.selected_table {
+                    selected_line = Some(table_list.len());
+                }
+                table_list.push(ListItem::new(Line::from(name)).style(style));
+
+                if let Some(schema) = self.table_schemas.get(table).filter(|_| expanded) {
+                    for column in &schema.columns {
+                        let column_info =
+                            format!("{}      ├─ {}", indent, column_summary(column));
+                        table_list.push(
+                            ListItem::new(column_info)
+                                .style(Style::default().fg(self.theme.secondary)),
+                        );
+                    }
+                    for line in constraint_lines(schema) {
+                        table_list.push(
+                            ListItem::new(format!("{}      {}", indent, line))
+                                .style(Style::default().fg(self.theme.muted)),
+                        );
                     }
                 }
             }
 
             let tables_block = Block::default()
                 .borders(Borders::ALL)
-                .title("Tables")
+                .title({
+                    let mut title = match &self.current_database {
+                        Some(db_name) => format!("Tables ({})", db_name),
+                        None => "Tables".to_string(),
+                    };
+                    if self.editing_table_filter {
+                        title.push_str(&format!(" /{}_", self.table_filter));
+                    } else if !self.table_filter.is_empty() {
+                        title.push_str(&format!(" /{}", self.table_filter));
+                    }
+                    title
+                })
                 .border_style(if let FocusedWidget::TablesList = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                    Style::default().fg(self.theme.accent)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 });
 
+            let mut tables_state = ListState::default()
+                .with_offset(tables_list_offset)
+                .with_selected(selected_line);
             let tables_widget = List::new(table_list)
                 .block(tables_block)
-                .highlight_style(Style::default().bg(Color::Yellow).fg(Color::Black));
+                .highlight_style(self.theme.selected());
 
             let sql_query_block = Block::default()
                 .borders(Borders::ALL)
                 .title("SQL Query")
                 .border_style(if let FocusedWidget::SqlEditor = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                    Style::default().fg(self.theme.accent)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 });
 
-            let sql_query_widget = Paragraph::new(self.sql_editor_content.clone())
+            let mut editor_lines = highlight_sql(&self.sql_editor_content, &self.theme);
+            if let Some(selection) = self.editor_selection() {
+                editor_lines = highlight_selection(editor_lines, selection);
+            }
+            // Inside the borders, scrolled so the cursor's line and column show.
+            let editor_area = right_chunks[0];
+            let (cursor_x, cursor_y) = self.editor_cursor_position();
+            editor_scroll = (
+                follow_cursor(
+                    editor_scroll.0,
+                    cursor_x,
+                    editor_area.width.saturating_sub(2),
+                ),
+                follow_cursor(
+                    editor_scroll.1,
+                    cursor_y,
+                    editor_area.height.saturating_sub(2),
+                ),
+            );
+            // Where the cursor is drawn, kept inside the editor's borders.
+            let editor_cursor = (
+                (editor_area.x + 1 + cursor_x - editor_scroll.0)
+                    .min(editor_area.right().saturating_sub(2)),
+                (editor_area.y + 1 + cursor_y - editor_scroll.1)
+                    .min(editor_area.bottom().saturating_sub(2)),
+            );
+            let sql_query_widget = Paragraph::new(editor_lines)
                 .block(sql_query_block)
-                .style(Style::default().fg(Color::White));
+                .scroll((editor_scroll.1, editor_scroll.0));
+
+            // Borders and the pinned header row take three lines, plus one for the footer.
+            let footer_height = usize::from(self.result_footer.is_some());
+            result_viewport_rows = (results_area.height as usize)
+                .saturating_sub(3 + footer_height)
+                .max(1);
+            let total_rows = self.sql_query_result.len();
+            let scroll = self
+                .result_scroll
+                .min(total_rows.saturating_sub(result_viewport_rows));
+            result_scroll = scroll;
+            panel_areas = PanelAreas {
+                tables: main_chunks[0],
+                editor: right_chunks[0],
+                results: results_area,
+            };
+            let visible_results =
+                &self.sql_query_result[scroll..(scroll + result_viewport_rows).min(total_rows)];
+
+            let all_headers = &self.sql_query_columns;
+            let mut widths = column_widths(
+                &self.sql_query_result,
+                all_headers,
+                MAX_COLUMN_WIDTH,
+                &self.timestamp_columns,
+            );
+            let sorted_column = self.result_sort.as_ref().and_then(|(column, order)| {
+                let index = all_headers.iter().position(|header| header == column)?;
+                Some((index, *order))
+            });
+            // Room for the sort arrow and NULL mark after the header.
+            if let Some((index, _)) = sorted_column {
+                widths[index] = widths[index].saturating_add(3);
+            }
+            // Pinned columns are drawn first; the rest scroll in the space left.
+            let pinned: BTreeSet<usize> = all_headers
+                .iter()
+                .enumerate()
+                .filter(|(_, header)| self.pinned_columns.contains(*header))
+                .map(|(index, _)| index)
+                .collect();
+            let (scroll_widths, pinned_width) = unpinned_widths(&widths, &pinned);
+            let available_width = results_area
+                .width
+                .saturating_sub(2)
+                .saturating_sub(pinned_width);
+            result_column_offset = self
+                .result_column_offset
+                .min(all_headers.len().saturating_sub(1));
+            let mut visible_columns =
+                visible_column_range(&scroll_widths, result_column_offset, available_width);
+            // Scroll right until the focused column is in view.
+            while !pinned.contains(&self.selected_result_column)
+                && self.selected_result_column >= visible_columns.end
+                && visible_columns.end < all_headers.len()
+            {
+                result_column_offset += 1;
+                visible_columns =
+                    visible_column_range(&scroll_widths, result_column_offset, available_width);
+            }
+            let shown = shown_columns(&pinned, visible_columns.clone());
+
+            let mut result_title = if total_rows > 0 && self.sql_query_error.is_none() {
+                let column_count = all_headers.len();
+                let mut title = format!(
+                    "Query Result (rows {}–{} of {}",
+                    scroll + 1,
+                    scroll + visible_results.len(),
+                    total_rows
+                );
+                if shown.len() < column_count {
+                    title.push_str(&format!(
+                        ", cols {}–{} of {}",
+                        visible_columns.start + 1,
+                        visible_columns.end,
+                        column_count
+                    ));
+                    if !pinned.is_empty() {
+                        title.push_str(&format!(", {} pinned", pinned.len()));
+                    }
+                }
+                title.push(')');
+                title
+            } else {
+                "Query Result".to_string()
+            };
+            if self.result_from_cache {
+                result_title.push_str(" [cached]");
+            }
 
             let sql_result_block = Block::default()
                 .borders(Borders::ALL)
-                .title("Query Result")
-                .border_style(if let FocusedWidget::_QueryResult = self.current_focus {
-                    Style::default().fg(Color::Yellow)
+                .title(result_title)
+                .border_style(if let FocusedWidget::QueryResult = self.current_focus {
+                    Style::default().fg(self.theme.accent)
                 } else {
-                    Style::default().fg(Color::White)
+                    Style::default().fg(self.theme.text)
                 });
 
             if let Some(error) = &self.sql_query_error {
                 let error_widget = Paragraph::new(format!("Error: {}", error))
                     .block(sql_result_block)
-                    .style(Style::default().fg(Color::Red));
+                    .style(Style::default().fg(self.theme.danger))
+                    .wrap(Wrap { trim: false });
 
-                f.render_widget(tables_widget, main_chunks[0]);
+                f.render_stateful_widget(tables_widget, main_chunks[0], &mut tables_state);
                 f.render_widget(sql_query_widget, right_chunks[0]);
-                f.render_widget(error_widget, right_chunks[1]);
+                f.render_widget(error_widget, results_area);
             } else if !self.sql_query_result.is_empty() {
-                let headers: Vec<String> = self.sql_query_result[0].keys().cloned().collect();
-                let rows: Vec<Row> = self
-                    .sql_query_result
+                let headers: Vec<String> =
+                    shown.iter().map(|&index| all_headers[index].clone()).collect();
+                let header_row: Vec<String> = headers
+                    .iter()
+                    .enumerate()
+                    .map(|(j, header)| match sorted_column {
+                        Some((index, order)) if index == shown[j] => {
+                            format!(
+                                "{} {}",
+                                header,
+                                sort_indicator(order, self.result_nulls_last)
+                            )
+                        }
+                        _ => header.clone(),
+                    })
+                    .collect();
+                let focused_cell = (self.current_focus == FocusedWidget::QueryResult)
+                    .then_some((self.selected_result_row, self.selected_result_column));
+                let rows: Vec<Row> = visible_results
                     .iter()
-                    .map(|result| {
-                        let cells: Vec<String> = headers
+                    .enumerate()
+                    .map(|(i, result)| {
+                        let cells: Vec<Cell> = headers
                             .iter()
-                            .map(|header| {
-                                result
-                                    .get(header)
-                                    .map_or("NULL".to_string(), |v| v.to_string())
+                            .enumerate()
+                            .map(|(j, header)| {
+                                let cell =
+                                    result_cell(result, header, &self.theme, &self.timestamp_columns);
+                                if focused_cell == Some((scroll + i, shown[j])) {
+                                    cell.style(self.theme.selected())
+                                } else {
+                                    cell
+                                }
                             })
                             .collect();
                         Row::new(cells)
                     })
                     .collect();
 
-                let sql_result_widget =
-                    Table::new(rows, headers.iter().map(|_| Constraint::Percentage(25)))
-                        .header(Row::new(headers).style(Style::default().fg(Color::Yellow)))
-                        .block(sql_result_block);
+                // The footer cells hold only values, so they fit their columns;
+                // which aggregate they are is named on the bottom border.
+                let footer = self.result_footer.map(|aggregate| {
+                    let cells: Vec<String> = headers
+                        .iter()
+                        .map(|header| {
+                            aggregate_column(visible_results, header, aggregate).unwrap_or_default()
+                        })
+                        .collect();
+                    Row::new(cells).style(
+                        Style::default()
+                            .fg(self.theme.info)
+                            .add_modifier(Modifier::BOLD | Modifier::ITALIC),
+                    )
+                });
+
+                // The last visible column takes up any remaining space.
+                let visible_widths: Vec<u16> = shown.iter().map(|&index| widths[index]).collect();
+                let constraints: Vec<Constraint> = visible_widths
+                    .iter()
+                    .enumerate()
+                    .map(|(i, width)| {
+                        if i + 1 == visible_widths.len() {
+                            Constraint::Min(*width)
+                        } else {
+                            Constraint::Length(*width)
+                        }
+                    })
+                    .collect();
+
+                let sql_result_block = match self.result_footer {
+                    Some(aggregate) => sql_result_block.title_bottom(
+                        Line::from(format!(" {} ", aggregate.as_str()))
+                            .style(Style::default().fg(self.theme.info)),
+                    ),
+                    None => sql_result_block,
+                };
+                let mut sql_result_widget = Table::new(rows, constraints)
+                    .header(Row::new(header_row).style(Style::default().fg(self.theme.accent)))
+                    .block(sql_result_block);
+                if let Some(footer) = footer {
+                    sql_result_widget = sql_result_widget.footer(footer);
+                }
 
-                f.render_widget(tables_widget, main_chunks[0]);
+                f.render_stateful_widget(tables_widget, main_chunks[0], &mut tables_state);
                 f.render_widget(sql_query_widget, right_chunks[0]);
-                f.render_widget(sql_result_widget, right_chunks[1]);
+                f.render_widget(sql_result_widget, results_area);
             } else {
                 let result_message = self
                     .sql_query_success_message
@@ -504,103 +1050,726 @@ impl UIRenderer for DatabaseClientUI {
                     .unwrap_or_else(|| "No results".to_string());
                 let result_widget = Paragraph::new(result_message).block(sql_result_block);
 
-                f.render_widget(tables_widget, main_chunks[0]);
+                f.render_stateful_widget(tables_widget, main_chunks[0], &mut tables_state);
                 f.render_widget(sql_query_widget, right_chunks[0]);
-                f.render_widget(result_widget, right_chunks[1]);
+                f.render_widget(result_widget, results_area);
             }
 
+            tables_list_offset = tables_state.offset();
+
             if let FocusedWidget::SqlEditor = self.current_focus {
-                let editor_lines: Vec<&str> = self.sql_editor_content.split('\n').collect();
+                f.set_cursor_position(editor_cursor);
+            }
 
-                let cursor_x = editor_lines.last().map_or(0, |line| line.len()) as u16;
-                let cursor_y = editor_lines.len() as u16 - 1;
+            if let Some(completion) = &self.completion {
+                render_completion_popup(
+                    f,
+                    &self.theme,
+                    completion,
+                    editor_cursor.0,
+                    editor_cursor.1 + 1,
+                );
+            }
+
+            if let Some(selected) = self.database_switcher {
+                let db_list: Vec<ListItem> = self
+                    .databases
+                    .iter()
+                    .enumerate()
+                    .map(|(i, db)| {
+                        let marker = if Some(db) == self.current_database.as_ref() {
+                            "* "
+                        } else {
+                            "  "
+                        };
+                        let item = ListItem::new(format!("{}{}", marker, db));
+                        if i == selected {
+                            item.style(
+                                Style::default()
+                                    .bg(self.theme.selection)
+                                    .fg(self.theme.selection_text)
+                                    .add_modifier(Modifier::BOLD),
+                            )
+                        } else {
+                            item.style(Style::default().fg(self.theme.text))
+                        }
+                    })
+                    .collect();
 
-                let adjusted_cursor_y = right_chunks[0].y + cursor_y + 1;
+                let switcher_block = Block::default()
+                    .title("Switch Database")
+                    .borders(Borders::ALL)
+                    .title_alignment(Alignment::Center)
+                    .border_style(Style::default().fg(self.theme.accent));
 
-                f.set_cursor_position((right_chunks[0].x + cursor_x + 1, adjusted_cursor_y));
+                let switcher_area = centered_rect(40, chunks[0]);
+                f.render_widget(Clear, switcher_area);
+                f.render_widget(List::new(db_list).block(switcher_block), switcher_area);
             }
 
-            let help_message = vec![Line::from(vec![
-                Span::styled(
-                    "Tab",
+            if let Some(selected) = self.session_switcher {
+                let active = self.active_session();
+                let mut labels = self.session_labels();
+                labels.push("+ New connection".to_string());
+                let session_list: Vec<ListItem> = labels
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, label)| {
+                        let marker = if i == active { "* " } else { "  " };
+                        let item = ListItem::new(format!("{}{}", marker, label));
+                        if i == selected {
+                            item.style(self.theme.selected().add_modifier(Modifier::BOLD))
+                        } else {
+                            item.style(Style::default().fg(self.theme.text))
+                        }
+                    })
+                    .collect();
+
+                let switcher_block = Block::default()
+                    .title("Connections (Enter to switch, Delete to close)")
+                    .borders(Borders::ALL)
+                    .title_alignment(Alignment::Center)
+                    .border_style(Style::default().fg(self.theme.accent));
+
+                let switcher_area = centered_rect(60, chunks[0]);
+                f.render_widget(Clear, switcher_area);
+                f.render_widget(List::new(session_list).block(switcher_block), switcher_area);
+            }
+
+            if let Some(list) = &self.server_sessions {
+                let session_list: Vec<ListItem> = if list.sessions.is_empty() {
+                    vec![ListItem::new("No client sessions.")
+                        .style(Style::default().fg(self.theme.text))]
+                } else {
+                    list.sessions
+                        .iter()
+                        .enumerate()
+                        .map(|(i, session)| {
+                            let item = ListItem::new(session.line());
+                            if i == list.selected {
+                                item.style(self.theme.selected().add_modifier(Modifier::BOLD))
+                            } else {
+                                item.style(Style::default().fg(self.theme.text))
+                            }
+                        })
+                        .collect()
+                };
+
+                let sessions_block = Block::default()
+                    .title("Server sessions (x to terminate, r to refresh, Esc to close)")
+                    .borders(Borders::ALL)
+                    .title_alignment(Alignment::Center)
+                    .border_style(Style::default().fg(self.theme.accent));
+
+                let sessions_area = centered_rect(90, chunks[0]);
+                let mut state = ListState::default().with_selected(Some(list.selected));
+                f.render_widget(Clear, sessions_area);
+                f.render_stateful_widget(
+                    List::new(session_list).block(sessions_block),
+                    sessions_area,
+                    &mut state,
+                );
+
+                if let Some(session) = list.selected_session().filter(|_| list.confirming) {
+                    let message = format!(
+                        "Terminate session {} of {} on {}?\n\n{}\n\n(y/n)",
+                        session.id, session.user, session.database, session.query
+                    );
+                    render_dialog(f, &self.theme, "Confirm", &message, self.theme.danger);
+                }
+            }
+
+            if let Some(detail) = &self.cell_detail {
+                let header = match &self.cell_detail_table {
+                    Some(table) => format!("DDL of {}", table),
+                    None => self
+                        .sql_query_columns
+                        .get(self.selected_result_column)
+                        .map_or("Value", String::as_str)
+                        .to_string(),
+                };
+                let options = CellDetailOptions {
+                    wrap: self.cell_detail_wrap,
+                    line_numbers: self.cell_detail_line_numbers,
+                };
+                cell_detail_scroll = render_cell_detail(
+                    f,
+                    &self.theme,
+                    &header,
+                    detail,
+                    options,
+                    cell_detail_scroll,
+                );
+            }
+
+            if let Some(view) = &self.schema_view {
+                if let Some(schema) = self.table_schemas.get(&view.table) {
+                    render_schema_view(f, &self.theme, &view.table, schema, view.scroll);
+                }
+            }
+
+            if let Some(view) = &self.connection_info {
+                render_connection_info(f, &self.theme, &connection_label, view);
+            }
+
+            if let Some(prompt) = &self.insert_export {
+                let target = match prompt.target {
+                    InsertTarget::File => "a .sql file",
+                    InsertTarget::Editor => "the SQL editor",
+                };
+                let message = format!(
+                    "Table: {}_\nWrite to: {} (Tab to change)\n\nEnter to write {} rows, Esc to cancel",
+                    prompt.table,
+                    target,
+                    self.sql_query_result.len()
+                );
+                render_dialog(f, &self.theme, "INSERT statements", &message, self.theme.accent);
+            }
+
+            if let Some(call) = &self.routine_call {
+                let mut message = String::new();
+                for (i, (argument, value)) in
+                    call.routine.inputs().into_iter().zip(&call.values).enumerate()
+                {
+                    let name = if argument.name.is_empty() {
+                        format!("${}", i + 1)
+                    } else {
+                        argument.name.clone()
+                    };
+                    let (marker, cursor) = if i == call.field {
+                        ("> ", "_")
+                    } else {
+                        ("  ", "")
+                    };
+                    message.push_str(&format!(
+                        "{}{} ({}): {}{}\n",
+                        marker, name, argument.data_type, value, cursor
+                    ));
+                }
+                message.push_str(
+                    "\nEmpty values pass NULL. Tab to move, Enter to run, Esc to cancel",
+                );
+                let title = format!("Call {}", call.routine.name);
+                render_dialog(f, &self.theme, &title, &message, self.theme.accent);
+            }
+
+            if let Some(statement) = &self.pending_destructive_query {
+                let message = format!(
+                    "This statement may delete or overwrite data:\n\n{}\n\nRun the query? (y/n)",
+                    statement
+                );
+                render_dialog(f, &self.theme, "Confirm", &message, self.theme.accent);
+            }
+
+            let mut status_line = Vec::new();
+            if self.connection_input.read_only {
+                status_line.push(Span::styled(
+                    " READ ONLY ",
                     Style::default()
-                        .fg(Color::Yellow)
+                        .fg(self.theme.selection_text)
+                        .bg(self.theme.danger)
                         .add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(" - to navigate, "),
-                Span::styled(
-                    "F5",
+                ));
+                status_line.push(Span::raw(" "));
+            }
+            if self.transaction_active {
+                status_line.push(Span::styled(
+                    " TX active ",
                     Style::default()
-                        .fg(Color::Green)
+                        .fg(self.theme.selection_text)
+                        .bg(self.theme.selection)
                         .add_modifier(Modifier::BOLD),
-                ),
-                Span::raw(" or "),
+                ));
+                status_line.push(Span::raw(" "));
+            }
+            if self.editing_result_filter || !self.result_filter.is_empty() {
+                let cursor = if self.editing_result_filter { "_" } else { "" };
+                status_line.push(Span::styled(
+                    format!(
+                        "/{}{} showing {} of {} rows",
+                        self.result_filter,
+                        cursor,
+                        self.sql_query_result.len(),
+                        self.unfiltered_result
+                            .as_ref()
+                            .map_or(self.sql_query_result.len(), Vec::len)
+                    ),
+                    Style::default().fg(self.theme.accent),
+                ));
+                status_line.push(Span::raw("  "));
+            }
+            if let Some(status) = &self.status_message {
+                status_line.push(Span::styled(
+                    status.clone(),
+                    Style::default().fg(self.theme.info),
+                ));
+            }
+            let status_chunks = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Min(0),
+                    Constraint::Length(connection_label.chars().count() as u16 + 1),
+                ])
+                .split(chunks[1]);
+            f.render_widget(Paragraph::new(Line::from(status_line)), status_chunks[0]);
+            let connection_widget = Paragraph::new(connection_label.clone())
+                .style(Style::default().fg(self.theme.label))
+                .alignment(Alignment::Right);
+            f.render_widget(connection_widget, status_chunks[1]);
+
+            let key = |label: String, color: Color| {
                 Span::styled(
-                    "Ctrl+E",
-                    Style::default()
-                        .fg(Color::Green)
-                        .add_modifier(Modifier::BOLD),
-                ),
+                    label,
+                    Style::default().fg(color).add_modifier(Modifier::BOLD),
+                )
+            };
+            let label = |action| self.keymap.label(action);
+            let help_message = vec![Line::from(vec![
+                key(label(Action::CycleFocus), self.theme.accent),
+                Span::raw(" - to navigate, "),
+                key(label(Action::Execute), self.theme.action),
                 Span::raw(" - to execute SQL query, "),
-                Span::styled(
-                    "F1",
-                    Style::default()
-                        .fg(Color::Cyan)
-                        .add_modifier(Modifier::BOLD),
+                key(label(Action::ExecuteFresh), self.theme.action),
+                Span::raw(" - to run it bypassing the result cache, "),
+                key(label(Action::Explain), self.theme.action),
+                Span::raw(" - to explain it, "),
+                key(label(Action::OpenInEditor), self.theme.action),
+                Span::raw(" - to edit it in $EDITOR, "),
+                key(
+                    format!(
+                        "{}/{}/{}",
+                        label(Action::BeginTransaction),
+                        label(Action::CommitTransaction),
+                        label(Action::RollbackTransaction)
+                    ),
+                    self.theme.action,
                 ),
+                Span::raw(" - to begin/commit/roll back a transaction, "),
+                key(label(Action::DatabaseSelection), self.theme.info),
                 Span::raw(" - to return to database selection, "),
-                Span::styled(
-                    "Esc",
-                    Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
-                ),
+                key(label(Action::SwitchDatabase), self.theme.info),
+                Span::raw(" - to switch database, "),
+                key(label(Action::SwitchConnection), self.theme.info),
+                Span::raw(" - to switch connection, "),
+                key(label(Action::ServerSessions), self.theme.info),
+                Span::raw(" - to list server sessions, "),
+                key(label(Action::ConnectionInfo), self.theme.info),
+                Span::raw(" - to describe the connection, "),
+                key(label(Action::ToggleTotals), self.theme.info),
+                Span::raw(" - to toggle result totals, "),
+                key(label(Action::ExportCsv), self.theme.info),
+                Span::raw(" / "),
+                key(label(Action::ExportJson), self.theme.info),
+                Span::raw(" / "),
+                key(label(Action::ExportInserts), self.theme.info),
+                Span::raw(" / "),
+                key(label(Action::ExportMarkdown), self.theme.info),
+                Span::raw(" - to export results as CSV / JSON / INSERTs / Markdown, "),
+                key(label(Action::CopyCell), self.theme.info),
+                Span::raw(" / "),
+                key(label(Action::CopyRow), self.theme.info),
+                Span::raw(" - to copy the cell / row, "),
+                key(label(Action::SortResults), self.theme.info),
+                Span::raw(" - to sort by a column, "),
+                key(label(Action::ToggleNullsOrder), self.theme.info),
+                Span::raw(" - to sort NULLs first / last, "),
+                key(label(Action::PinColumn), self.theme.info),
+                Span::raw(" - to pin a column, "),
+                key(label(Action::Filter), self.theme.info),
+                Span::raw(" - to filter tables / rows, "),
+                key(label(Action::Refresh), self.theme.info),
+                Span::raw(" - to refresh tables, "),
+                key(label(Action::Preview), self.theme.info),
+                Span::raw(" - to preview a table, "),
+                key(label(Action::ShowDdl), self.theme.info),
+                Span::raw(" - to show a table's DDL, "),
+                key(label(Action::DescribeTable), self.theme.info),
+                Span::raw(" - to describe a table, "),
+                key(label(Action::ToggleObjects), self.theme.info),
+                Span::raw(" - to list views / sequences / routines, "),
+                key(label(Action::CallRoutine), self.theme.info),
+                Span::raw(" - to call a routine, "),
+                key(label(Action::PreviousPage), self.theme.info),
+                Span::raw(" / "),
+                key(label(Action::NextPage), self.theme.info),
+                Span::raw(" - to change page, "),
+                key(label(Action::Help), self.theme.info),
+                Span::raw(" - to list all keys, "),
+                key(label(Action::Quit), self.theme.danger),
                 Span::raw(" - to quit"),
             ])];
 
             let help_paragraph = Paragraph::new(help_message)
-                .style(Style::default().fg(Color::White))
+                .style(Style::default().fg(self.theme.text))
                 .alignment(Alignment::Center)
                 .wrap(Wrap { trim: true });
 
-            f.render_widget(help_paragraph, chunks[1]);
+            f.render_widget(help_paragraph, chunks[2]);
+
+            if let Some(scroll) = self.help_scroll {
+                render_help(f, &self.theme, &self.help_entries(), scroll);
+            }
+
+            if let Some(error_message) = &self.connection_error_message {
+                render_error_dialog(f, &self.theme, error_message);
+            }
         })?;
 
+        self.result_viewport_rows = result_viewport_rows;
+        self.result_column_offset = result_column_offset;
+        (self.cell_detail_scroll, self.cell_detail_hscroll) = cell_detail_scroll;
+        self.result_scroll = result_scroll;
+        self.panel_areas = panel_areas;
+        self.tables_list_offset = tables_list_offset;
+        self.editor_scroll = editor_scroll;
+
         Ok(())
     }
+}
 
-    async fn render_table_schema(
-        &self,
-        terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
-        table_schema: &TableSchema,
-    ) -> io::Result<()> {
-        terminal.draw(|f| {
-            let size = f.area();
+/// Draws `message` in a bordered dialog over the middle of the screen.
+fn render_dialog(f: &mut Frame, theme: &Theme, title: &str, message: &str, color: Color) {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(30),
+                Constraint::Percentage(40),
+                Constraint::Percentage(30),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+    let area = centered_rect(50, vertical[1]);
+
+    let dialog_block = Block::default()
+        .title(title.to_string())
+        .borders(Borders::ALL)
+        .style(Style::default().fg(color))
+        .title_alignment(Alignment::Center);
+
+    let dialog_paragraph = Paragraph::new(message.to_string())
+        .block(dialog_block)
+        .style(Style::default().fg(theme.text))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(Clear, area);
+    f.render_widget(dialog_paragraph, area);
+}
 
-            let block = Block::default()
-                .title(table_schema.table_name.clone())
-                .borders(Borders::ALL);
+/// How the cell detail popup lays out its value.
+#[derive(Clone, Copy)]
+struct CellDetailOptions {
+    wrap: bool,
+    line_numbers: bool,
+}
 
-            let column_list: Vec<ListItem> = table_schema
-                .columns
-                .iter()
-                .map(|col| {
-                    let col_info = format!(
-                        "{}: {} (Nullable: {}, Default: {:?})",
-                        col.name, col.data_type, col.is_nullable, col.default
-                    );
-                    ListItem::new(col_info).style(Style::default().fg(Color::White))
-                })
-                .collect();
+/// Draws the full value of a result cell in a large scrollable popup,
+/// left-aligned so pretty-printed JSON keeps its indentation.
+///
+/// `scroll` is the first row and, when not wrapping, the first column shown.
+/// Returns both clamped so the last page of the value stays filled.
+fn render_cell_detail(
+    f: &mut Frame,
+    theme: &Theme,
+    header: &str,
+    value: &str,
+    options: CellDetailOptions,
+    scroll: (usize, usize),
+) -> (usize, usize) {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(10),
+                Constraint::Percentage(80),
+                Constraint::Percentage(10),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+    let area = centered_rect(80, vertical[1]);
+
+    let number_width = if options.line_numbers {
+        value.lines().count().max(1).to_string().len()
+    } else {
+        0
+    };
+    // The numbers are followed by " │ ".
+    let gutter_width = if options.line_numbers {
+        number_width + 3
+    } else {
+        0
+    };
+    let text_width = area.width.saturating_sub(2 + gutter_width as u16);
+    let lines = detail_rows(value, text_width, options.wrap);
+    let page_height = usize::from(area.height.saturating_sub(2));
+    let (scroll, hscroll) = scroll;
+    let scroll = scroll.min(lines.len().saturating_sub(page_height));
+    let widest = lines
+        .iter()
+        .map(|(_, line)| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let max_hscroll = widest.saturating_sub(usize::from(text_width));
+    let hscroll = if options.wrap {
+        0
+    } else {
+        hscroll.min(max_hscroll)
+    };
+
+    let mut title = format!(
+        "{} (Esc to close, y to copy, w to toggle wrap, n for line numbers",
+        header
+    );
+    if lines.len() > page_height {
+        title.push_str(&format!(
+            ", ↑/↓ to scroll, lines {}–{} of {}",
+            scroll + 1,
+            (scroll + page_height).min(lines.len()),
+            lines.len()
+        ));
+    }
+    if !options.wrap && max_hscroll > 0 {
+        title.push_str(", ←/→ to scroll sideways");
+    }
+    title.push(')');
+
+    let detail_block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title_alignment(Alignment::Center);
+
+    let visible_lines: Vec<Line> = lines
+        .into_iter()
+        .skip(scroll)
+        .take(page_height)
+        .map(|(number, line)| {
+            let text: String = line.chars().skip(hscroll).collect();
+            if !options.line_numbers {
+                return Line::from(text);
+            }
+            let number = number.map_or(String::new(), |number| number.to_string());
+            Line::from(vec![
+                Span::styled(
+                    format!("{:>width$} │ ", number, width = number_width),
+                    Style::default().fg(theme.muted),
+                ),
+                Span::raw(text),
+            ])
+        })
+        .collect();
+    let detail_paragraph = Paragraph::new(visible_lines)
+        .block(detail_block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(Clear, area);
+    f.render_widget(detail_paragraph, area);
+
+    (scroll, hscroll)
+}
+
+/// Draws the key help overlay, listing `entries` from line `scroll` on.
+fn render_help(f: &mut Frame, theme: &Theme, entries: &[(String, &str)], scroll: usize) {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(10),
+                Constraint::Percentage(80),
+                Constraint::Percentage(10),
+            ]
+            .as_ref(),
+        )
+        .split(f.area());
+    let area = centered_rect(70, vertical[1]);
+
+    let key_width = entries
+        .iter()
+        .map(|(keys, _)| keys.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = entries
+        .iter()
+        .skip(scroll)
+        .map(|(keys, description)| {
+            Line::from(vec![
+                Span::styled(
+                    format!("{:<width$}  ", keys, width = key_width),
+                    Style::default()
+                        .fg(theme.accent)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(description.to_string()),
+            ])
+        })
+        .collect();
+
+    let help_block = Block::default()
+        .title("Keys (? or Esc to close, ↑/↓ to scroll)")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title_alignment(Alignment::Center);
+    let help_paragraph = Paragraph::new(lines)
+        .block(help_block)
+        .style(Style::default().fg(theme.text));
+
+    f.render_widget(Clear, area);
+    f.render_widget(help_paragraph, area);
+}
 
-            let columns_widget = List::new(column_list).block(block);
+/// Draws `schema` over the whole screen, from line `scroll` on. Columns are
+/// in the text color and their defaults and comments muted beneath them.
+fn render_schema_view(
+    f: &mut Frame,
+    theme: &Theme,
+    table: &str,
+    schema: &TableSchema,
+    scroll: usize,
+) {
+    let lines: Vec<Line> = schema_view_lines(schema)
+        .into_iter()
+        .skip(scroll)
+        .map(|line| {
+            let color = if line.starts_with(' ') {
+                theme.muted
+            } else {
+                theme.text
+            };
+            Line::styled(line, Style::default().fg(color))
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!("Schema of {} (Esc to close, ↑/↓ to scroll)", table))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title_alignment(Alignment::Center);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    let area = f.area();
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
 
-            f.render_widget(columns_widget, size);
-        })?;
+/// Draws the connection diagnostics over the whole screen, with the
+/// settings muted below the overview.
+fn render_connection_info(f: &mut Frame, theme: &Theme, label: &str, view: &ConnectionInfoView) {
+    let lines: Vec<Line> = view
+        .lines
+        .iter()
+        .skip(view.scroll)
+        .map(|line| {
+            let color = if line.starts_with(' ') {
+                theme.muted
+            } else {
+                theme.text
+            };
+            Line::styled(line.as_str(), Style::default().fg(color))
+        })
+        .collect();
+
+    let block = Block::default()
+        .title(format!(
+            "Connection {} (Esc to close, ↑/↓ to scroll)",
+            label
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent))
+        .title_alignment(Alignment::Center);
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    let area = f.area();
+    f.render_widget(Clear, area);
+    f.render_widget(paragraph, area);
+}
 
-        Ok(())
+/// Draws the completion candidates in a small list just below `(x, y)`,
+/// shifted to stay on screen.
+fn render_completion_popup(f: &mut Frame, theme: &Theme, completion: &Completion, x: u16, y: u16) {
+    const MAX_VISIBLE: usize = 8;
+
+    let screen = f.area();
+    let widest = completion
+        .candidates
+        .iter()
+        .map(|candidate| Span::raw(candidate.as_str()).width())
+        .max()
+        .unwrap_or(0);
+    let width = (widest as u16 + 2).min(screen.width);
+    let height = (completion.candidates.len().min(MAX_VISIBLE) as u16 + 2).min(screen.height);
+    let area = Rect::new(
+        x.min(screen.width.saturating_sub(width)),
+        y.min(screen.height.saturating_sub(height)),
+        width,
+        height,
+    );
+
+    // Keep the selected candidate in the visible window.
+    let first = (completion.selected + 1).saturating_sub(MAX_VISIBLE);
+    let items: Vec<ListItem> = completion
+        .candidates
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MAX_VISIBLE)
+        .map(|(i, candidate)| {
+            let item = ListItem::new(candidate.clone());
+            if i == completion.selected {
+                item.style(theme.selected())
+            } else {
+                item.style(Style::default().fg(theme.text))
+            }
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(theme.accent));
+
+    f.render_widget(Clear, area);
+    f.render_widget(List::new(items).block(block), area);
+}
+
+fn render_error_dialog(f: &mut Frame, theme: &Theme, message: &str) {
+    render_dialog(f, theme, "Error (press any key)", message, theme.danger);
+}
+
+/// Terminals smaller than this get a resize message instead of the screens,
+/// whose panels would be squeezed to nothing.
+const MIN_WIDTH: u16 = 40;
+const MIN_HEIGHT: u16 = 12;
+
+/// Asks for a bigger terminal when the frame is under `MIN_WIDTH`×`MIN_HEIGHT`.
+///
+/// Returns `true` if it did, so the caller skips drawing its screen.
+fn render_too_small(f: &mut Frame, theme: &Theme) -> bool {
+    let area = f.area();
+    if area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT {
+        return false;
     }
+
+    let message = format!(
+        "Terminal too small ({}x{}). Resize it to at least {}x{}.",
+        area.width, area.height, MIN_WIDTH, MIN_HEIGHT
+    );
+    let paragraph = Paragraph::new(message)
+        .style(Style::default().fg(theme.danger))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, area);
+    true
 }
 
+/// The middle `percent_x` percent of `r`'s width, at full height.
 fn centered_rect(percent_x: u16, r: Rect) -> Rect {
+    let percent_x = percent_x.min(100);
     let popup_layout = Layout::default()
         .direction(Direction::Horizontal)
         .constraints(
@@ -615,3 +1784,46 @@ fn centered_rect(percent_x: u16, r: Rect) -> Rect {
 
     popup_layout[1]
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    #[test]
+    fn test_centered_rect_stays_inside_small_areas() {
+        for area in [
+            Rect::new(0, 0, 0, 0),
+            Rect::new(0, 0, 1, 1),
+            Rect::new(5, 2, 10, 3),
+        ] {
+            let centered = centered_rect(50, area);
+            assert!(
+                area.union(centered) == area,
+                "{:?} outside {:?}",
+                centered,
+                area
+            );
+        }
+
+        assert_eq!(centered_rect(50, Rect::new(0, 0, 10, 3)).width, 5);
+        assert_eq!(centered_rect(150, Rect::new(0, 0, 10, 3)).width, 10);
+    }
+
+    #[test]
+    fn test_tiny_terminal_asks_for_resize() {
+        let mut terminal = Terminal::new(TestBackend::new(10, 3)).unwrap();
+        let mut too_small = false;
+        terminal
+            .draw(|f| too_small = render_too_small(f, &Theme::DARK))
+            .unwrap();
+        assert!(too_small);
+
+        let mut terminal = Terminal::new(TestBackend::new(MIN_WIDTH, MIN_HEIGHT)).unwrap();
+        terminal
+            .draw(|f| too_small = render_too_small(f, &Theme::DARK))
+            .unwrap();
+        assert!(!too_small);
+    }
+}
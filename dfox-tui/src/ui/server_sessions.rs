@@ -0,0 +1,82 @@
+use crate::db::ServerSession;
+
+use super::DatabaseClientUI;
+
+/// The server session list, while open.
+pub struct ServerSessionList {
+    pub sessions: Vec<ServerSession>,
+    pub selected: usize,
+    /// Set while the selected session waits for a y/n confirmation to be
+    /// terminated.
+    pub confirming: bool,
+}
+
+impl ServerSessionList {
+    pub fn selected_session(&self) -> Option<&ServerSession> {
+        self.sessions.get(self.selected)
+    }
+}
+
+impl DatabaseClientUI {
+    /// Lists the client sessions on the server, keeping the cursor where it
+    /// was if the list is already open.
+    pub async fn open_server_sessions(&mut self) {
+        match self.fetch_server_sessions().await {
+            Ok(sessions) => {
+                let selected = self.server_sessions.as_ref().map_or(0, |list| {
+                    list.selected.min(sessions.len().saturating_sub(1))
+                });
+                self.server_sessions = Some(ServerSessionList {
+                    sessions,
+                    selected,
+                    confirming: false,
+                });
+            }
+            Err(err) => {
+                self.server_sessions = None;
+                self.sql_query_error = Some(format!("Error listing server sessions: {}", err));
+            }
+        }
+    }
+
+    /// Terminates the selected session, then reloads the list.
+    pub async fn terminate_selected_server_session(&mut self) {
+        let Some(session) = self
+            .server_sessions
+            .as_ref()
+            .and_then(ServerSessionList::selected_session)
+            .cloned()
+        else {
+            return;
+        };
+
+        match self.terminate_server_session(&session.id).await {
+            Ok(()) => {
+                self.status_message = Some(format!("Terminated session {}.", session.id));
+            }
+            Err(err) => {
+                self.sql_query_error =
+                    Some(format!("Error terminating session {}: {}", session.id, err));
+            }
+        }
+        self.open_server_sessions().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ui::sqlite_ui;
+
+    #[tokio::test]
+    async fn test_sqlite_has_no_server_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("x.db")).await;
+
+        ui.open_server_sessions().await;
+        assert!(ui.server_sessions.is_none());
+        assert!(ui
+            .sql_query_error
+            .as_deref()
+            .is_some_and(|err| err.contains("SQLite has no server sessions")));
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::Arc,
+};
+
+use dfox_core::{
+    models::schema::{DbObject, TableSchema},
+    DbManager,
+};
+
+use super::{
+    components::{ConnectionInput, ScreenState},
+    connection::connection_label,
+    DatabaseClientUI, DatabaseType,
+};
+
+/// An open connection that isn't the one shown, with the table view state it
+/// had when it was switched away from.
+pub struct Session {
+    db_manager: Arc<DbManager>,
+    db_type: DatabaseType,
+    connection_input: ConnectionInput,
+    databases: Vec<String>,
+    selected_database: usize,
+    database_filter: String,
+    current_database: Option<String>,
+    tables: Vec<String>,
+    table_row_counts: HashMap<String, u64>,
+    objects: Vec<DbObject>,
+    table_schemas: HashMap<String, TableSchema>,
+    expanded_tables: HashSet<String>,
+    selected_table: usize,
+    table_filter: String,
+    transaction_active: bool,
+}
+
+impl Session {
+    pub fn label(&self) -> String {
+        connection_label(
+            self.db_type,
+            &self.connection_input,
+            self.current_database.as_deref(),
+        )
+    }
+}
+
+impl DatabaseClientUI {
+    /// Index into `sessions` of the connection shown, whose slot is empty.
+    pub fn active_session(&self) -> usize {
+        self.sessions
+            .iter()
+            .position(Option::is_none)
+            .unwrap_or_default()
+    }
+
+    /// Labels of every open connection, in switcher order.
+    pub fn session_labels(&self) -> Vec<String> {
+        self.sessions
+            .iter()
+            .map(|session| match session {
+                Some(session) => session.label(),
+                None => self.connection_label(),
+            })
+            .collect()
+    }
+
+    /// Moves the shown connection's state out of the UI, leaving a fresh
+    /// `DbManager` with nothing connected in its place.
+    fn park_session(&mut self) -> Session {
+        let read_only = self.connection_input.read_only;
+        let mut connection_input = ConnectionInput::new();
+        connection_input.read_only = read_only;
+
+        self.database_fetch = None;
+        Session {
+            db_manager: mem::replace(&mut self.db_manager, Arc::new(DbManager::new())),
+            db_type: self.db_type,
+            connection_input: mem::replace(&mut self.connection_input, connection_input),
+            databases: mem::take(&mut self.databases),
+            selected_database: mem::take(&mut self.selected_database),
+            database_filter: mem::take(&mut self.database_filter),
+            current_database: self.current_database.take(),
+            tables: mem::take(&mut self.tables),
+            table_row_counts: mem::take(&mut self.table_row_counts),
+            objects: mem::take(&mut self.objects),
+            table_schemas: mem::take(&mut self.table_schemas),
+            expanded_tables: mem::take(&mut self.expanded_tables),
+            selected_table: mem::take(&mut self.selected_table),
+            table_filter: mem::take(&mut self.table_filter),
+            transaction_active: mem::take(&mut self.transaction_active),
+        }
+    }
+
+    fn restore_session(&mut self, session: Session) {
+        self.db_manager = session.db_manager;
+        self.db_type = session.db_type;
+        self.connection_input = session.connection_input;
+        self.databases = session.databases;
+        self.selected_database = session.selected_database;
+        self.database_filter = session.database_filter;
+        self.current_database = session.current_database;
+        self.tables = session.tables;
+        self.table_row_counts = session.table_row_counts;
+        self.objects = session.objects;
+        self.table_schemas = session.table_schemas;
+        self.expanded_tables = session.expanded_tables;
+        self.selected_table = session.selected_table;
+        self.table_filter = session.table_filter;
+        self.transaction_active = session.transaction_active;
+        self.clear_query_result();
+        self.schema_view = None;
+        self.current_screen = ScreenState::TableView;
+        self.restore_last_query();
+    }
+
+    /// Shows the connection at `index` in the table view, keeping the one
+    /// shown so far open.
+    pub fn switch_session(&mut self, index: usize) {
+        let active = self.active_session();
+        if index == active || index >= self.sessions.len() {
+            return;
+        }
+        let parked = self.park_session();
+        self.sessions[active] = Some(parked);
+        if let Some(session) = self.sessions[index].take() {
+            self.restore_session(session);
+        }
+    }
+
+    /// Keeps the shown connection open and goes to the database type screen
+    /// to open another one next to it.
+    pub fn open_new_session(&mut self) {
+        let active = self.active_session();
+        let parked = self.park_session();
+        self.sessions[active] = Some(parked);
+        self.sessions.insert(active + 1, None);
+        self.current_screen = ScreenState::DbTypeSelection;
+    }
+
+    /// Gives up on the connection being opened, going back to the one before
+    /// it. Returns `false` when no other connection is open.
+    pub fn abandon_new_session(&mut self) -> bool {
+        if self.sessions.len() < 2 {
+            return false;
+        }
+        let active = self.active_session();
+        self.sessions.remove(active);
+        let previous = active.saturating_sub(1);
+        if let Some(session) = self.sessions[previous].take() {
+            self.restore_session(session);
+        }
+        true
+    }
+
+    /// Closes the connection at `index`, unless it is the one shown. Any
+    /// transaction open on it is rolled back when its pool is dropped.
+    pub fn close_session(&mut self, index: usize) {
+        if self.sessions.get(index).is_some_and(Option::is_some) {
+            self.sessions.remove(index);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::sqlite_ui;
+
+    #[tokio::test]
+    async fn test_each_session_queries_its_own_connection() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("first.db")).await;
+        ui.execute_sql_query("CREATE TABLE first_only (id INTEGER)")
+            .await
+            .unwrap();
+        ui.update_tables().await;
+
+        ui.open_new_session();
+        assert!(matches!(ui.current_screen, ScreenState::DbTypeSelection));
+        assert!(ui.tables.is_empty());
+        ui.db_type = DatabaseType::SQLite;
+        ui.connection_input.file_path =
+            format!("{}?mode=rwc", dir.path().join("second.db").display());
+        ui.connect_from_input().await;
+        ui.execute_sql_query("CREATE TABLE second_only (id INTEGER)")
+            .await
+            .unwrap();
+        ui.update_tables().await;
+
+        assert_eq!(ui.active_session(), 1);
+        assert_eq!(ui.session_labels().len(), 2);
+        assert!(ui.session_labels()[0].contains("first.db"));
+
+        ui.switch_session(0);
+        assert_eq!(ui.tables, vec!["first_only"]);
+        assert!(ui
+            .execute_sql_query("SELECT * FROM first_only")
+            .await
+            .is_ok());
+        assert!(ui
+            .execute_sql_query("SELECT * FROM second_only")
+            .await
+            .is_err());
+
+        ui.switch_session(1);
+        assert_eq!(ui.tables, vec!["second_only"]);
+
+        ui.close_session(0);
+        assert_eq!(ui.session_labels().len(), 1);
+        assert_eq!(ui.active_session(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_switching_back_restores_the_last_query() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("first.db")).await;
+        ui.remember_last_query = true;
+        ui.last_queries
+            .insert(ui.connection_label(), "SELECT 1".to_string());
+
+        ui.open_new_session();
+        ui.db_type = DatabaseType::SQLite;
+        ui.connection_input.file_path =
+            format!("{}?mode=rwc", dir.path().join("second.db").display());
+        ui.connect_from_input().await;
+        assert!(ui.sql_editor_content.is_empty());
+
+        ui.switch_session(0);
+        assert_eq!(ui.sql_editor_content, "SELECT 1");
+        assert_eq!(ui.sql_editor_cursor, "SELECT 1".len());
+
+        ui.sql_editor_content = "SELECT 2".to_string();
+        ui.switch_session(1);
+        ui.switch_session(0);
+        assert_eq!(ui.sql_editor_content, "SELECT 2");
+    }
+
+    #[tokio::test]
+    async fn test_abandoning_a_new_session_returns_to_the_previous_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("only.db")).await;
+        assert!(!ui.abandon_new_session());
+
+        ui.open_new_session();
+        assert!(ui.abandon_new_session());
+        assert_eq!(ui.sessions.len(), 1);
+        assert!(matches!(ui.current_screen, ScreenState::TableView));
+        assert!(ui.session_labels()[0].contains("only.db"));
+        assert!(ui.execute_sql_query("SELECT 1").await.is_ok());
+    }
+}
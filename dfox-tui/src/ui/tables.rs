@@ -0,0 +1,732 @@
+use std::ops::Range;
+
+use dfox_core::{
+    models::schema::{ColumnSchema, ObjectKind, TableSchema},
+    sql,
+};
+
+use super::{DatabaseClientUI, DatabaseType};
+
+/// A table being browsed a page at a time in the Query Result panel.
+pub struct TablePreview {
+    pub table: String,
+    /// Zero-based page index.
+    pub page: usize,
+    /// Set once a page came back short, so there is nothing after it.
+    pub is_last_page: bool,
+}
+
+/// The full-screen schema of a table, opened from the Tables panel.
+pub struct SchemaView {
+    pub table: String,
+    /// First line shown.
+    pub scroll: usize,
+}
+
+/// `SELECT *` over one page of `table`, with the name quoted for `db_type`.
+pub fn preview_query(db_type: DatabaseType, table: &str, page_size: usize, page: usize) -> String {
+    format!(
+        "SELECT * FROM {} LIMIT {} OFFSET {}",
+        sql::quote_table(&db_type.db_type(), table),
+        page_size,
+        page * page_size
+    )
+}
+
+/// Byte range of the first case-insensitive occurrence of `filter` in `name`.
+pub fn filter_match(name: &str, filter: &str) -> Option<Range<usize>> {
+    // ASCII lowercasing keeps byte offsets identical to `name`.
+    let start = name
+        .to_ascii_lowercase()
+        .find(&filter.to_ascii_lowercase())?;
+    Some(start..start + filter.len())
+}
+
+/// Byte offsets of the characters of `name` that spell out `pattern` in
+/// order, ignoring case, or `None` when they don't all occur. Gaps are
+/// allowed, so `ordb` finds `orders_db`.
+pub fn fuzzy_match(name: &str, pattern: &str) -> Option<Vec<usize>> {
+    let mut pattern = pattern.chars().peekable();
+    let mut matched = Vec::new();
+    for (offset, c) in name.char_indices() {
+        let Some(wanted) = pattern.peek() else {
+            break;
+        };
+        if c.to_lowercase().eq(wanted.to_lowercase()) {
+            matched.push(offset);
+            pattern.next();
+        }
+    }
+    pattern.peek().is_none().then_some(matched)
+}
+
+/// Tables whose name contains `filter`, ignoring case; all tables when it is empty.
+pub fn filtered_tables<'a>(tables: &'a [String], filter: &str) -> Vec<&'a String> {
+    tables
+        .iter()
+        .filter(|table| filter_match(table, filter).is_some())
+        .collect()
+}
+
+/// Formats a row count with thousands separators, e.g. `1,203`.
+pub fn format_count(count: u64) -> String {
+    let digits = count.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// One-line description of a column, with a `[PK]` badge on primary-key columns.
+pub fn column_summary(column: &ColumnSchema) -> String {
+    let badge = if column.is_primary_key { " [PK]" } else { "" };
+    format!(
+        "{}{}: {} (Nullable: {}, Default: {:?})",
+        column.name, badge, column.data_type, column.is_nullable, column.default
+    )
+}
+
+/// Lines of the full-screen schema view: each column with its type and
+/// whole default and comment, then the indexes and foreign keys.
+pub fn schema_view_lines(schema: &TableSchema) -> Vec<String> {
+    let mut lines = Vec::new();
+    for column in &schema.columns {
+        let badge = if column.is_primary_key { " [PK]" } else { "" };
+        let not_null = if column.is_nullable { "" } else { " NOT NULL" };
+        lines.push(format!(
+            "{}{}: {}{}",
+            column.name, badge, column.data_type, not_null
+        ));
+        if let Some(default) = &column.default {
+            lines.push(format!("  Default: {}", default));
+        }
+        if let Some(comment) = &column.comment {
+            lines.push(format!("  Comment: {}", comment));
+        }
+    }
+
+    let constraints = constraint_lines(schema);
+    if !constraints.is_empty() {
+        lines.push(String::new());
+        lines.extend(constraints);
+    }
+
+    if let Some(definition) = &schema.definition {
+        lines.push(String::new());
+        lines.push("Definition:".to_string());
+        lines.extend(definition.trim().lines().map(|line| format!("  {}", line)));
+    }
+    lines
+}
+
+/// "Indexes" and "Foreign keys" sections listed under a table's columns.
+pub fn constraint_lines(schema: &TableSchema) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if !schema.indexes.is_empty() {
+        lines.push("Indexes:".to_string());
+        for index in &schema.indexes {
+            let unique = if index.is_unique { " UNIQUE" } else { "" };
+            lines.push(format!(
+                "  {} ({}){}",
+                index.name,
+                index.columns.join(", "),
+                unique
+            ));
+        }
+    }
+
+    if !schema.foreign_keys.is_empty() {
+        lines.push("Foreign keys:".to_string());
+        for foreign_key in &schema.foreign_keys {
+            lines.push(format!(
+                "  {} ({}) -> {} ({})",
+                foreign_key.name,
+                foreign_key.columns.join(", "),
+                foreign_key.referenced_table,
+                foreign_key.referenced_columns.join(", ")
+            ));
+        }
+    }
+
+    lines
+}
+
+impl DatabaseClientUI {
+    /// Tables shown in the Tables panel, followed by the other objects while
+    /// they are listed; `selected_table` indexes into this list.
+    pub fn visible_tables(&self) -> Vec<&String> {
+        let mut tables = filtered_tables(&self.tables, &self.table_filter);
+        tables.extend(
+            self.objects
+                .iter()
+                .map(|object| &object.name)
+                .filter(|name| filter_match(name, &self.table_filter).is_some()),
+        );
+        tables
+    }
+
+    /// What kind of object `name` is, or `None` for a base table.
+    pub fn object_kind(&self, name: &str) -> Option<ObjectKind> {
+        self.objects
+            .iter()
+            .find(|object| object.name == name)
+            .map(|object| object.kind)
+    }
+
+    /// Schema a table is listed under in the Tables panel. Only Postgres
+    /// tables are schema-qualified, so only they are grouped; other objects
+    /// are grouped by kind instead.
+    pub fn table_group<'a>(&self, table: &'a str) -> Option<&'a str> {
+        match self.db_type {
+            DatabaseType::Postgres if self.object_kind(table).is_none() => {
+                sql::split_qualified(table).0
+            }
+            _ => None,
+        }
+    }
+
+    /// Header drawn above `table` in the Tables panel when it starts a schema
+    /// or a section of views, materialized views or sequences. `previous` is
+    /// the table listed just above it.
+    pub fn list_header(&self, previous: Option<&str>, table: &str) -> Option<String> {
+        let previous_kind = previous.and_then(|previous| self.object_kind(previous));
+        match self.object_kind(table) {
+            Some(kind) if previous_kind != Some(kind) => Some(format!("▪ {}", kind.section())),
+            Some(_) => None,
+            None => {
+                let group = self.table_group(table)?;
+                let previous_group = previous.and_then(|previous| self.table_group(previous));
+                (previous_group != Some(group)).then(|| format!("◆ {}", group))
+            }
+        }
+    }
+
+    /// Lists the views, materialized views and sequences of the current
+    /// database while `show_objects` is set. They are extra, so a failure
+    /// just leaves them out.
+    pub async fn update_objects(&mut self) {
+        self.objects = if self.show_objects {
+            self.fetch_objects().await.unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Shows or hides the views, materialized views and sequences after the
+    /// tables.
+    pub async fn toggle_objects(&mut self) {
+        self.show_objects = !self.show_objects;
+        self.update_objects().await;
+        let visible = self.visible_tables().len();
+        self.selected_table = self.selected_table.min(visible.saturating_sub(1));
+        self.status_message = Some(if self.show_objects {
+            "Listing views, materialized views and sequences.".to_string()
+        } else {
+            "Listing tables only.".to_string()
+        });
+    }
+
+    /// Index into `visible_tables()` of the table drawn on `line` of the Tables
+    /// panel, or `None` for the database line, the headers and the schema
+    /// lines under expanded tables.
+    pub fn table_at_line(&self, mut line: usize) -> Option<usize> {
+        if self.current_database.is_some() {
+            line = line.checked_sub(1)?;
+        }
+        let mut previous = None;
+        for (i, table) in self.visible_tables().into_iter().enumerate() {
+            if self.list_header(previous, table).is_some() {
+                line = line.checked_sub(1)?;
+            }
+            previous = Some(table.as_str());
+            if line == 0 {
+                return Some(i);
+            }
+            line -= 1;
+
+            if self.expanded_tables.contains(table) {
+                if let Some(schema) = self.table_schemas.get(table) {
+                    let schema_lines = schema.columns.len() + constraint_lines(schema).len();
+                    if line < schema_lines {
+                        return None;
+                    }
+                    line -= schema_lines;
+                }
+            }
+        }
+        None
+    }
+
+    /// Drops the cached schema of every table a DDL `statement` mentions, so
+    /// expanding it again describes the table afresh.
+    pub fn invalidate_schemas(&mut self, statement: &str) {
+        let identifiers = sql::ddl_identifiers(&self.db_type.db_type(), statement);
+        let stale: Vec<String> = self
+            .table_schemas
+            .keys()
+            .filter(|table| {
+                let short_name = sql::split_qualified(table).1;
+                identifiers.iter().any(|name| {
+                    name.eq_ignore_ascii_case(table) || name.eq_ignore_ascii_case(short_name)
+                })
+            })
+            .cloned()
+            .collect();
+        for table in stale {
+            self.table_schemas.remove(&table);
+            self.expanded_tables.remove(&table);
+        }
+    }
+
+    pub fn set_table_filter(&mut self, filter: String) {
+        self.table_filter = filter;
+        self.selected_table = 0;
+    }
+
+    /// The selected table, once its schema is in `table_schemas`; describes
+    /// the table only if its schema isn't cached yet. A function or
+    /// procedure has no schema, so its source is shown instead.
+    async fn selected_table_with_schema(&mut self) -> Option<String> {
+        if self.selected_routine().is_some() {
+            self.show_selected_routine().await;
+            return None;
+        }
+        let Some(table) = self
+            .visible_tables()
+            .get(self.selected_table)
+            .map(|table| table.to_string())
+        else {
+            self.status_message = Some("No tables available.".to_string());
+            return None;
+        };
+
+        if !self.table_schemas.contains_key(&table) {
+            match self.describe_table(&table).await {
+                Ok(schema) => {
+                    self.table_schemas.insert(table.clone(), schema);
+                }
+                Err(err) => {
+                    self.connection_error_message =
+                        Some(format!("Error describing table: {}", err));
+                    return None;
+                }
+            }
+        }
+        Some(table)
+    }
+
+    /// Shows the selected table's columns and indexes in the tree.
+    pub async fn expand_selected_table(&mut self) {
+        if let Some(table) = self.selected_table_with_schema().await {
+            self.expanded_tables.insert(table);
+        }
+    }
+
+    /// Shows the selected table's schema over the whole table view, with
+    /// room for long defaults and comments.
+    pub async fn describe_selected_table(&mut self) {
+        if let Some(table) = self.selected_table_with_schema().await {
+            self.schema_view = Some(SchemaView { table, scroll: 0 });
+        }
+    }
+
+    /// Shows the selected table's DDL in the cell detail popup, where `y`
+    /// copies it.
+    pub async fn show_selected_table_ddl(&mut self) {
+        if self.selected_routine().is_some() {
+            self.show_selected_routine().await;
+            return;
+        }
+        let Some(table) = self
+            .visible_tables()
+            .get(self.selected_table)
+            .map(|table| table.to_string())
+        else {
+            self.status_message = Some("No tables available.".to_string());
+            return;
+        };
+
+        match self.table_ddl(&table).await {
+            Ok(ddl) => {
+                self.cell_detail = Some(ddl);
+                self.cell_detail_table = Some(table);
+                self.cell_detail_scroll = 0;
+                self.cell_detail_hscroll = 0;
+            }
+            Err(err) => {
+                self.connection_error_message = Some(format!("Error fetching DDL: {}", err));
+            }
+        }
+    }
+
+    pub fn collapse_selected_table(&mut self) {
+        if let Some(table) = self.visible_tables().get(self.selected_table) {
+            let table = table.to_string();
+            self.expanded_tables.remove(&table);
+        }
+    }
+
+    pub async fn toggle_selected_table(&mut self) {
+        let expanded = self
+            .visible_tables()
+            .get(self.selected_table)
+            .is_some_and(|table| self.expanded_tables.contains(*table));
+        if expanded {
+            self.collapse_selected_table();
+        } else {
+            self.expand_selected_table().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crossterm::event::KeyCode;
+    use dfox_core::{
+        models::schema::{ForeignKeySchema, IndexSchema},
+        DbManager,
+    };
+
+    use super::*;
+    use crate::ui::{sqlite_ui, UIHandler};
+
+    #[test]
+    fn test_filter_is_case_insensitive() {
+        let tables = vec![
+            "users".to_string(),
+            "user_roles".to_string(),
+            "Orders".to_string(),
+        ];
+        assert_eq!(
+            filtered_tables(&tables, "USER"),
+            vec!["users", "user_roles"]
+        );
+        assert_eq!(filtered_tables(&tables, "ord"), vec!["Orders"]);
+        assert_eq!(filtered_tables(&tables, "").len(), 3);
+    }
+
+    #[test]
+    fn test_fuzzy_match_finds_characters_in_order() {
+        assert_eq!(fuzzy_match("orders_db", "ODB"), Some(vec![0, 2, 8]));
+        assert_eq!(fuzzy_match("orders_db", "bdo"), None);
+        assert_eq!(fuzzy_match("orders_db", ""), Some(vec![]));
+        // Offsets are bytes, so they can slice names with non-ASCII letters.
+        assert_eq!(fuzzy_match("bücher", "uc"), None);
+        assert_eq!(fuzzy_match("bücher", "büh"), Some(vec![0, 1, 4]));
+    }
+
+    #[test]
+    fn test_column_summary_marks_primary_key() {
+        let mut column = ColumnSchema {
+            name: "id".to_string(),
+            data_type: "integer".to_string(),
+            is_nullable: false,
+            default: None,
+            is_primary_key: true,
+            comment: None,
+        };
+        assert_eq!(
+            column_summary(&column),
+            "id [PK]: integer (Nullable: false, Default: None)"
+        );
+
+        column.is_primary_key = false;
+        assert_eq!(
+            column_summary(&column),
+            "id: integer (Nullable: false, Default: None)"
+        );
+    }
+
+    #[test]
+    fn test_constraint_lines() {
+        let schema = TableSchema {
+            table_name: "orders".to_string(),
+            columns: Vec::new(),
+            indexes: vec![IndexSchema {
+                name: "orders_pkey".to_string(),
+                columns: vec!["id".to_string()],
+                is_unique: true,
+            }],
+            foreign_keys: vec![ForeignKeySchema {
+                name: "orders_user_fkey".to_string(),
+                columns: vec!["user_id".to_string()],
+                referenced_table: "users".to_string(),
+                referenced_columns: vec!["id".to_string()],
+            }],
+            definition: None,
+        };
+
+        assert_eq!(
+            constraint_lines(&schema),
+            vec![
+                "Indexes:",
+                "  orders_pkey (id) UNIQUE",
+                "Foreign keys:",
+                "  orders_user_fkey (user_id) -> users (id)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_view_lines_show_whole_defaults_and_comments() {
+        let schema = TableSchema {
+            table_name: "events".to_string(),
+            columns: vec![
+                ColumnSchema {
+                    name: "id".to_string(),
+                    data_type: "bigint".to_string(),
+                    is_nullable: false,
+                    default: Some("nextval('events_id_seq'::regclass)".to_string()),
+                    is_primary_key: true,
+                    comment: None,
+                },
+                ColumnSchema {
+                    name: "payload".to_string(),
+                    data_type: "jsonb".to_string(),
+                    is_nullable: true,
+                    default: None,
+                    is_primary_key: false,
+                    comment: Some("Raw webhook body".to_string()),
+                },
+            ],
+            indexes: vec![IndexSchema {
+                name: "events_pkey".to_string(),
+                columns: vec!["id".to_string()],
+                is_unique: true,
+            }],
+            foreign_keys: Vec::new(),
+            definition: None,
+        };
+
+        assert_eq!(
+            schema_view_lines(&schema),
+            vec![
+                "id [PK]: bigint NOT NULL",
+                "  Default: nextval('events_id_seq'::regclass)",
+                "payload: jsonb",
+                "  Comment: Raw webhook body",
+                "",
+                "Indexes:",
+                "  events_pkey (id) UNIQUE",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_describe_opens_scrollable_schema_view() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+        ui.execute_sql_query("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT DEFAULT 'x')")
+            .await
+            .unwrap();
+        ui.tables = vec!["t".to_string()];
+
+        ui.describe_selected_table().await;
+        assert_eq!(
+            ui.schema_view.as_ref().map(|view| view.table.as_str()),
+            Some("t")
+        );
+        // Opening the view doesn't expand the table in the tree.
+        assert!(ui.expanded_tables.is_empty());
+
+        // id, name and its default: the last line is 2.
+        UIHandler::handle_schema_view_input(&mut ui, KeyCode::End).await;
+        assert_eq!(ui.schema_view.as_ref().unwrap().scroll, 2);
+        UIHandler::handle_schema_view_input(&mut ui, KeyCode::Down).await;
+        assert_eq!(ui.schema_view.as_ref().unwrap().scroll, 2);
+
+        UIHandler::handle_schema_view_input(&mut ui, KeyCode::Esc).await;
+        assert!(ui.schema_view.is_none());
+    }
+
+    #[test]
+    fn test_table_at_line_skips_expanded_schema() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.tables = vec!["orders".to_string(), "users".to_string()];
+        ui.expanded_tables.insert("orders".to_string());
+        ui.table_schemas.insert(
+            "orders".to_string(),
+            TableSchema {
+                table_name: "orders".to_string(),
+                columns: vec![ColumnSchema {
+                    name: "id".to_string(),
+                    data_type: "integer".to_string(),
+                    is_nullable: false,
+                    default: None,
+                    is_primary_key: true,
+                    comment: None,
+                }],
+                indexes: Vec::new(),
+                foreign_keys: Vec::new(),
+                definition: None,
+            },
+        );
+
+        assert_eq!(ui.table_at_line(0), Some(0));
+        assert_eq!(ui.table_at_line(1), None);
+        assert_eq!(ui.table_at_line(2), Some(1));
+        assert_eq!(ui.table_at_line(3), None);
+
+        // The database is the root of the tree, above its tables.
+        ui.current_database = Some("shop".to_string());
+        assert_eq!(ui.table_at_line(0), None);
+        assert_eq!(ui.table_at_line(1), Some(0));
+        assert_eq!(ui.table_at_line(3), Some(1));
+    }
+
+    #[test]
+    fn test_table_at_line_skips_postgres_schema_headers() {
+        let mut ui = DatabaseClientUI::new(Arc::new(DbManager::new()));
+        ui.db_type = DatabaseType::Postgres;
+        ui.tables = vec![
+            "analytics.events".to_string(),
+            "public.orders".to_string(),
+            "public.users".to_string(),
+        ];
+
+        // analytics, events, public, orders, users
+        assert_eq!(ui.table_at_line(0), None);
+        assert_eq!(ui.table_at_line(1), Some(0));
+        assert_eq!(ui.table_at_line(2), None);
+        assert_eq!(ui.table_at_line(3), Some(1));
+        assert_eq!(ui.table_at_line(4), Some(2));
+        assert_eq!(
+            preview_query(ui.db_type, "public.users", 10, 0),
+            "SELECT * FROM \"public\".\"users\" LIMIT 10 OFFSET 0"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_ddl_lists_table_then_indexes() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+        ui.execute_sql_query("CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT)")
+            .await
+            .unwrap();
+        ui.execute_sql_query("CREATE INDEX t_name ON t (name)")
+            .await
+            .unwrap();
+        ui.tables = vec!["t".to_string()];
+
+        ui.show_selected_table_ddl().await;
+        assert_eq!(ui.cell_detail_table.as_deref(), Some("t"));
+        assert_eq!(
+            ui.cell_detail.as_deref(),
+            Some(
+                "CREATE TABLE t (id INTEGER PRIMARY KEY, name TEXT);\n\n\
+                 CREATE INDEX t_name ON t (name);"
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_views_are_listed_in_their_own_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("v.db")).await;
+        ui.execute_sql_query("CREATE TABLE users (id INTEGER, active INTEGER)")
+            .await
+            .unwrap();
+        ui.execute_sql_query("CREATE VIEW active_users AS SELECT id FROM users WHERE active")
+            .await
+            .unwrap();
+        ui.update_tables().await;
+        assert_eq!(ui.visible_tables(), vec!["users"]);
+
+        ui.toggle_objects().await;
+        assert_eq!(ui.visible_tables(), vec!["users", "active_users"]);
+        assert_eq!(ui.object_kind("active_users"), Some(ObjectKind::View));
+        assert_eq!(ui.list_header(None, "users"), None);
+        assert_eq!(
+            ui.list_header(Some("users"), "active_users").as_deref(),
+            Some("▪ Views")
+        );
+        // main, users, the Views header, active_users
+        assert_eq!(ui.table_at_line(3), Some(1));
+
+        ui.selected_table = 1;
+        ui.describe_selected_table().await;
+        let schema = &ui.table_schemas["active_users"];
+        assert_eq!(schema.columns.len(), 1);
+        assert_eq!(
+            schema.definition.as_deref(),
+            Some("CREATE VIEW active_users AS SELECT id FROM users WHERE active")
+        );
+        assert!(schema_view_lines(schema).contains(&"Definition:".to_string()));
+
+        ui.toggle_objects().await;
+        assert_eq!(ui.visible_tables(), vec!["users"]);
+        assert_eq!(ui.selected_table, 0);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_odd_table_name_is_quoted_everywhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("t.db")).await;
+        let table = "select \"it's\" here";
+        let quoted = sql::quote_identifier(&ui.db_type.db_type(), table);
+        ui.execute_sql_query(&format!("CREATE TABLE {} (id INTEGER)", quoted))
+            .await
+            .unwrap();
+        ui.execute_sql_query(&format!("INSERT INTO {} VALUES (1)", quoted))
+            .await
+            .unwrap();
+
+        ui.update_tables().await;
+        assert_eq!(ui.tables, vec![table]);
+        assert_eq!(ui.table_row_counts.get(table), Some(&1));
+
+        ui.expand_selected_table().await;
+        assert_eq!(ui.connection_error_message, None);
+        assert_eq!(ui.table_schemas[table].columns[0].name, "id");
+
+        ui.show_selected_table_ddl().await;
+        assert_eq!(ui.connection_error_message, None);
+        assert!(ui.cell_detail.is_some());
+
+        ui.execute_sql_query(&preview_query(ui.db_type, table, 10, 0))
+            .await
+            .unwrap();
+        assert_eq!(ui.sql_query_columns, vec!["id"]);
+    }
+
+    #[test]
+    fn test_preview_query() {
+        assert_eq!(
+            preview_query(DatabaseType::Postgres, "order items", 50, 2),
+            "SELECT * FROM \"order items\" LIMIT 50 OFFSET 100"
+        );
+        assert_eq!(
+            preview_query(DatabaseType::MySQL, "orders", 100, 0),
+            "SELECT * FROM `orders` LIMIT 100 OFFSET 0"
+        );
+        assert_eq!(
+            preview_query(DatabaseType::SQLite, "a\"; DROP TABLE t; --", 10, 0),
+            "SELECT * FROM \"a\"\"; DROP TABLE t; --\" LIMIT 10 OFFSET 0"
+        );
+        assert_eq!(
+            preview_query(DatabaseType::MySQL, "a` b", 10, 0),
+            "SELECT * FROM `a`` b` LIMIT 10 OFFSET 0"
+        );
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1203), "1,203");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
+
+    #[test]
+    fn test_filter_match_range() {
+        assert_eq!(filter_match("user_roles", "ROLE"), Some(5..9));
+        assert_eq!(filter_match("users", "x"), None);
+    }
+}
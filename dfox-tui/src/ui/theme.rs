@@ -0,0 +1,110 @@
+use ratatui::style::{Color, Style};
+use serde::Deserialize;
+
+/// Built-in palettes, picked with `theme = "..."` in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeName {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+}
+
+/// Colors the screens are drawn with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Regular text.
+    pub text: Color,
+    /// Focused borders, titles, table headers and navigation keys.
+    pub accent: Color,
+    /// Background of the selected item, drawn in `selection_text`.
+    pub selection: Color,
+    pub selection_text: Color,
+    /// Keys that run or connect.
+    pub action: Color,
+    /// Status messages and secondary keys.
+    pub info: Color,
+    /// Quit keys, errors and the row limit warning.
+    pub danger: Color,
+    /// NULLs, SQL comments and schema details.
+    pub muted: Color,
+    /// Column lines in the schema tree.
+    pub secondary: Color,
+    /// The connection shown in the status line.
+    pub label: Color,
+    pub keyword: Color,
+    pub string: Color,
+    pub number: Color,
+}
+
+impl Theme {
+    pub const DARK: Theme = Theme {
+        text: Color::White,
+        accent: Color::Yellow,
+        selection: Color::Yellow,
+        selection_text: Color::Black,
+        action: Color::Green,
+        info: Color::Cyan,
+        danger: Color::Red,
+        muted: Color::DarkGray,
+        secondary: Color::Gray,
+        label: Color::Magenta,
+        keyword: Color::Blue,
+        string: Color::Green,
+        number: Color::Magenta,
+    };
+
+    /// For terminals with a light background.
+    pub const LIGHT: Theme = Theme {
+        text: Color::Black,
+        accent: Color::Blue,
+        selection: Color::Blue,
+        selection_text: Color::White,
+        action: Color::Green,
+        info: Color::Magenta,
+        danger: Color::Red,
+        muted: Color::DarkGray,
+        secondary: Color::DarkGray,
+        label: Color::Magenta,
+        keyword: Color::Blue,
+        string: Color::Green,
+        number: Color::Magenta,
+    };
+
+    /// Bright colors only, for terminals that render the dark palette dimly.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        text: Color::White,
+        accent: Color::LightCyan,
+        selection: Color::White,
+        selection_text: Color::Black,
+        action: Color::LightGreen,
+        info: Color::LightCyan,
+        danger: Color::LightRed,
+        muted: Color::Gray,
+        secondary: Color::White,
+        label: Color::LightMagenta,
+        keyword: Color::LightBlue,
+        string: Color::LightGreen,
+        number: Color::LightMagenta,
+    };
+
+    pub fn named(name: ThemeName) -> Theme {
+        match name {
+            ThemeName::Dark => Theme::DARK,
+            ThemeName::Light => Theme::LIGHT,
+            ThemeName::HighContrast => Theme::HIGH_CONTRAST,
+        }
+    }
+
+    /// Style of the selected list item or cell.
+    pub fn selected(&self) -> Style {
+        Style::default().bg(self.selection).fg(self.selection_text)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::DARK
+    }
+}
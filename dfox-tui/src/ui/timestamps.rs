@@ -0,0 +1,214 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, SecondsFormat, Utc};
+use dfox_core::sql;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::{results::cell_text, DatabaseClientUI};
+
+/// How timestamp columns are shown, picked with `timestamps = "..."` in the
+/// config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimestampFormat {
+    /// As the driver returned them.
+    #[default]
+    Raw,
+    /// ISO 8601, with zoned values converted to UTC.
+    Utc,
+    /// ISO 8601, with zoned values converted to the local time zone.
+    Local,
+}
+
+/// The result columns that hold timestamps, and how to show them.
+#[derive(Debug, Default)]
+pub struct TimestampColumns {
+    pub format: TimestampFormat,
+    pub columns: HashSet<String>,
+}
+
+impl TimestampColumns {
+    /// Like [`cell_text`], but with timestamps in `format`.
+    pub fn cell_text(&self, row: &HashMap<String, Value>, header: &str) -> String {
+        let text = cell_text(row, header);
+        if self.columns.contains(header) {
+            if let Some(formatted) = format_timestamp(&text, self.format) {
+                return formatted;
+            }
+        }
+        text
+    }
+}
+
+/// Whether a column's declared type, as in `ColumnSchema::data_type`, holds a
+/// date and time: `timestamp [with|without time zone]`, `timestamptz` or
+/// `datetime`.
+pub fn is_timestamp_type(data_type: &str) -> bool {
+    let data_type = data_type.to_lowercase();
+    data_type.starts_with("timestamp") || data_type.starts_with("datetime")
+}
+
+/// `text` in ISO 8601, or `None` if it isn't a timestamp or `format` is raw.
+///
+/// Values with a UTC offset are converted to UTC or local time. Values
+/// without one have no zone to convert from, so only their layout changes.
+pub fn format_timestamp(text: &str, format: TimestampFormat) -> Option<String> {
+    if format == TimestampFormat::Raw {
+        return None;
+    }
+    if let Some(zoned) = parse_zoned(text) {
+        return Some(match format {
+            TimestampFormat::Local => zoned
+                .with_timezone(&Local)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, false),
+            _ => zoned
+                .with_timezone(&Utc)
+                .to_rfc3339_opts(SecondsFormat::AutoSi, true),
+        });
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|layout| NaiveDateTime::parse_from_str(text, layout).ok())
+        .map(|naive| naive.format("%Y-%m-%dT%H:%M:%S%.f").to_string())
+}
+
+/// The entries of `tables` that `query` names, either by their qualified
+/// name or, for `schema.table` entries, by the table name alone.
+pub fn named_tables(tables: &[String], query: &str) -> Vec<String> {
+    let words: HashSet<String> = query
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .map(str::to_lowercase)
+        .collect();
+    tables
+        .iter()
+        .filter(|table| {
+            words.contains(&table.to_lowercase())
+                || words.contains(&sql::split_qualified(table).1.to_lowercase())
+        })
+        .cloned()
+        .collect()
+}
+
+fn parse_zoned(text: &str) -> Option<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(text)
+        .or_else(|_| DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S%.f%#z"))
+        .ok()
+}
+
+impl DatabaseClientUI {
+    /// Finds the timestamp columns of the result of `query` from the schemas
+    /// of the tables it names, describing any not loaded yet. Skipped while
+    /// timestamps are shown raw.
+    pub async fn detect_timestamp_columns(&mut self, query: &str) {
+        self.timestamp_columns.columns.clear();
+        if self.timestamp_columns.format == TimestampFormat::Raw {
+            return;
+        }
+
+        for table in named_tables(&self.tables, query) {
+            if !self.table_schemas.contains_key(&table) {
+                if let Ok(schema) = self.describe_table(&table).await {
+                    self.table_schemas.insert(table.clone(), schema);
+                }
+            }
+            if let Some(schema) = self.table_schemas.get(&table) {
+                self.timestamp_columns.columns.extend(
+                    schema
+                        .columns
+                        .iter()
+                        .filter(|column| is_timestamp_type(&column.data_type))
+                        .map(|column| column.name.clone()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::sqlite_ui;
+
+    #[test]
+    fn test_format_timestamp() {
+        let utc = TimestampFormat::Utc;
+        assert_eq!(
+            format_timestamp("2024-03-01T23:30:00+02:00", utc).as_deref(),
+            Some("2024-03-01T21:30:00Z")
+        );
+        assert_eq!(
+            format_timestamp("2024-03-01 23:30:00.250-01:00", utc).as_deref(),
+            Some("2024-03-02T00:30:00.250Z")
+        );
+        assert_eq!(
+            format_timestamp("2024-03-01 23:30:00", utc).as_deref(),
+            Some("2024-03-01T23:30:00")
+        );
+        assert_eq!(
+            format_timestamp("2024-03-01 23:30:00", TimestampFormat::Raw),
+            None
+        );
+        assert_eq!(format_timestamp("yesterday", utc), None);
+
+        let local = format_timestamp("2024-03-01T23:30:00+02:00", TimestampFormat::Local).unwrap();
+        assert_eq!(
+            DateTime::parse_from_rfc3339(&local).unwrap(),
+            DateTime::parse_from_rfc3339("2024-03-01T21:30:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_is_timestamp_type() {
+        assert!(is_timestamp_type("timestamp with time zone"));
+        assert!(is_timestamp_type("DATETIME"));
+        assert!(!is_timestamp_type("date"));
+        assert!(!is_timestamp_type("text"));
+    }
+
+    #[test]
+    fn test_named_tables_match_short_names() {
+        let tables = vec![
+            "public.events".to_string(),
+            "audit.events_log".to_string(),
+            "users".to_string(),
+        ];
+        assert_eq!(
+            named_tables(&tables, "SELECT * FROM events e JOIN Users u ON true"),
+            vec!["public.events".to_string(), "users".to_string()]
+        );
+        assert_eq!(
+            named_tables(&tables, "SELECT * FROM audit.events_log"),
+            vec!["audit.events_log".to_string()]
+        );
+        assert!(named_tables(&tables, "SELECT 1").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_only_timestamp_columns_are_reformatted() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ui = sqlite_ui(&dir.path().join("ts.db")).await;
+        ui.execute_sql_query("CREATE TABLE events (note TEXT, at DATETIME)")
+            .await
+            .unwrap();
+        ui.execute_sql_query(
+            "INSERT INTO events VALUES ('2024-01-01 10:00:00', '2024-01-01 10:00:00')",
+        )
+        .await
+        .unwrap();
+        ui.update_tables().await;
+        ui.timestamp_columns.format = TimestampFormat::Utc;
+
+        let query = "SELECT * FROM events";
+        let (rows, _) = ui.execute_sql_query(query).await.unwrap();
+        ui.detect_timestamp_columns(query).await;
+        assert_eq!(
+            ui.timestamp_columns.cell_text(&rows[0], "at"),
+            "2024-01-01T10:00:00"
+        );
+        assert_eq!(
+            ui.timestamp_columns.cell_text(&rows[0], "note"),
+            "2024-01-01 10:00:00"
+        );
+    }
+}